@@ -0,0 +1,138 @@
+//! Types shared by the typed API modules
+//!
+//! Akamai APIs report errors as [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807)
+//! `problem+json` documents. The typed modules turn any non-2xx response into
+//! [`EdgeGridError::Api`] carrying the parsed [`Problem`].
+
+use crate::error::{EdgeGridError, Result};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Akamai delivery network a change is deployed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Network {
+    /// The staging network, for testing before production
+    #[serde(alias = "staging")]
+    Staging,
+    /// The production network
+    #[serde(alias = "production")]
+    Production,
+}
+
+impl Network {
+    /// Lowercase name as used in API paths
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Staging => "staging",
+            Network::Production => "production",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An API error response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    /// HTTP status of the response
+    #[serde(default)]
+    pub status: u16,
+    /// URI identifying the kind of problem
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub problem_type: Option<String>,
+    /// Short summary of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Explanation specific to this occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// URI identifying this occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Any other members, e.g. `errors` or `requestId`
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Problem {
+    /// Parse an error body; bodies that are not `problem+json` become the detail
+    pub fn from_body(status: u16, body: &[u8]) -> Self {
+        let mut problem = serde_json::from_slice::<Problem>(body).unwrap_or_else(|_| Problem {
+            detail: Some(String::from_utf8_lossy(body).trim().to_string())
+                .filter(|detail| !detail.is_empty()),
+            ..Problem::default()
+        });
+        problem.status = status;
+        problem
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP {}", self.status)?;
+        if let Some(title) = &self.title {
+            write!(f, " {}", title)?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize a successful JSON response, or turn the error into a [`Problem`]
+pub(crate) async fn read_json<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let response = check_status(response).await?;
+    Ok(response.json().await?)
+}
+
+/// Pass successful responses through, turning errors into a [`Problem`]
+pub(crate) async fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.bytes().await?;
+    Err(EdgeGridError::Api(Box::new(Problem::from_body(
+        status.as_u16(),
+        &body,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_from_body() {
+        let body = br#"{"type":"https://problems.luna.akamaiapis.net/ccu/v3/rate-limit","title":"Too Many Requests","detail":"Slow down","requestId":"abc"}"#;
+        let problem = Problem::from_body(429, body);
+        assert_eq!(problem.status, 429);
+        assert_eq!(problem.title.as_deref(), Some("Too Many Requests"));
+        assert_eq!(problem.extra["requestId"], "abc");
+        assert_eq!(problem.to_string(), "HTTP 429 Too Many Requests: Slow down");
+
+        let problem = Problem::from_body(502, b"Bad Gateway\n");
+        assert_eq!(problem.detail.as_deref(), Some("Bad Gateway"));
+        assert_eq!(problem.to_string(), "HTTP 502: Bad Gateway");
+    }
+
+    #[test]
+    fn test_network_names() {
+        assert_eq!(Network::Staging.to_string(), "staging");
+        assert_eq!(
+            serde_json::to_string(&Network::Production).unwrap(),
+            "\"PRODUCTION\""
+        );
+        let network: Network = serde_json::from_str("\"staging\"").unwrap();
+        assert_eq!(network, Network::Staging);
+    }
+}
@@ -0,0 +1,1053 @@
+//! Application Security (AppSec v1) API
+//!
+//! Security configurations and their versions, security policies, match
+//! targets, rate policies, custom rules and configuration exports.
+//!
+//! [`ConfigExport`] turns an export into a deterministic JSON document that
+//! can be committed and reviewed. Object keys are sorted, arrays of objects
+//! with an ID are ordered by it, and audit fields such as `createDate` are
+//! dropped. [`ConfigExport::diff`] lists what changed between two exports.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::appsec::AppSec;
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let appsec = AppSec::new(client);
+//!
+//! let live = appsec.export(12345, 7).await?;
+//! let draft = appsec.export(12345, 8).await?;
+//! std::fs::write("waf.json", draft.to_json())?;
+//! for change in live.diff(&draft) {
+//!     println!("{}", change);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json};
+use crate::client::EdgeGridClient;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Keys dropped from exports because they change without the configuration
+/// changing
+pub const VOLATILE_KEYS: &[&str] = &[
+    "createDate",
+    "createdBy",
+    "updateDate",
+    "updatedBy",
+    "lastModifiedDate",
+    "lastModifiedBy",
+];
+
+/// Keys identifying an object within an array, in order of preference
+const ID_KEYS: &[&str] = &["id", "policyId", "targetId", "ruleId", "ratePolicyId"];
+
+/// Application Security API client
+#[derive(Debug, Clone)]
+pub struct AppSec {
+    client: EdgeGridClient,
+}
+
+/// A security configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Configuration {
+    /// Configuration ID
+    pub id: i64,
+    /// Configuration name
+    pub name: String,
+    /// Configuration description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Newest version
+    #[serde(default)]
+    pub latest_version: u32,
+    /// Version active on staging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staging_version: Option<u32>,
+    /// Version active on production
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub production_version: Option<u32>,
+    /// Hostnames protected on production
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub production_hostnames: Vec<String>,
+    /// Members not modelled above, e.g. `targetProduct`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A version of a security configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigVersion {
+    /// Version number
+    pub version: u32,
+    /// Version the version was created from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub based_on: Option<u32>,
+    /// When the version was created
+    #[serde(default)]
+    pub create_date: String,
+    /// Who created the version
+    #[serde(default)]
+    pub created_by: String,
+    /// Activation state on staging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staging: Option<VersionStatus>,
+    /// Activation state on production
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub production: Option<VersionStatus>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Activation state of a version on one network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionStatus {
+    /// `Active`, `Inactive`, `Pending` and so on
+    pub status: String,
+}
+
+/// A security policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityPolicy {
+    /// Policy ID, e.g. `abc1_123456`
+    pub policy_id: String,
+    /// Policy name
+    pub policy_name: String,
+    /// Which protections are switched on
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub policy_security_controls: Map<String, Value>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A security policy to create
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSecurityPolicy {
+    /// Policy name
+    pub policy_name: String,
+    /// Four-character prefix of the policy ID
+    pub policy_prefix: String,
+    /// Start from Akamai's default settings rather than an empty policy
+    pub default_settings: bool,
+    /// Copy an existing policy instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_from_security_policy: Option<String>,
+}
+
+impl NewSecurityPolicy {
+    /// A policy with the default settings
+    pub fn new(policy_name: impl Into<String>, policy_prefix: impl Into<String>) -> Self {
+        Self {
+            policy_name: policy_name.into(),
+            policy_prefix: policy_prefix.into(),
+            default_settings: true,
+            create_from_security_policy: None,
+        }
+    }
+
+    /// Copy the settings of `policy_id`
+    pub fn copy_of(mut self, policy_id: impl Into<String>) -> Self {
+        self.default_settings = false;
+        self.create_from_security_policy = Some(policy_id.into());
+        self
+    }
+}
+
+/// Requests a security policy applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchTarget {
+    /// Match target ID, absent until created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<i64>,
+    /// `website` or `api`
+    #[serde(rename = "type")]
+    pub target_type: String,
+    /// Position in which targets are evaluated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+    /// Policy applied to matching requests
+    pub security_policy: PolicyRef,
+    /// Hostnames to match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hostnames: Vec<String>,
+    /// Paths to match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_paths: Vec<String>,
+    /// Members not modelled above, e.g. `bypassNetworkLists` or `apis`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Reference to a security policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRef {
+    /// Policy ID
+    pub policy_id: String,
+}
+
+/// A rate policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatePolicy {
+    /// Rate policy ID, absent until created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// Rate policy name
+    pub name: String,
+    /// Requests per second averaged over two minutes that trigger the policy
+    pub average_threshold: u32,
+    /// Requests per second over five seconds that trigger the policy
+    pub burst_threshold: u32,
+    /// `path` or `api`
+    pub match_type: String,
+    /// `WAF` or `BOTMAN`
+    #[serde(rename = "type")]
+    pub policy_type: String,
+    /// Members not modelled above, e.g. `clientIdentifier` or `path`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A custom rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomRule {
+    /// Rule ID, absent until created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// Rule name
+    pub name: String,
+    /// Rule description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Tags for reporting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag: Vec<String>,
+    /// Conditions that must all match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Value>,
+    /// Members not modelled above, e.g. `ruleActivated` or `operation`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ConfigurationsResponse {
+    configurations: Vec<Configuration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionsResponse {
+    version_list: Vec<ConfigVersion>,
+}
+
+#[derive(Deserialize)]
+struct PoliciesResponse {
+    policies: Vec<SecurityPolicy>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchTargetsResponse {
+    match_targets: MatchTargetLists,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchTargetLists {
+    #[serde(default)]
+    website_targets: Vec<MatchTarget>,
+    #[serde(default)]
+    api_targets: Vec<MatchTarget>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RatePoliciesResponse {
+    rate_policies: Vec<RatePolicy>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomRulesResponse {
+    custom_rules: Vec<CustomRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewVersionBody {
+    create_from_version: u32,
+    rule_update: bool,
+}
+
+impl AppSec {
+    /// Use `client` for Application Security requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// Security configurations
+    pub async fn configurations(&self) -> Result<Vec<Configuration>> {
+        let response: ConfigurationsResponse =
+            read_json(self.client.get("/appsec/v1/configs").send().await?).await?;
+        Ok(response.configurations)
+    }
+
+    /// A single security configuration
+    pub async fn configuration(&self, config_id: i64) -> Result<Configuration> {
+        read_json(self.client.get(&config_path(config_id)).send().await?).await
+    }
+
+    /// Versions of a configuration
+    pub async fn versions(&self, config_id: i64) -> Result<Vec<ConfigVersion>> {
+        let response = self
+            .client
+            .get(&format!("{}/versions", config_path(config_id)))
+            .query("page", "-1")
+            .send()
+            .await?;
+        let response: VersionsResponse = read_json(response).await?;
+        Ok(response.version_list)
+    }
+
+    /// A single version
+    pub async fn version(&self, config_id: i64, version: u32) -> Result<ConfigVersion> {
+        read_json(
+            self.client
+                .get(&version_path(config_id, version))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    /// Create an editable version from an existing one
+    pub async fn create_version(&self, config_id: i64, from_version: u32) -> Result<ConfigVersion> {
+        let response = self
+            .client
+            .post(&format!("{}/versions", config_path(config_id)))
+            .json(&NewVersionBody {
+                create_from_version: from_version,
+                rule_update: false,
+            })
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Security policies of a version
+    pub async fn security_policies(
+        &self,
+        config_id: i64,
+        version: u32,
+    ) -> Result<Vec<SecurityPolicy>> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/security-policies",
+                version_path(config_id, version)
+            ))
+            .send()
+            .await?;
+        let response: PoliciesResponse = read_json(response).await?;
+        Ok(response.policies)
+    }
+
+    /// A single security policy
+    pub async fn security_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        policy_id: &str,
+    ) -> Result<SecurityPolicy> {
+        let response = self
+            .client
+            .get(&policy_path(config_id, version, policy_id))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a security policy in an editable version
+    pub async fn create_security_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        policy: &NewSecurityPolicy,
+    ) -> Result<SecurityPolicy> {
+        let response = self
+            .client
+            .post(&format!(
+                "{}/security-policies",
+                version_path(config_id, version)
+            ))
+            .json(policy)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Rename a security policy
+    pub async fn rename_security_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        policy_id: &str,
+        policy_name: &str,
+    ) -> Result<SecurityPolicy> {
+        let response = self
+            .client
+            .put(&policy_path(config_id, version, policy_id))
+            .json(&serde_json::json!({ "policyName": policy_name }))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Delete a security policy from an editable version
+    pub async fn delete_security_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        policy_id: &str,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .delete(&policy_path(config_id, version, policy_id))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Website and API match targets of a version, in evaluation order
+    pub async fn match_targets(&self, config_id: i64, version: u32) -> Result<Vec<MatchTarget>> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/match-targets",
+                version_path(config_id, version)
+            ))
+            .send()
+            .await?;
+        let response: MatchTargetsResponse = read_json(response).await?;
+        let mut targets = response.match_targets.website_targets;
+        targets.extend(response.match_targets.api_targets);
+        targets.sort_by_key(|target| target.sequence);
+        Ok(targets)
+    }
+
+    /// Create a match target
+    pub async fn create_match_target(
+        &self,
+        config_id: i64,
+        version: u32,
+        target: &MatchTarget,
+    ) -> Result<MatchTarget> {
+        let response = self
+            .client
+            .post(&format!(
+                "{}/match-targets",
+                version_path(config_id, version)
+            ))
+            .json(target)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Replace a match target
+    pub async fn update_match_target(
+        &self,
+        config_id: i64,
+        version: u32,
+        target_id: i64,
+        target: &MatchTarget,
+    ) -> Result<MatchTarget> {
+        let response = self
+            .client
+            .put(&format!(
+                "{}/match-targets/{}",
+                version_path(config_id, version),
+                target_id
+            ))
+            .json(target)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Delete a match target
+    pub async fn delete_match_target(
+        &self,
+        config_id: i64,
+        version: u32,
+        target_id: i64,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .delete(&format!(
+                "{}/match-targets/{}",
+                version_path(config_id, version),
+                target_id
+            ))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Rate policies of a version
+    pub async fn rate_policies(&self, config_id: i64, version: u32) -> Result<Vec<RatePolicy>> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/rate-policies",
+                version_path(config_id, version)
+            ))
+            .send()
+            .await?;
+        let response: RatePoliciesResponse = read_json(response).await?;
+        Ok(response.rate_policies)
+    }
+
+    /// Create a rate policy
+    pub async fn create_rate_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        policy: &RatePolicy,
+    ) -> Result<RatePolicy> {
+        let response = self
+            .client
+            .post(&format!(
+                "{}/rate-policies",
+                version_path(config_id, version)
+            ))
+            .json(policy)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Replace a rate policy
+    pub async fn update_rate_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        rate_policy_id: i64,
+        policy: &RatePolicy,
+    ) -> Result<RatePolicy> {
+        let response = self
+            .client
+            .put(&format!(
+                "{}/rate-policies/{}",
+                version_path(config_id, version),
+                rate_policy_id
+            ))
+            .json(policy)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Delete a rate policy
+    pub async fn delete_rate_policy(
+        &self,
+        config_id: i64,
+        version: u32,
+        rate_policy_id: i64,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .delete(&format!(
+                "{}/rate-policies/{}",
+                version_path(config_id, version),
+                rate_policy_id
+            ))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Custom rules of a configuration, shared by all of its versions
+    pub async fn custom_rules(&self, config_id: i64) -> Result<Vec<CustomRule>> {
+        let response = self
+            .client
+            .get(&format!("{}/custom-rules", config_path(config_id)))
+            .send()
+            .await?;
+        let response: CustomRulesResponse = read_json(response).await?;
+        Ok(response.custom_rules)
+    }
+
+    /// A single custom rule with its conditions
+    pub async fn custom_rule(&self, config_id: i64, rule_id: i64) -> Result<CustomRule> {
+        let response = self
+            .client
+            .get(&custom_rule_path(config_id, rule_id))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a custom rule
+    pub async fn create_custom_rule(
+        &self,
+        config_id: i64,
+        rule: &CustomRule,
+    ) -> Result<CustomRule> {
+        let response = self
+            .client
+            .post(&format!("{}/custom-rules", config_path(config_id)))
+            .json(rule)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Replace a custom rule
+    pub async fn update_custom_rule(
+        &self,
+        config_id: i64,
+        rule_id: i64,
+        rule: &CustomRule,
+    ) -> Result<CustomRule> {
+        let response = self
+            .client
+            .put(&custom_rule_path(config_id, rule_id))
+            .json(rule)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Delete a custom rule that no policy uses
+    pub async fn delete_custom_rule(&self, config_id: i64, rule_id: i64) -> Result<()> {
+        let response = self
+            .client
+            .delete(&custom_rule_path(config_id, rule_id))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Export a whole configuration version as a deterministic document
+    pub async fn export(&self, config_id: i64, version: u32) -> Result<ConfigExport> {
+        let response = self
+            .client
+            .get(&format!(
+                "/appsec/v1/export/configs/{}/versions/{}",
+                config_id, version
+            ))
+            .send()
+            .await?;
+        Ok(ConfigExport::from_value(read_json(response).await?))
+    }
+}
+
+fn config_path(config_id: i64) -> String {
+    format!("/appsec/v1/configs/{}", config_id)
+}
+
+fn version_path(config_id: i64, version: u32) -> String {
+    format!("{}/versions/{}", config_path(config_id), version)
+}
+
+fn policy_path(config_id: i64, version: u32, policy_id: &str) -> String {
+    format!(
+        "{}/security-policies/{}",
+        version_path(config_id, version),
+        policy_id
+    )
+}
+
+fn custom_rule_path(config_id: i64, rule_id: i64) -> String {
+    format!("{}/custom-rules/{}", config_path(config_id), rule_id)
+}
+
+/// A configuration export in canonical form
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigExport {
+    value: Value,
+}
+
+impl ConfigExport {
+    /// Canonicalize an export returned by the API
+    pub fn from_value(value: Value) -> Self {
+        Self {
+            value: canonicalize(value),
+        }
+    }
+
+    /// Read an export saved with [`ConfigExport::to_json`]
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(Self::from_value(serde_json::from_str(json)?))
+    }
+
+    /// Pretty-printed JSON ending in a newline
+    ///
+    /// Equal configurations always produce identical text.
+    pub fn to_json(&self) -> String {
+        let mut json =
+            serde_json::to_string_pretty(&self.value).expect("JSON values always serialize");
+        json.push('\n');
+        json
+    }
+
+    /// The canonical document
+    pub fn as_value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Configuration ID, if the export has one
+    pub fn config_id(&self) -> Option<i64> {
+        self.value.get("configId")?.as_i64()
+    }
+
+    /// Version number, if the export has one
+    pub fn version(&self) -> Option<u64> {
+        self.value.get("version")?.as_u64()
+    }
+
+    /// Differences from `self` to `other`, in document order
+    pub fn diff(&self, other: &ConfigExport) -> Vec<Change> {
+        let mut changes = Vec::new();
+        diff_values("", &self.value, &other.value, &mut changes);
+        changes
+    }
+}
+
+/// One difference between two exports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Where the difference is, e.g. `/securityPolicies[id=abc1_1234]/policyName`
+    ///
+    /// Array elements are identified by position, e.g. `/matchTargets[0]`,
+    /// when their IDs are not unique.
+    pub path: String,
+    /// What changed
+    pub kind: ChangeKind,
+}
+
+/// What happened at a path
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// Only present in the newer export
+    Added(Value),
+    /// Only present in the older export
+    Removed(Value),
+    /// Present in both with different values
+    Changed {
+        /// Value in the older export
+        old: Value,
+        /// Value in the newer export
+        new: Value,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added(value) => write!(f, "+ {}: {}", self.path, value),
+            ChangeKind::Removed(value) => write!(f, "- {}: {}", self.path, value),
+            ChangeKind::Changed { old, new } => write!(f, "~ {}: {} -> {}", self.path, old, new),
+        }
+    }
+}
+
+/// Sort keys, drop volatile keys and order arrays of identified objects
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map
+                .into_iter()
+                .filter(|(key, _)| !VOLATILE_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key, canonicalize(value)))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().collect())
+        }
+        Value::Array(items) => {
+            let mut items: Vec<Value> = items.into_iter().map(canonicalize).collect();
+            if let Some(key) = id_key(&items) {
+                // Elements sharing an ID are ordered by content, not API order
+                items.sort_by_cached_key(|item| (id_of(item, key), item.to_string()));
+            }
+            Value::Array(items)
+        }
+        value => value,
+    }
+}
+
+/// The ID key shared by every element, if the array holds identified objects
+fn id_key(items: &[Value]) -> Option<&'static str> {
+    if items.is_empty() {
+        return None;
+    }
+    ID_KEYS.iter().copied().find(|key| {
+        items
+            .iter()
+            .all(|item| matches!(item.get(key), Some(Value::String(_) | Value::Number(_))))
+    })
+}
+
+/// An element's ID as a sortable, printable string; integers sort numerically
+fn id_of(item: &Value, key: &str) -> (u8, i128, String) {
+    match &item[key] {
+        Value::Number(number) => match number
+            .as_i64()
+            .map(i128::from)
+            .or(number.as_u64().map(i128::from))
+        {
+            Some(id) => (0, id, number.to_string()),
+            None => (1, 0, number.to_string()),
+        },
+        value => (1, 0, value.as_str().unwrap_or_default().to_string()),
+    }
+}
+
+/// Whether every element of `items` has a different ID
+fn unique_ids(items: &[Value], key: &str) -> bool {
+    let mut seen = HashSet::new();
+    items.iter().all(|item| seen.insert(id_of(item, key)))
+}
+
+fn diff_values<'a>(path: &str, old: &'a Value, new: &'a Value, changes: &mut Vec<Change>) {
+    if old == new {
+        return;
+    }
+    let change = |kind| Change {
+        path: if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        },
+        kind,
+    };
+
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", path, key);
+                diff_entry(&child, old.get(key), new.get(key), changes);
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            let key = id_key(old_items)
+                .or_else(|| id_key(new_items))
+                .filter(|&key| {
+                    [old_items, new_items]
+                        .iter()
+                        .all(|items| items.is_empty() || id_key(items) == Some(key))
+                });
+            match key {
+                Some(key) if !(unique_ids(old_items, key) && unique_ids(new_items, key)) => {
+                    for index in 0..old_items.len().max(new_items.len()) {
+                        let child = format!("{}[{}]", path, index);
+                        diff_entry(&child, old_items.get(index), new_items.get(index), changes);
+                    }
+                }
+                Some(key) => {
+                    let mut ids: Vec<(u8, i128, String)> = old_items
+                        .iter()
+                        .chain(new_items)
+                        .map(|item| id_of(item, key))
+                        .collect();
+                    ids.sort();
+                    ids.dedup();
+                    for id in ids {
+                        let find = |items: &'a [Value]| -> Option<&'a Value> {
+                            items.iter().find(|item| id_of(item, key) == id)
+                        };
+                        let child = format!("{}[{}={}]", path, key, id.2);
+                        diff_entry(&child, find(old_items), find(new_items), changes);
+                    }
+                }
+                None => changes.push(change(ChangeKind::Changed {
+                    old: old.clone(),
+                    new: new.clone(),
+                })),
+            }
+        }
+        _ => changes.push(change(ChangeKind::Changed {
+            old: old.clone(),
+            new: new.clone(),
+        })),
+    }
+}
+
+fn diff_entry(path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Some(old), Some(new)) => diff_values(path, old, new, changes),
+        (Some(old), None) => changes.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Removed(old.clone()),
+        }),
+        (None, Some(new)) => changes.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Added(new.clone()),
+        }),
+        (None, None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn export(value: Value) -> ConfigExport {
+        ConfigExport::from_value(value)
+    }
+
+    #[test]
+    fn test_export_is_deterministic() {
+        let a = export(json!({
+            "version": 7,
+            "configId": 12345,
+            "createDate": "2026-10-01T00:00:00Z",
+            "securityPolicies": [
+                {"id": "pol2_222", "name": "API", "updatedBy": "someone"},
+                {"name": "Web", "id": "pol1_111"}
+            ],
+            "hostnames": ["www.example.com", "api.example.com"]
+        }));
+        let b = export(json!({
+            "securityPolicies": [
+                {"id": "pol1_111", "name": "Web"},
+                {"name": "API", "id": "pol2_222"}
+            ],
+            "hostnames": ["www.example.com", "api.example.com"],
+            "configId": 12345,
+            "createDate": "2026-10-18T00:00:00Z",
+            "version": 7
+        }));
+
+        assert_eq!(a.to_json(), b.to_json());
+        assert!(a.diff(&b).is_empty());
+        assert_eq!(a.config_id(), Some(12345));
+        assert!(a
+            .to_json()
+            .starts_with("{\n  \"configId\": 12345,\n  \"hostnames\""));
+        assert_eq!(ConfigExport::from_json(&a.to_json()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_numeric_ids_sort_numerically() {
+        let value = export(json!({"ratePolicies": [{"id": 10}, {"id": 9}, {"id": 100}]}));
+        assert_eq!(
+            value.as_value()["ratePolicies"],
+            json!([{"id": 9}, {"id": 10}, {"id": 100}])
+        );
+    }
+
+    #[test]
+    fn test_diff_matches_array_elements_by_id() {
+        let old = export(json!({
+            "version": 7,
+            "ratePolicies": [
+                {"id": 1, "name": "Login", "averageThreshold": 10},
+                {"id": 2, "name": "Search", "averageThreshold": 50}
+            ],
+            "hostnames": ["www.example.com"]
+        }));
+        let new = export(json!({
+            "version": 8,
+            "ratePolicies": [
+                {"id": 3, "name": "Checkout", "averageThreshold": 5},
+                {"id": 1, "name": "Login", "averageThreshold": 20}
+            ],
+            "hostnames": ["www.example.com", "shop.example.com"]
+        }));
+
+        let changes: Vec<String> = old.diff(&new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            vec![
+                r#"~ /hostnames: ["www.example.com"] -> ["www.example.com","shop.example.com"]"#
+                    .to_string(),
+                "~ /ratePolicies[id=1]/averageThreshold: 10 -> 20".to_string(),
+                r#"- /ratePolicies[id=2]: {"averageThreshold":50,"id":2,"name":"Search"}"#
+                    .to_string(),
+                r#"+ /ratePolicies[id=3]: {"averageThreshold":5,"id":3,"name":"Checkout"}"#
+                    .to_string(),
+                "~ /version: 7 -> 8".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_against_empty_array() {
+        let old = export(json!({"customRules": []}));
+        let new = export(json!({"customRules": [{"id": 5, "name": "Block bots"}]}));
+        assert_eq!(
+            old.diff(&new),
+            vec![Change {
+                path: "/customRules[id=5]".into(),
+                kind: ChangeKind::Added(json!({"id": 5, "name": "Block bots"})),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_with_duplicate_or_large_ids() {
+        let old = export(json!({"matchTargets": [
+            {"id": 1, "hostnames": ["a.example.com"]},
+            {"id": 1, "hostnames": ["b.example.com"]}
+        ]}));
+        let new = export(json!({"matchTargets": [
+            {"id": 1, "hostnames": ["a.example.com"]},
+            {"id": 1, "hostnames": ["c.example.com"]}
+        ]}));
+        let changes: Vec<String> = old.diff(&new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            vec![
+                r#"~ /matchTargets[1]/hostnames: ["b.example.com"] -> ["c.example.com"]"#
+                    .to_string()
+            ]
+        );
+
+        let reordered = export(json!({"matchTargets": [
+            {"id": 1, "hostnames": ["b.example.com"]},
+            {"id": 1, "hostnames": ["a.example.com"]}
+        ]}));
+        assert_eq!(reordered.to_json(), old.to_json());
+        assert!(old.diff(&reordered).is_empty());
+
+        let old = export(json!({"customRules": [
+            {"id": 9223372036854775808u64, "name": "One"},
+            {"id": 18446744073709551615u64, "name": "Two"}
+        ]}));
+        let new = export(json!({"customRules": [
+            {"id": 9223372036854775808u64, "name": "One"},
+            {"id": 18446744073709551615u64, "name": "Renamed"}
+        ]}));
+        let changes: Vec<String> = old.diff(&new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            vec![r#"~ /customRules[id=18446744073709551615]/name: "Two" -> "Renamed""#.to_string()]
+        );
+    }
+}
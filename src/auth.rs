@@ -1,7 +1,9 @@
 //! EdgeGrid authentication implementation
 
 use crate::config::EdgeGridConfig;
+use crate::credentials::SharedCredentials;
 use crate::error::Result;
+use crate::telemetry::redact_credentials;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::Utc;
 use hmac::{Hmac, Mac};
@@ -14,15 +16,28 @@ use uuid::Uuid;
 type HmacSha256 = Hmac<Sha256>;
 
 /// EdgeGrid authentication handler
+///
+/// Credentials are read through a [`SharedCredentials`] handle, so clones of
+/// an authenticator pick up rotated credentials automatically.
 #[derive(Debug, Clone)]
 pub struct EdgeGridAuth {
-    config: EdgeGridConfig,
+    credentials: SharedCredentials,
 }
 
 impl EdgeGridAuth {
     /// Create a new EdgeGrid authenticator
     pub fn new(config: EdgeGridConfig) -> Self {
-        Self { config }
+        Self::with_credentials(SharedCredentials::new(config))
+    }
+
+    /// Create an authenticator reading from an existing credentials handle
+    pub fn with_credentials(credentials: SharedCredentials) -> Self {
+        Self { credentials }
+    }
+
+    /// Handle to the credentials used for signing
+    pub fn credentials(&self) -> &SharedCredentials {
+        &self.credentials
     }
 
     /// From .edgerc file
@@ -33,9 +48,18 @@ impl EdgeGridAuth {
 
     /// Sign a request with EdgeGrid authentication
     pub fn sign_request(&self, request: &mut Request) -> Result<()> {
+        self.sign_request_with(request, &self.credentials.current())
+    }
+
+    /// Sign a request using the given credentials instead of the current ones
+    pub(crate) fn sign_request_with(
+        &self,
+        request: &mut Request,
+        config: &EdgeGridConfig,
+    ) -> Result<()> {
         let timestamp = create_timestamp();
         let nonce = Uuid::new_v4().to_string();
-        
+
         // Get request details
         let method = request.method().as_str();
         let url = request.url().clone();
@@ -49,12 +73,13 @@ impl EdgeGridAuth {
 
         // Get headers to sign
         let headers_to_sign = self.get_headers_to_sign(request);
-        
+
         // Calculate content hash if needed
-        let content_hash = self.calculate_content_hash(request)?;
-        
+        let content_hash = self.calculate_content_hash(request, config.max_body)?;
+
         // Create auth header
         let auth_header = self.create_auth_header(
+            config,
             method,
             &url,
             &full_path,
@@ -65,7 +90,8 @@ impl EdgeGridAuth {
         )?;
 
         // Set the authorization header
-        if let Ok(header_value) = auth_header.parse() {
+        if let Ok(mut header_value) = reqwest::header::HeaderValue::from_str(&auth_header) {
+            header_value.set_sensitive(true);
             request.headers_mut().insert("Authorization", header_value);
         }
 
@@ -75,47 +101,60 @@ impl EdgeGridAuth {
     /// Get headers that should be included in the signature
     fn get_headers_to_sign(&self, _request: &Request) -> HashMap<String, String> {
         let headers = HashMap::new();
-        
+
         // In the Node.js version, headersToSign can be passed in the request
         // For now, we'll return empty map as default behavior
         // This can be extended to read specific headers if needed
-        
+
         headers
     }
 
     /// Calculate content hash for POST requests
-    fn calculate_content_hash(&self, request: &Request) -> Result<String> {
-        if request.method() != Method::POST {
-            return Ok(String::new());
-        }
+    fn calculate_content_hash(&self, request: &Request, max_body: usize) -> Result<String> {
+        let body = request.body().and_then(|body| body.as_bytes());
+        Ok(content_hash(request.method(), body, max_body))
+    }
 
-        if let Some(body) = request.body() {
-            if let Some(bytes) = body.as_bytes() {
-                let body_len = bytes.len();
-                
-                // Truncate to max_body if needed
-                let bytes_to_hash = if body_len > self.config.max_body {
-                    log::warn!(
-                        "Request body size ({}) exceeds max_body ({}), truncating for signing",
-                        body_len,
-                        self.config.max_body
-                    );
-                    &bytes[..self.config.max_body]
-                } else {
-                    bytes
-                };
-
-                let hash = Sha256::digest(bytes_to_hash);
-                return Ok(BASE64.encode(hash));
-            }
-        }
+    /// Compute the signature a request with these parts should carry
+    ///
+    /// Used to verify incoming requests with exactly the algorithm used for
+    /// signing them.
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
+    pub(crate) fn expected_signature(
+        &self,
+        config: &EdgeGridConfig,
+        method: &Method,
+        url: &Url,
+        body: Option<&[u8]>,
+        timestamp: &str,
+        nonce: &str,
+    ) -> Result<String> {
+        let full_path = match url.query() {
+            Some(query) if !query.is_empty() => format!("{}?{}", url.path(), query),
+            _ => url.path().to_string(),
+        };
 
-        Ok(String::new())
+        let data_to_sign = self.build_data_to_sign(
+            config,
+            method.as_str(),
+            url.scheme(),
+            url.host_str().unwrap_or(""),
+            &full_path,
+            &HashMap::new(),
+            &content_hash(method, body, config.max_body),
+            timestamp,
+            nonce,
+        );
+
+        let signing_key = self.create_signing_key(config, timestamp)?;
+        self.sign_data(&data_to_sign, &signing_key)
     }
 
     /// Create the EdgeGrid authorization header
+    #[allow(clippy::too_many_arguments)]
     fn create_auth_header(
         &self,
+        config: &EdgeGridConfig,
         method: &str,
         url: &Url,
         path: &str,
@@ -126,6 +165,7 @@ impl EdgeGridAuth {
     ) -> Result<String> {
         // Build the data to sign
         let data_to_sign = self.build_data_to_sign(
+            config,
             method,
             url.scheme(),
             url.host_str().unwrap_or(""),
@@ -136,15 +176,22 @@ impl EdgeGridAuth {
             nonce,
         );
 
+        if config.debug {
+            tracing::debug!(
+                string_to_sign = %redact_credentials(&data_to_sign, config),
+                "EdgeGrid string to sign"
+            );
+        }
+
         // Calculate signature
-        let signing_key = self.create_signing_key(timestamp)?;
+        let signing_key = self.create_signing_key(config, timestamp)?;
         let signature = self.sign_data(&data_to_sign, &signing_key)?;
 
         // Build authorization header
         Ok(format!(
             "EG1-HMAC-SHA256 client_token={};access_token={};timestamp={};nonce={};signature={}",
-            self.config.client_token,
-            self.config.access_token,
+            config.client_token.expose(),
+            config.access_token.expose(),
             timestamp,
             nonce,
             signature
@@ -152,8 +199,10 @@ impl EdgeGridAuth {
     }
 
     /// Build the string that will be signed
+    #[allow(clippy::too_many_arguments)]
     fn build_data_to_sign(
         &self,
+        config: &EdgeGridConfig,
         method: &str,
         scheme: &str,
         host: &str,
@@ -166,8 +215,8 @@ impl EdgeGridAuth {
         let canonicalized_headers = self.canonicalize_headers(headers_to_sign);
         let auth_header = format!(
             "EG1-HMAC-SHA256 client_token={};access_token={};timestamp={};nonce={};",
-            self.config.client_token,
-            self.config.access_token,
+            config.client_token.expose(),
+            config.access_token.expose(),
             timestamp,
             nonce
         );
@@ -197,8 +246,8 @@ impl EdgeGridAuth {
     }
 
     /// Create the signing key
-    fn create_signing_key(&self, timestamp: &str) -> Result<String> {
-        let mut mac = HmacSha256::new_from_slice(self.config.client_secret.as_bytes())
+    fn create_signing_key(&self, config: &EdgeGridConfig, timestamp: &str) -> Result<String> {
+        let mut mac = HmacSha256::new_from_slice(config.client_secret.expose().as_bytes())
             .map_err(|e| crate::error::EdgeGridError::AuthError(e.to_string()))?;
         mac.update(timestamp.as_bytes());
         let result = mac.finalize();
@@ -210,7 +259,7 @@ impl EdgeGridAuth {
         let key_bytes = BASE64
             .decode(signing_key)
             .map_err(|e| crate::error::EdgeGridError::AuthError(e.to_string()))?;
-        
+
         let mut mac = HmacSha256::new_from_slice(&key_bytes)
             .map_err(|e| crate::error::EdgeGridError::AuthError(e.to_string()))?;
         mac.update(data.as_bytes());
@@ -219,6 +268,31 @@ impl EdgeGridAuth {
     }
 }
 
+/// Base64 SHA-256 of a POST body, truncated to `max_body`; empty for other methods
+fn content_hash(method: &Method, body: Option<&[u8]>, max_body: usize) -> String {
+    if method != Method::POST {
+        return String::new();
+    }
+
+    let Some(bytes) = body else {
+        return String::new();
+    };
+
+    // Truncate to max_body if needed
+    let bytes_to_hash = if bytes.len() > max_body {
+        tracing::warn!(
+            "Request body size ({}) exceeds max_body ({}), truncating for signing",
+            bytes.len(),
+            max_body
+        );
+        &bytes[..max_body]
+    } else {
+        bytes
+    };
+
+    BASE64.encode(Sha256::digest(bytes_to_hash))
+}
+
 /// Create timestamp in the required format: yyyyMMddTHH:mm:ss+0000
 fn create_timestamp() -> String {
     Utc::now().format("%Y%m%dT%H:%M:%S+0000").to_string()
@@ -234,7 +308,7 @@ mod tests {
         assert_eq!(timestamp.len(), 22); // Fixed: format is "20140321T19:34:21+0000"
         assert!(timestamp.contains('T'));
         assert!(timestamp.ends_with("+0000"));
-        
+
         // Test the actual format matches expectation
         let parts: Vec<&str> = timestamp.split('T').collect();
         assert_eq!(parts.len(), 2);
@@ -251,12 +325,12 @@ mod tests {
             "test.com".to_string(),
         );
         let auth = EdgeGridAuth::new(config);
-        
+
         let mut headers = HashMap::new();
         headers.insert("X-Test".to_string(), "value1".to_string());
         headers.insert("X-Another".to_string(), "value2".to_string());
-        
+
         let result = auth.canonicalize_headers(&headers);
         assert_eq!(result, "x-another:value2\tx-test:value1");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,492 @@
+//! `akamai-edgegrid doctor`: diagnose a credential
+//!
+//! Reports where each configuration value was loaded from, lints the
+//! credential format, compares the local clock with the API's `Date` header
+//! and asks the Identity and Access Management API whether the credential is
+//! accepted, which APIs it can reach and when it expires.
+
+use crate::{CredentialArgs, DEFAULT_EDGERC};
+use akamai_edgegrid::secret::{
+    CLIENT_SECRET_COMMAND_KEY, CLIENT_SECRET_FILE_KEY, CLIENT_SECRET_KEYRING_KEY,
+};
+use akamai_edgegrid::{EdgeGridClient, EdgeGridConfig, Edgerc, Severity};
+use chrono::{DateTime, Utc};
+use reqwest::header::DATE;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::process::ExitCode;
+
+/// Largest clock difference the API tolerates before rejecting signatures
+const MAX_CLOCK_SKEW_SECS: i64 = 30;
+
+/// Warn when the credential expires within this many days
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+const API_CLIENT_SELF: &str = "/identity-management/v3/api-clients/self";
+const CREDENTIALS_SELF: &str = "/identity-management/v3/api-clients/self/credentials";
+
+/// Where the configuration was loaded from
+enum Origin {
+    Env { prefix: String },
+    Edgerc { path: String, section: String },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiClient {
+    client_name: Option<String>,
+    client_id: Option<String>,
+    api_access: Option<ApiAccess>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAccess {
+    #[serde(default)]
+    all_accessible_apis: bool,
+    #[serde(default)]
+    apis: Vec<ApiScope>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiScope {
+    api_name: String,
+    access_level: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Credential {
+    client_token: String,
+    status: Option<String>,
+    expires_on: Option<String>,
+}
+
+/// Collects check results and remembers the worst one
+#[derive(Default)]
+struct Report {
+    failed: bool,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!("\n{}", title);
+    }
+
+    fn ok(&self, message: impl AsRef<str>) {
+        println!("  ok     {}", message.as_ref());
+    }
+
+    fn warn(&self, message: impl AsRef<str>) {
+        println!("  warn   {}", message.as_ref());
+    }
+
+    fn error(&mut self, message: impl AsRef<str>) {
+        self.failed = true;
+        println!("  error  {}", message.as_ref());
+    }
+
+    fn info(&self, message: impl AsRef<str>) {
+        println!("         {}", message.as_ref());
+    }
+}
+
+pub(crate) async fn run(args: CredentialArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let mut report = Report::default();
+
+    let (config, origin) = load(&args)?;
+    report_sources(&report, &config, &origin, args.edgerc.is_some());
+
+    report.section("Format");
+    let findings = config.lint();
+    if findings.is_empty() {
+        report.ok("no problems found");
+    }
+    for finding in &findings {
+        let message = format!("{}: {}", finding.field, finding.message);
+        match finding.severity {
+            Severity::Warning => report.warn(message),
+            Severity::Error => report.error(message),
+        }
+    }
+
+    let client = EdgeGridClient::new(config.clone())?;
+    check_api(&mut report, &client, &config).await;
+
+    Ok(if report.failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Load the configuration the same way requests do, remembering its origin
+///
+/// `AKAMAI_*` environment variables take precedence over the `.edgerc` file,
+/// even when `--edgerc` is given.
+fn load(args: &CredentialArgs) -> akamai_edgegrid::Result<(EdgeGridConfig, Origin)> {
+    if let Ok(config) = EdgeGridConfig::from_env(&args.section) {
+        let origin = Origin::Env {
+            prefix: env_prefix(&args.section),
+        };
+        return Ok((config, origin));
+    }
+
+    let path = args.edgerc.as_deref().unwrap_or(DEFAULT_EDGERC);
+    let config = Edgerc::load(path)?.config(&args.section)?;
+    let origin = Origin::Edgerc {
+        path: path.to_string(),
+        section: args.section.clone(),
+    };
+    Ok((config, origin))
+}
+
+/// Environment variable prefix used by `EdgeGridConfig::from_env`
+fn env_prefix(section: &str) -> String {
+    if section == "default" {
+        "AKAMAI_".to_string()
+    } else {
+        format!("AKAMAI_{}_", section.to_uppercase())
+    }
+}
+
+fn report_sources(report: &Report, config: &EdgeGridConfig, origin: &Origin, edgerc_given: bool) {
+    report.section("Sources");
+    if let (Origin::Env { prefix }, true) = (origin, edgerc_given) {
+        report.warn(format!(
+            "{}* environment variables take precedence over --edgerc",
+            prefix
+        ));
+    }
+
+    for (field, source) in sources(config, origin) {
+        println!("  {:<20} {}", field, source);
+    }
+    println!("  {:<20} {}", "api host", config.host);
+}
+
+/// Where each configuration value was loaded from
+fn sources(config: &EdgeGridConfig, origin: &Origin) -> Vec<(&'static str, String)> {
+    let fields = [
+        "host",
+        "client_token",
+        "client_secret",
+        "access_token",
+        "max_body",
+        "account_switch_key",
+    ];
+    let edgerc = match origin {
+        Origin::Edgerc { path, .. } => Edgerc::load(path).ok(),
+        Origin::Env { .. } => None,
+    };
+
+    fields
+        .into_iter()
+        .map(|field| {
+            let source = match origin {
+                Origin::Env { prefix } => env_source(prefix, field),
+                Origin::Edgerc { path, section } => {
+                    let location = format!("{} [{}]", path, section);
+                    edgerc
+                        .as_ref()
+                        .and_then(|edgerc| edgerc_source(edgerc, section, field))
+                        .map(|detail| match detail {
+                            Some(via) => format!("{} via {}", location, via),
+                            None => location,
+                        })
+                }
+            };
+
+            let source = source.unwrap_or_else(|| match field {
+                "max_body" => format!("default ({})", config.max_body),
+                _ => "not set".to_string(),
+            });
+            (field, source)
+        })
+        .collect()
+}
+
+fn env_source(prefix: &str, field: &str) -> Option<String> {
+    let names: &[&str] = match field {
+        "host" => &["HOST"],
+        "client_token" => &["CLIENT_TOKEN"],
+        "access_token" => &["ACCESS_TOKEN"],
+        "client_secret" => &[
+            "CLIENT_SECRET",
+            "CLIENT_SECRET_FILE",
+            "CLIENT_SECRET_COMMAND",
+        ],
+        _ => &[],
+    };
+
+    names
+        .iter()
+        .map(|name| format!("{}{}", prefix, name))
+        .find(|name| std::env::var_os(name).is_some())
+        .map(|name| format!("environment variable {}", name))
+}
+
+/// `Some(None)` if the value is set directly in the section, `Some(Some(via))`
+/// if it comes from a secret provider, `None` if it is not set
+fn edgerc_source(edgerc: &Edgerc, section: &str, field: &str) -> Option<Option<String>> {
+    let is_set = |key: &str| {
+        edgerc
+            .get(section, key)
+            .is_some_and(|value| !value.trim().is_empty())
+    };
+
+    if is_set(field) {
+        return Some(None);
+    }
+    if field != "client_secret" {
+        return None;
+    }
+
+    [
+        CLIENT_SECRET_FILE_KEY,
+        CLIENT_SECRET_COMMAND_KEY,
+        CLIENT_SECRET_KEYRING_KEY,
+    ]
+    .into_iter()
+    .find(|key| is_set(key))
+    .map(|key| Some(key.to_string()))
+}
+
+async fn check_api(report: &mut Report, client: &EdgeGridClient, config: &EdgeGridConfig) {
+    report.section("API");
+
+    let response = match client
+        .get(API_CLIENT_SELF)
+        .query("apiAccess", "true")
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            report.error(format!("could not reach {}: {}", config.host, e));
+            return;
+        }
+    };
+
+    check_clock(report, response.headers().get(DATE));
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if status == StatusCode::UNAUTHORIZED {
+        report.error(format!(
+            "credential rejected: {}",
+            problem_detail(&body).unwrap_or(body)
+        ));
+        return;
+    }
+    if !status.is_success() {
+        report.error(format!(
+            "{} returned {}: {}",
+            API_CLIENT_SELF,
+            status,
+            problem_detail(&body).unwrap_or(body)
+        ));
+        return;
+    }
+
+    let api_client: ApiClient = match serde_json::from_str(&body) {
+        Ok(api_client) => api_client,
+        Err(e) => {
+            report.warn(format!(
+                "credential accepted, but the response was not understood: {}",
+                e
+            ));
+            return;
+        }
+    };
+    report.ok(format!(
+        "credential accepted for API client {} ({})",
+        api_client.client_name.as_deref().unwrap_or("<unnamed>"),
+        api_client.client_id.as_deref().unwrap_or("unknown id")
+    ));
+
+    match api_client.api_access {
+        Some(access) if access.all_accessible_apis => report.info("scopes: all accessible APIs"),
+        Some(access) if !access.apis.is_empty() => {
+            report.info("scopes:");
+            for api in access.apis {
+                report.info(format!("  {:<40} {}", api.api_name, api.access_level));
+            }
+        }
+        _ => report.warn("no API scopes reported"),
+    }
+
+    check_expiry(report, client, config).await;
+}
+
+fn check_clock(report: &mut Report, date: Option<&reqwest::header::HeaderValue>) {
+    let server_time = date
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+    let Some(server_time) = server_time else {
+        report.warn("clock skew not checked: the response had no Date header");
+        return;
+    };
+
+    let skew = (Utc::now() - server_time.with_timezone(&Utc)).num_seconds();
+    let direction = if skew >= 0 { "ahead of" } else { "behind" };
+    let message = format!("local clock is {}s {} the API", skew.abs(), direction);
+    if skew.abs() > MAX_CLOCK_SKEW_SECS {
+        report.error(format!(
+            "{}; signatures will be rejected, sync the clock",
+            message
+        ));
+    } else {
+        report.ok(message);
+    }
+}
+
+async fn check_expiry(report: &mut Report, client: &EdgeGridClient, config: &EdgeGridConfig) {
+    let credentials: Vec<Credential> = match client.get(CREDENTIALS_SELF).send_json().await {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            report.warn(format!("could not list credentials: {}", e));
+            return;
+        }
+    };
+
+    let Some(credential) = credentials
+        .iter()
+        .find(|c| c.client_token == config.client_token.expose())
+    else {
+        report.warn("this credential is not listed for the API client");
+        return;
+    };
+
+    let status = credential.status.as_deref().unwrap_or("UNKNOWN");
+    if status != "ACTIVE" {
+        report.error(format!("credential status is {}", status));
+    }
+
+    let expires = credential
+        .expires_on
+        .as_deref()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+    match expires {
+        Some(expires) => {
+            let days = (expires.with_timezone(&Utc) - Utc::now()).num_days();
+            let message = format!(
+                "credential expires {} ({} days)",
+                expires.to_rfc3339(),
+                days
+            );
+            if days < 0 {
+                report.error(message);
+            } else if days < EXPIRY_WARNING_DAYS {
+                report.warn(message);
+            } else {
+                report.ok(message);
+            }
+        }
+        None => report.info("credential expiry not reported"),
+    }
+}
+
+/// The `detail` or `title` of a problem+json error body
+fn problem_detail(body: &str) -> Option<String> {
+    let problem: serde_json::Value = serde_json::from_str(body).ok()?;
+    problem
+        .get("detail")
+        .or_else(|| problem.get("title"))
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SECRET: &str = "abcdefghijklmnopqrstuvwxyz0123456789ABCDEFG=";
+
+    fn edgerc(content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    fn args(edgerc: Option<&std::path::Path>, section: &str) -> CredentialArgs {
+        CredentialArgs {
+            edgerc: edgerc.map(|path| path.display().to_string()),
+            section: section.to_string(),
+        }
+    }
+
+    fn source<'a>(sources: &'a [(&str, String)], field: &str) -> &'a str {
+        &sources.iter().find(|(f, _)| *f == field).unwrap().1
+    }
+
+    #[test]
+    fn test_load_from_edgerc() {
+        let file = edgerc(&format!(
+            "[doctoredgerc]\nclient_secret = {}\nhost = akab-host.luna.akamaiapis.net\naccess_token = akab-access\nclient_token = akab-client\n",
+            SECRET
+        ));
+        let (config, origin) = load(&args(Some(file.path()), "doctoredgerc")).unwrap();
+        assert!(matches!(origin, Origin::Edgerc { .. }));
+
+        let sources = sources(&config, &origin);
+        let location = format!("{} [doctoredgerc]", file.path().display());
+        assert_eq!(source(&sources, "host"), location);
+        assert_eq!(source(&sources, "client_secret"), location);
+        assert_eq!(source(&sources, "max_body"), "default (131072)");
+        assert_eq!(source(&sources, "account_switch_key"), "not set");
+    }
+
+    #[test]
+    fn test_load_secret_from_provider() {
+        let mut secret = tempfile::NamedTempFile::new().unwrap();
+        writeln!(secret, "{}", SECRET).unwrap();
+        let file = edgerc(&format!(
+            "[doctorprovider]\nclient_secret_file = {}\nhost = akab-host.luna.akamaiapis.net\naccess_token = akab-access\nclient_token = akab-client\n",
+            secret.path().display()
+        ));
+
+        let (config, origin) = load(&args(Some(file.path()), "doctorprovider")).unwrap();
+        assert_eq!(config.client_secret.expose(), SECRET);
+
+        let sources = sources(&config, &origin);
+        assert_eq!(
+            source(&sources, "client_secret"),
+            format!(
+                "{} [doctorprovider] via client_secret_file",
+                file.path().display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_environment_takes_precedence_over_edgerc() {
+        let file = edgerc(&format!(
+            "[doctorenv]\nclient_secret = {}\nhost = akab-file.luna.akamaiapis.net\naccess_token = akab-access\nclient_token = akab-client\n",
+            SECRET
+        ));
+        std::env::set_var("AKAMAI_DOCTORENV_HOST", "akab-env.luna.akamaiapis.net");
+        std::env::set_var("AKAMAI_DOCTORENV_CLIENT_TOKEN", "akab-env-client");
+        std::env::set_var("AKAMAI_DOCTORENV_CLIENT_SECRET", SECRET);
+        std::env::set_var("AKAMAI_DOCTORENV_ACCESS_TOKEN", "akab-env-access");
+
+        let (config, origin) = load(&args(Some(file.path()), "doctorenv")).unwrap();
+        assert_eq!(config.host, "https://akab-env.luna.akamaiapis.net");
+        match &origin {
+            Origin::Env { prefix } => assert_eq!(prefix, "AKAMAI_DOCTORENV_"),
+            Origin::Edgerc { .. } => panic!("environment credentials reported as .edgerc"),
+        }
+
+        let sources = sources(&config, &origin);
+        assert_eq!(
+            source(&sources, "host"),
+            "environment variable AKAMAI_DOCTORENV_HOST"
+        );
+        assert_eq!(
+            source(&sources, "client_secret"),
+            "environment variable AKAMAI_DOCTORENV_CLIENT_SECRET"
+        );
+    }
+}
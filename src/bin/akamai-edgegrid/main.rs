@@ -0,0 +1,430 @@
+//! `akamai-edgegrid`: make EdgeGrid-signed requests from the command line
+//!
+//! ```text
+//! akamai-edgegrid /papi/v1/groups
+//! akamai-edgegrid POST /ccu/v3/invalidate/url/staging -d @purge.json
+//! akamai-edgegrid --section ccu -q network=staging -H 'Accept: application/json' /ccu/v3/queues/default
+//! akamai-edgegrid doctor --section ccu
+//! ```
+//!
+//! Built with the `cli` feature.
+
+use akamai_edgegrid::{
+    EdgeGridClient, EdgeGridConfig, InterceptAction, Interceptor, Redaction, SharedCredentials,
+    SignedRequest,
+};
+use clap::{Args, Parser, Subcommand};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, Request, Response};
+use std::io::{Read, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod doctor;
+
+/// Exit code for failures that happen before a response is received
+const EXIT_ERROR: u8 = 1;
+
+/// .edgerc location used when neither `--edgerc` nor environment credentials are given
+pub(crate) const DEFAULT_EDGERC: &str = "~/.edgerc";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "akamai-edgegrid",
+    version,
+    about = "Make EdgeGrid-signed requests to Akamai APIs",
+    after_help = "Exits with 0 for 2xx responses, 3, 4 or 5 for 3xx, 4xx or 5xx responses, and 1 for other errors.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    request: RequestArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Check where credentials come from, their format, the local clock and whether the API accepts them
+    Doctor(CredentialArgs),
+}
+
+/// Where to load credentials from
+#[derive(Debug, Args)]
+pub(crate) struct CredentialArgs {
+    /// Path to the .edgerc file [default: ~/.edgerc]
+    #[arg(long, env = "AKAMAI_EDGERC")]
+    pub(crate) edgerc: Option<String>,
+
+    /// Section of the .edgerc file to use
+    #[arg(long, env = "AKAMAI_EDGERC_SECTION", default_value = "default")]
+    pub(crate) section: String,
+}
+
+#[derive(Debug, Args)]
+struct RequestArgs {
+    #[command(flatten)]
+    credentials: CredentialArgs,
+
+    /// Account switch key for acting on another account
+    #[arg(long, env = "AKAMAI_ACCOUNT_KEY")]
+    account_key: Option<String>,
+
+    /// Request header, as 'Name: value' (repeatable)
+    #[arg(short = 'H', long = "header", value_name = "HEADER")]
+    headers: Vec<String>,
+
+    /// Query parameter, as name=value (repeatable)
+    #[arg(short, long = "query", value_name = "NAME=VALUE")]
+    queries: Vec<String>,
+
+    /// Request body; use @file to read a file or @- to read stdin
+    #[arg(short, long, value_name = "DATA")]
+    data: Option<String>,
+
+    /// Request timeout in seconds
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Print the response body exactly as received instead of pretty-printing JSON
+    #[arg(long)]
+    raw: bool,
+
+    /// Show the signed request and the response headers on stderr
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// HTTP method followed by the path, or just the path for a GET (POST with --data)
+    #[arg(value_name = "[METHOD] PATH", num_args = 1..=2, required = true)]
+    target: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Some(Command::Doctor(credentials)) => doctor::run(credentials).await,
+        None => run(cli.request).await,
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+async fn run(cli: RequestArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let (method, path) = parse_target(&cli.target, cli.data.is_some())?;
+
+    let config = load_config(&cli.credentials)?;
+    let account_key = cli
+        .account_key
+        .clone()
+        .or_else(|| config.account_switch_key.clone());
+
+    let mut client = EdgeGridClient::new(config)?;
+    if let Some(seconds) = cli.timeout {
+        client = client.with_timeout(Duration::from_secs(seconds));
+    }
+    if cli.verbose {
+        let credentials = client.credentials().clone();
+        client = client.with_interceptor(Verbose(credentials));
+    }
+
+    let mut request = client.request(method, &path);
+    if let Some(key) = account_key {
+        request = request.query("accountSwitchKey", key);
+    }
+    for query in &cli.queries {
+        let (name, value) = query
+            .split_once('=')
+            .ok_or_else(|| format!("query parameter '{}' is not name=value", query))?;
+        request = request.query(name, value);
+    }
+
+    let mut has_content_type = false;
+    for header in &cli.headers {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| format!("header '{}' is not 'Name: value'", header))?;
+        has_content_type |= name.trim().eq_ignore_ascii_case(CONTENT_TYPE.as_str());
+        request = request.header(name.trim(), value.trim());
+    }
+
+    if let Some(data) = &cli.data {
+        let body = read_body(data)?;
+        if !has_content_type && serde_json::from_slice::<serde_json::Value>(&body).is_ok() {
+            request = request.header(CONTENT_TYPE.as_str(), "application/json");
+        }
+        request = request.body(body);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if cli.verbose {
+        print_response_head(&response);
+    }
+
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    let body = response.bytes().await?;
+    write_body(&mut std::io::stdout().lock(), &body, is_json && !cli.raw)?;
+
+    Ok(exit_code(status))
+}
+
+/// Split `[METHOD] PATH` into the method and path, defaulting to GET, or POST with a body
+fn parse_target(
+    target: &[String],
+    has_body: bool,
+) -> Result<(Method, String), Box<dyn std::error::Error>> {
+    Ok(match target {
+        [method, path] => (method.to_uppercase().parse::<Method>()?, path.clone()),
+        [path] if has_body => (Method::POST, path.clone()),
+        [path] => (Method::GET, path.clone()),
+        _ => return Err("expected [METHOD] PATH".into()),
+    })
+}
+
+/// Use the .edgerc file unless none was given and the environment has credentials
+pub(crate) fn load_config(args: &CredentialArgs) -> akamai_edgegrid::Result<EdgeGridConfig> {
+    match &args.edgerc {
+        Some(path) => EdgeGridConfig::from_edgerc(path, &args.section),
+        None => EdgeGridConfig::from_env(&args.section)
+            .or_else(|_| EdgeGridConfig::from_edgerc(DEFAULT_EDGERC, &args.section)),
+    }
+}
+
+fn read_body(data: &str) -> std::io::Result<Vec<u8>> {
+    match data.strip_prefix('@') {
+        Some("-") => {
+            let mut body = Vec::new();
+            std::io::stdin().read_to_end(&mut body)?;
+            Ok(body)
+        }
+        Some(path) => std::fs::read(path),
+        None => Ok(data.as_bytes().to_vec()),
+    }
+}
+
+fn write_body(out: &mut impl Write, body: &[u8], pretty: bool) -> std::io::Result<()> {
+    let formatted = pretty
+        .then(|| serde_json::from_slice::<serde_json::Value>(body).ok())
+        .flatten()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok());
+    match formatted {
+        Some(json) => writeln!(out, "{}", json),
+        None => {
+            out.write_all(body)?;
+            if !body.is_empty() && !body.ends_with(b"\n") {
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_response_head(response: &Response) {
+    eprintln!("{:?} {}", response.version(), response.status());
+    for (name, value) in response.headers() {
+        eprintln!("{}: {}", name, value.to_str().unwrap_or("<binary>"));
+    }
+    eprintln!();
+}
+
+fn exit_code(status: reqwest::StatusCode) -> ExitCode {
+    match status.as_u16() {
+        200..=299 => ExitCode::SUCCESS,
+        300..=399 => ExitCode::from(3),
+        400..=499 => ExitCode::from(4),
+        500..=599 => ExitCode::from(5),
+        _ => ExitCode::from(EXIT_ERROR),
+    }
+}
+
+/// Prints each signed request to stderr with its credentials redacted
+struct Verbose(SharedCredentials);
+
+impl Verbose {
+    /// The credentials `request` was signed with: the current ones, or the
+    /// previous ones when a `401` is retried during a rotation overlap
+    fn signed_with(&self, request: &Request) -> Arc<EdgeGridConfig> {
+        let client_token = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                value
+                    .split([' ', ';'])
+                    .find_map(|field| field.strip_prefix("client_token="))
+            });
+
+        let current = self.0.current();
+        match (client_token, self.0.previous()) {
+            (Some(token), Some(previous))
+                if token != current.client_token.expose()
+                    && token == previous.client_token.expose() =>
+            {
+                previous
+            }
+            _ => current,
+        }
+    }
+
+    /// The signed request as HTTP/1.1 text with credentials redacted
+    fn render(&self, request: &Request) -> Option<String> {
+        let signed = SignedRequest::new(request.try_clone()?, self.signed_with(request));
+        Some(signed.http(Redaction::Credentials).replace("\r\n", "\n"))
+    }
+}
+
+impl Interceptor for Verbose {
+    fn after_sign(&self, request: &mut Request) -> akamai_edgegrid::Result<InterceptAction> {
+        if let Some(text) = self.render(request) {
+            eprint!("{}", text);
+        }
+        Ok(InterceptAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use akamai_edgegrid::auth::EdgeGridAuth;
+
+    fn config(token: &str) -> EdgeGridConfig {
+        EdgeGridConfig::new(
+            format!("akab-client-{}", token),
+            "c2VjcmV0",
+            format!("akab-access-{}", token),
+            "https://akab-host.luna.akamaiapis.net".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_parse_request_arguments() {
+        let cli = Cli::try_parse_from([
+            "akamai-edgegrid",
+            "--section",
+            "ccu",
+            "-H",
+            "Accept: application/json",
+            "-q",
+            "network=staging",
+            "-d",
+            "@purge.json",
+            "post",
+            "/ccu/v3/invalidate/url",
+        ])
+        .unwrap();
+        assert!(cli.command.is_none());
+
+        let request = cli.request;
+        assert_eq!(request.credentials.section, "ccu");
+        assert_eq!(request.headers, vec!["Accept: application/json"]);
+        assert_eq!(request.queries, vec!["network=staging"]);
+        assert_eq!(request.data.as_deref(), Some("@purge.json"));
+
+        let (method, path) = parse_target(&request.target, true).unwrap();
+        assert_eq!(method, Method::POST);
+        assert_eq!(path, "/ccu/v3/invalidate/url");
+    }
+
+    #[test]
+    fn test_parse_target_defaults() {
+        let target = vec!["/papi/v1/groups".to_string()];
+        assert_eq!(parse_target(&target, false).unwrap().0, Method::GET);
+        assert_eq!(parse_target(&target, true).unwrap().0, Method::POST);
+
+        let target = vec!["not a method".to_string(), "/papi/v1/groups".to_string()];
+        assert!(parse_target(&target, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_doctor_and_missing_path() {
+        let cli =
+            Cli::try_parse_from(["akamai-edgegrid", "doctor", "--edgerc", "/tmp/.edgerc"]).unwrap();
+        match cli.command {
+            Some(Command::Doctor(args)) => {
+                assert_eq!(args.edgerc.as_deref(), Some("/tmp/.edgerc"));
+                assert_eq!(args.section, "default");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from(["akamai-edgegrid", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_write_body() {
+        let mut out = Vec::new();
+        write_body(&mut out, br#"{"a":[1]}"#, true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\n  \"a\": [\n    1\n  ]\n}\n"
+        );
+
+        let mut out = Vec::new();
+        write_body(&mut out, br#"{"a":[1]}"#, false).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"a\":[1]}\n");
+
+        let mut out = Vec::new();
+        write_body(&mut out, b"", true).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(
+            exit_code(reqwest::StatusCode::NO_CONTENT),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(exit_code(reqwest::StatusCode::FOUND), ExitCode::from(3));
+        assert_eq!(exit_code(reqwest::StatusCode::NOT_FOUND), ExitCode::from(4));
+        assert_eq!(
+            exit_code(reqwest::StatusCode::BAD_GATEWAY),
+            ExitCode::from(5)
+        );
+    }
+
+    #[test]
+    fn test_verbose_reports_the_credentials_used() {
+        let credentials = SharedCredentials::new(config("old"));
+        credentials.rotate(config("new")).unwrap();
+        let verbose = Verbose(credentials);
+
+        let sign = |config: EdgeGridConfig| {
+            let mut request = reqwest::Client::new()
+                .get("https://akab-host.luna.akamaiapis.net/papi/v1/groups")
+                .build()
+                .unwrap();
+            EdgeGridAuth::new(config)
+                .sign_request(&mut request)
+                .unwrap();
+            request
+        };
+
+        // A 401 retry is signed with the previous credentials
+        let retried = verbose.render(&sign(config("old"))).unwrap();
+        assert!(
+            retried.starts_with("GET /papi/v1/groups HTTP/1.1\n"),
+            "{}",
+            retried
+        );
+        assert!(!retried.contains("akab-client-old"), "{}", retried);
+        assert!(!retried.contains("akab-access-old"), "{}", retried);
+
+        let first = verbose.render(&sign(config("new"))).unwrap();
+        assert!(!first.contains("akab-client-new"), "{}", first);
+        assert!(!first.contains("akab-access-new"), "{}", first);
+    }
+}
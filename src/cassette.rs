@@ -0,0 +1,441 @@
+//! Record and replay API traffic for offline tests
+//!
+//! A [`Cassette`] attached with
+//! [`EdgeGridClient::with_cassette`](crate::EdgeGridClient::with_cassette)
+//! either records every exchange to a JSON file or serves previously recorded
+//! responses without touching the network. Recorded requests never contain
+//! the `Authorization` header, and the client's tokens, secret and host are
+//! scrubbed from everything written to disk.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::cassette::{Cassette, MatchOn};
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! // Record once against the real API...
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?
+//!     .with_cassette(Cassette::record("tests/cassettes/list_groups.json"));
+//! client.get("/papi/v1/groups").send_text().await?;
+//!
+//! // ...then replay in CI
+//! let cassette = Cassette::replay("tests/cassettes/list_groups.json")?
+//!     .match_on(MatchOn { body: false, ..MatchOn::default() });
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::buffer_response;
+use crate::config::EdgeGridConfig;
+use crate::error::{EdgeGridError, Result};
+use crate::telemetry::Scrubber;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Request, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Whether a cassette records live traffic or replays it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests to the API and append each exchange to the cassette file
+    Record,
+    /// Answer requests from the cassette file without sending them
+    Replay,
+}
+
+/// Which parts of a request must match a recorded one during replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchOn {
+    /// HTTP method
+    pub method: bool,
+    /// URL path
+    pub path: bool,
+    /// Query parameters, in any order
+    pub query: bool,
+    /// Request body
+    pub body: bool,
+}
+
+impl Default for MatchOn {
+    fn default() -> Self {
+        Self {
+            method: true,
+            path: true,
+            query: true,
+            body: true,
+        }
+    }
+}
+
+/// A recorded request/response pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as sent, without credentials
+    pub request: RecordedRequest,
+    /// The response received for it
+    pub response: RecordedResponse,
+}
+
+/// A request stored in a cassette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: String,
+    /// URL path
+    pub path: String,
+    /// Raw query string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Request headers other than `Authorization`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Request body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<RecordedBody>,
+}
+
+/// A response stored in a cassette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Response body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<RecordedBody>,
+}
+
+/// A message body, stored as text when it is valid UTF-8
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecordedBody {
+    /// UTF-8 body
+    Text(String),
+    /// Binary body, base64 encoded
+    Binary {
+        /// Base64 encoding of the body
+        base64: String,
+    },
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8], scrub: &Scrubber) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => RecordedBody::Text(scrub.apply(text)),
+            Err(_) => RecordedBody::Binary {
+                base64: BASE64.encode(bytes),
+            },
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        match self {
+            RecordedBody::Text(text) => Ok(text.as_bytes().to_vec()),
+            RecordedBody::Binary { base64 } => BASE64
+                .decode(base64)
+                .map_err(|e| EdgeGridError::Cassette(format!("invalid base64 body: {}", e))),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+/// Recorded API traffic backed by a JSON file
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    match_on: MatchOn,
+    scrubs: Vec<(String, String)>,
+    state: Mutex<State>,
+}
+
+impl Cassette {
+    /// Record exchanges to `path`, replacing any existing cassette
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            match_on: MatchOn::default(),
+            scrubs: Vec::new(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Replay exchanges previously recorded to `path`
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let content = std::fs::read_to_string(&path)?;
+        let file: CassetteFile = serde_json::from_str(&content)?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            match_on: MatchOn::default(),
+            scrubs: Vec::new(),
+            state: Mutex::new(State {
+                used: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+            }),
+        })
+    }
+
+    /// Choose which parts of a request are compared during replay
+    pub fn match_on(mut self, match_on: MatchOn) -> Self {
+        self.match_on = match_on;
+        self
+    }
+
+    /// Replace every occurrence of `value` with `replacement` in recorded data
+    ///
+    /// Scrubs are applied to live requests before matching as well, so a
+    /// replayed request matches the scrubbed recording.
+    pub fn scrub(mut self, value: impl Into<String>, replacement: impl Into<String>) -> Self {
+        self.scrubs.push((value.into(), replacement.into()));
+        self
+    }
+
+    /// Whether this cassette records or replays
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Path of the cassette file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The interactions recorded or loaded so far
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().interactions.clone()
+    }
+
+    /// Write the recorded interactions to the cassette file
+    ///
+    /// Called automatically after every recorded exchange.
+    pub fn save(&self) -> Result<()> {
+        let file = CassetteFile {
+            interactions: self.interactions(),
+        };
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)? + "\n")?;
+        Ok(())
+    }
+
+    /// Replay `request`, or send it with `send` and record the exchange
+    pub(crate) async fn exchange<F, Fut>(
+        &self,
+        request: Request,
+        config: &EdgeGridConfig,
+        send: F,
+    ) -> Result<Response>
+    where
+        F: FnOnce(Request) -> Fut,
+        Fut: Future<Output = Result<Response>>,
+    {
+        let scrub = Scrubber::new(config, &self.scrubs);
+        let recorded = record_request(&request, &scrub);
+
+        if self.mode == CassetteMode::Replay {
+            let interaction = self.find(&recorded)?;
+            return build_response(&interaction.response, request.url());
+        }
+
+        let (response, body) = buffer_response(send(request).await?).await?;
+        let recorded_response = RecordedResponse {
+            status: response.status().as_u16(),
+            headers: record_headers(response.headers(), &scrub),
+            body: (!body.is_empty()).then(|| RecordedBody::new(&body, &scrub)),
+        };
+        {
+            let mut state = self.lock();
+            state.interactions.push(Interaction {
+                request: recorded,
+                response: recorded_response,
+            });
+            state.used.push(true);
+        }
+        self.save()?;
+
+        Ok(response)
+    }
+
+    /// First unused interaction matching `request`, falling back to the last used one
+    fn find(&self, request: &RecordedRequest) -> Result<Interaction> {
+        let mut state = self.lock();
+        let matches: Vec<usize> = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| self.matches(&interaction.request, request))
+            .map(|(index, _)| index)
+            .collect();
+
+        let index = matches
+            .iter()
+            .copied()
+            .find(|&index| !state.used[index])
+            .or_else(|| matches.last().copied())
+            .ok_or_else(|| {
+                EdgeGridError::Cassette(format!(
+                    "no recorded interaction matches {} {} in {}",
+                    request.method,
+                    request.path,
+                    self.path.display()
+                ))
+            })?;
+
+        state.used[index] = true;
+        Ok(state.interactions[index].clone())
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        (!self.match_on.method || recorded.method.eq_ignore_ascii_case(&request.method))
+            && (!self.match_on.path || recorded.path == request.path)
+            && (!self.match_on.query
+                || query_pairs(recorded.query.as_deref()) == query_pairs(request.query.as_deref()))
+            && (!self.match_on.body || recorded.body == request.body)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn record_request(request: &Request, scrub: &Scrubber) -> RecordedRequest {
+    RecordedRequest {
+        method: request.method().to_string(),
+        path: scrub.apply(request.url().path()),
+        query: request.url().query().map(|query| scrub.apply(query)),
+        headers: record_headers(request.headers(), scrub),
+        body: request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| RecordedBody::new(bytes, scrub)),
+    }
+}
+
+fn record_headers(headers: &HeaderMap, scrub: &Scrubber) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| *name != AUTHORIZATION)
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or("<binary>");
+            (name.to_string(), scrub.apply(value))
+        })
+        .collect()
+}
+
+fn build_response(recorded: &RecordedResponse, url: &url::Url) -> Result<Response> {
+    let mut builder = http::Response::builder()
+        .status(recorded.status)
+        .url(url.clone());
+    for (name, value) in &recorded.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            builder = builder.header(name, value);
+        }
+    }
+
+    let body = match &recorded.body {
+        Some(body) => body.to_bytes()?,
+        None => Vec::new(),
+    };
+    builder
+        .body(body)
+        .map(Response::from)
+        .map_err(|e| EdgeGridError::Cassette(e.to_string()))
+}
+
+fn query_pairs(query: Option<&str>) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = query
+        .map(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    pairs.sort();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::{Client, Method};
+
+    fn config() -> EdgeGridConfig {
+        EdgeGridConfig::new(
+            "akab-client",
+            "secret",
+            "akab-access",
+            "akab-host.luna.akamaiapis.net".to_string(),
+        )
+    }
+
+    fn request(url: &str, body: &str) -> Request {
+        Client::new()
+            .request(Method::POST, url)
+            .header(AUTHORIZATION, "EG1-HMAC-SHA256 client_token=akab-client")
+            .body(body.to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_recorded_request_is_scrubbed() {
+        let config = config();
+        let scrub = Scrubber::new(&config, &[("ctr_1".to_string(), "ctr_X".to_string())]);
+        let recorded = record_request(
+            &request(
+                "https://akab-host.luna.akamaiapis.net/papi/v1/groups?contractId=ctr_1",
+                r#"{"host":"akab-host.luna.akamaiapis.net"}"#,
+            ),
+            &scrub,
+        );
+
+        assert!(!recorded.headers.contains_key("authorization"));
+        assert_eq!(recorded.query.as_deref(), Some("contractId=ctr_X"));
+        assert_eq!(
+            recorded.body,
+            Some(RecordedBody::Text(r#"{"host":"[REDACTED]"}"#.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_matching_ignores_query_order_and_optionally_body() {
+        let config = config();
+        let scrub = Scrubber::new(&config, &[]);
+        let recorded = record_request(&request("https://h/a?x=1&y=2", "one"), &scrub);
+        let live = record_request(&request("https://h/a?y=2&x=1", "two"), &scrub);
+
+        let cassette = Cassette::record("unused.json");
+        assert!(!cassette.matches(&recorded, &live));
+
+        let cassette = cassette.match_on(MatchOn {
+            body: false,
+            ..MatchOn::default()
+        });
+        assert!(cassette.matches(&recorded, &live));
+    }
+}
@@ -0,0 +1,316 @@
+//! Fast Purge (CCU v3) API
+//!
+//! Invalidate or delete cached content by URL, CP code or cache tag. Large
+//! purges are split into as many requests as needed to stay under the API's
+//! 50,000 byte request body limit.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::ccu::{FastPurge, PurgeObjects};
+//! use akamai_edgegrid::{EdgeGridClient, Network};
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "ccu")?;
+//! let ccu = FastPurge::new(client);
+//!
+//! let urls = PurgeObjects::urls(["https://www.example.com/index.html"]);
+//! for response in ccu.invalidate(Network::Production, urls).await? {
+//!     println!("{} done in ~{}s", response.purge_id, response.estimated_seconds);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, Network};
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+
+/// Largest request body the Fast Purge API accepts
+pub const MAX_BODY_BYTES: usize = 50_000;
+
+const RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+
+/// How purged content is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeAction {
+    /// Mark content stale; edge servers revalidate it with the origin
+    Invalidate,
+    /// Remove content; edge servers fetch it again from the origin
+    Delete,
+}
+
+impl PurgeAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PurgeAction::Invalidate => "invalidate",
+            PurgeAction::Delete => "delete",
+        }
+    }
+}
+
+/// What to purge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeObjects {
+    /// Full URLs or ARLs
+    Urls(Vec<String>),
+    /// CP codes
+    CpCodes(Vec<u64>),
+    /// Cache tags set with the `Edge-Cache-Tag` response header
+    Tags(Vec<String>),
+}
+
+impl PurgeObjects {
+    /// Purge by URL
+    pub fn urls<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        PurgeObjects::Urls(urls.into_iter().map(Into::into).collect())
+    }
+
+    /// Purge by CP code
+    pub fn cp_codes(cp_codes: impl IntoIterator<Item = u64>) -> Self {
+        PurgeObjects::CpCodes(cp_codes.into_iter().collect())
+    }
+
+    /// Purge by cache tag
+    pub fn tags<I, S>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        PurgeObjects::Tags(tags.into_iter().map(Into::into).collect())
+    }
+
+    /// Number of objects to purge
+    pub fn len(&self) -> usize {
+        match self {
+            PurgeObjects::Urls(urls) => urls.len(),
+            PurgeObjects::CpCodes(cp_codes) => cp_codes.len(),
+            PurgeObjects::Tags(tags) => tags.len(),
+        }
+    }
+
+    /// Whether there is nothing to purge
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn path_segment(&self) -> &'static str {
+        match self {
+            PurgeObjects::Urls(_) => "url",
+            PurgeObjects::CpCodes(_) => "cpcode",
+            PurgeObjects::Tags(_) => "tag",
+        }
+    }
+
+    /// Each object serialized as a JSON array element
+    fn to_json(&self) -> Result<Vec<String>> {
+        fn encode<T: Serialize>(items: &[T]) -> Result<Vec<String>> {
+            items
+                .iter()
+                .map(|item| serde_json::to_string(item).map_err(EdgeGridError::from))
+                .collect()
+        }
+
+        match self {
+            PurgeObjects::Urls(urls) => encode(urls),
+            PurgeObjects::CpCodes(cp_codes) => encode(cp_codes),
+            PurgeObjects::Tags(tags) => encode(tags),
+        }
+    }
+}
+
+/// Rate limit state reported with a purge response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Objects allowed in the current window
+    pub limit: Option<u64>,
+    /// Objects still allowed in the current window
+    pub remaining: Option<u64>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        };
+        Self {
+            limit: number(RATE_LIMIT_LIMIT),
+            remaining: number(RATE_LIMIT_REMAINING),
+        }
+    }
+}
+
+/// An accepted purge request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeResponse {
+    /// Status reported in the body, normally 201
+    pub http_status: u16,
+    /// Human-readable result
+    #[serde(default)]
+    pub detail: String,
+    /// Reference for Akamai support
+    #[serde(default)]
+    pub support_id: String,
+    /// Identifier of the purge
+    pub purge_id: String,
+    /// Estimated seconds until the purge completes
+    pub estimated_seconds: u64,
+    /// Number of objects purged by this request
+    #[serde(skip)]
+    pub objects: usize,
+    /// Rate limit headers of the response
+    #[serde(skip)]
+    pub rate_limit: RateLimit,
+}
+
+/// Fast Purge API client
+#[derive(Debug, Clone)]
+pub struct FastPurge {
+    client: EdgeGridClient,
+    max_body: usize,
+}
+
+impl FastPurge {
+    /// Purge through `client`
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self {
+            client,
+            max_body: MAX_BODY_BYTES,
+        }
+    }
+
+    /// Split purges into requests with bodies of at most `bytes`
+    pub fn with_max_body(mut self, bytes: usize) -> Self {
+        self.max_body = bytes;
+        self
+    }
+
+    /// Invalidate `objects` on `network`
+    pub async fn invalidate(
+        &self,
+        network: Network,
+        objects: PurgeObjects,
+    ) -> Result<Vec<PurgeResponse>> {
+        self.purge(PurgeAction::Invalidate, network, objects).await
+    }
+
+    /// Delete `objects` on `network`
+    pub async fn delete(
+        &self,
+        network: Network,
+        objects: PurgeObjects,
+    ) -> Result<Vec<PurgeResponse>> {
+        self.purge(PurgeAction::Delete, network, objects).await
+    }
+
+    /// Purge `objects` on `network`, one response per request sent
+    ///
+    /// Requests are sent one after another and the first error is returned;
+    /// batches sent before it have already been accepted.
+    pub async fn purge(
+        &self,
+        action: PurgeAction,
+        network: Network,
+        objects: PurgeObjects,
+    ) -> Result<Vec<PurgeResponse>> {
+        let path = format!(
+            "/ccu/v3/{}/{}/{}",
+            action.as_str(),
+            objects.path_segment(),
+            network.as_str()
+        );
+
+        let mut responses = Vec::new();
+        for batch in batches(objects.to_json()?, self.max_body) {
+            let count = batch.len();
+            let response = self
+                .client
+                .post(&path)
+                .header(CONTENT_TYPE.as_str(), "application/json")
+                .body(request_body(&batch))
+                .send()
+                .await?;
+            let response = check_status(response).await?;
+
+            let rate_limit = RateLimit::from_headers(response.headers());
+            let mut purge: PurgeResponse = response.json().await?;
+            purge.objects = count;
+            purge.rate_limit = rate_limit;
+            responses.push(purge);
+        }
+        Ok(responses)
+    }
+}
+
+fn request_body(objects: &[String]) -> String {
+    format!("{{\"objects\":[{}]}}", objects.join(","))
+}
+
+/// Group serialized objects so each request body stays within `max_body`
+///
+/// An object too large to fit on its own is sent alone and left for the API
+/// to reject.
+fn batches(objects: Vec<String>, max_body: usize) -> Vec<Vec<String>> {
+    let empty = request_body(&[]).len();
+
+    let mut batches = Vec::new();
+    let mut batch: Vec<String> = Vec::new();
+    let mut size = empty;
+    for object in objects {
+        // Every object after the first needs a separating comma
+        if !batch.is_empty() && size + 1 + object.len() > max_body {
+            batches.push(std::mem::take(&mut batch));
+            size = empty;
+        }
+        size += object.len() + usize::from(!batch.is_empty());
+        batch.push(object);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_fit_body_limit() {
+        let objects =
+            PurgeObjects::urls((0..2_000).map(|i| format!("https://www.example.com/{:040}", i)));
+        let batches = batches(objects.to_json().unwrap(), MAX_BODY_BYTES);
+
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 2_000);
+        for batch in &batches {
+            assert!(request_body(batch).len() <= MAX_BODY_BYTES);
+        }
+        // Batches are packed, not just split evenly
+        let first = request_body(&batches[0]).len();
+        assert!(first + 70 > MAX_BODY_BYTES);
+    }
+
+    #[test]
+    fn test_batch_boundary_is_exact() {
+        let objects = vec!["\"a\"".to_string(), "\"b\"".to_string()];
+        let both = request_body(&objects).len();
+        assert_eq!(batches(objects.clone(), both).len(), 1);
+        assert_eq!(batches(objects, both - 1).len(), 2);
+    }
+
+    #[test]
+    fn test_cp_codes_serialize_as_numbers() {
+        let body = request_body(&PurgeObjects::cp_codes([123, 456]).to_json().unwrap());
+        assert_eq!(body, r#"{"objects":[123,456]}"#);
+    }
+}
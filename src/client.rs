@@ -1,72 +1,183 @@
 //! EdgeGrid HTTP client implementation
 
+use crate::api::read_json;
 use crate::auth::EdgeGridAuth;
-use crate::config::EdgeGridConfig;
+use crate::cassette::Cassette;
+use crate::config::{is_loopback_http, EdgeGridConfig};
+use crate::credentials::SharedCredentials;
 use crate::error::{EdgeGridError, Result};
-use reqwest::{Client, Method, RequestBuilder, Response};
+use crate::export::{Redaction, SignedRequest};
+use crate::har::HarRecorder;
+use crate::intercept::{Interceptor, Interceptors};
+use crate::observe::{Observers, RequestInfo, RequestObserver, RequestOutcome};
+use crate::operation::{Operation, OperationStatus};
+use crate::telemetry::{redact_headers, request_id};
+use reqwest::{Client, Method, Request, RequestBuilder, Response, ResponseBuilderExt, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span};
 use url::Url;
 
 /// EdgeGrid client for making authenticated requests to Akamai APIs
 #[derive(Debug, Clone)]
 pub struct EdgeGridClient {
+    transport: Transport,
+    base_url: Url,
+    timeout: Option<Duration>,
+    observers: Arc<Observers>,
+}
+
+/// Everything needed to sign and send a built request
+#[derive(Debug, Clone)]
+struct Transport {
     client: Client,
     auth: EdgeGridAuth,
-    base_url: Url,
+    interceptors: Arc<Interceptors>,
+    cassette: Option<Arc<Cassette>>,
+    har: Option<HarRecorder>,
 }
 
 impl EdgeGridClient {
     /// Create a new EdgeGrid client with the given configuration
     pub fn new(config: EdgeGridConfig) -> Result<Self> {
+        Self::with_credentials(SharedCredentials::new(config))
+    }
+
+    /// Create a client that signs with a shared, rotatable credentials handle
+    ///
+    /// Requests go to the host of the current credentials, so a rotation
+    /// that changes the host is picked up as well.
+    pub fn with_credentials(credentials: SharedCredentials) -> Result<Self> {
+        let config = credentials.current();
+
         // Validate configuration first
-        if config.client_token.trim().is_empty() {
+        if config.client_token.is_blank() {
             return Err(EdgeGridError::MissingCredential("client_token".to_string()));
         }
-        if config.client_secret.trim().is_empty() {
-            return Err(EdgeGridError::MissingCredential("client_secret".to_string()));
+        if config.client_secret.is_blank() {
+            return Err(EdgeGridError::MissingCredential(
+                "client_secret".to_string(),
+            ));
         }
-        if config.access_token.trim().is_empty() {
+        if config.access_token.is_blank() {
             return Err(EdgeGridError::MissingCredential("access_token".to_string()));
         }
         if config.host.trim().is_empty() {
             return Err(EdgeGridError::MissingCredential("host".to_string()));
         }
-        
+        if config.host.starts_with("http://") && !is_loopback_http(&config.host) {
+            return Err(EdgeGridError::Config(format!(
+                "host must use https: {}",
+                config.host
+            )));
+        }
+
         let base_url = Url::parse(&config.host)?;
-        let auth = EdgeGridAuth::new(config);
+        let auth = EdgeGridAuth::with_credentials(credentials);
         let client = Client::new();
 
         Ok(Self {
-            client,
-            auth,
+            transport: Transport {
+                client,
+                auth,
+                interceptors: Arc::new(Interceptors::default()),
+                cassette: None,
+                har: None,
+            },
             base_url,
+            timeout: None,
+            observers: Arc::new(Observers::default()),
         })
     }
 
+    /// Set a default deadline for every request made by this client
+    ///
+    /// The deadline covers signing, sending and, for `send_json`,
+    /// `send_text` and `send_bytes`, reading the response body. Individual
+    /// requests can override it with [`EdgeGridRequestBuilder::timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Register an observer that is notified before and after every request
+    pub fn with_observer(mut self, observer: impl RequestObserver + 'static) -> Self {
+        Arc::make_mut(&mut self.observers).push(Arc::new(observer));
+        self
+    }
+
+    /// Register an interceptor that can modify, answer or abort every request
+    ///
+    /// Interceptors run in the order they are registered.
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        Arc::make_mut(&mut self.transport.interceptors).push(Arc::new(interceptor));
+        self
+    }
+
+    /// Record traffic to, or replay it from, a cassette file
+    ///
+    /// Interceptors run before the cassette, so recordings capture requests
+    /// as they would have been sent.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.transport.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Capture every exchange sent over the network into a HAR recorder
+    ///
+    /// The recorder is a shared handle; keep a clone to write the HAR file.
+    pub fn with_har(mut self, recorder: HarRecorder) -> Self {
+        self.transport.har = Some(recorder);
+        self
+    }
+
     /// Create a client from .edgerc file
     pub fn from_edgerc(path: impl AsRef<std::path::Path>, section: &str) -> Result<Self> {
         let config = EdgeGridConfig::from_edgerc(path, section)?;
         Self::new(config)
     }
 
+    /// Handle to the credentials used by this client and all of its clones
+    pub fn credentials(&self) -> &SharedCredentials {
+        self.transport.auth.credentials()
+    }
+
     /// Build a request with the given method and path
     pub fn request(&self, method: Method, path: &str) -> EdgeGridRequestBuilder {
-        let url = self.base_url.join(path).unwrap_or_else(|_| {
+        let base_url = self.base_url();
+        let url = base_url.join(path).unwrap_or_else(|_| {
             // If join fails, try to parse as absolute URL
-            Url::parse(&format!("{}{}", self.base_url, path))
-                .unwrap_or_else(|_| self.base_url.clone())
+            Url::parse(&format!("{}{}", base_url, path)).unwrap_or_else(|_| base_url.clone())
         });
 
         EdgeGridRequestBuilder {
-            client: self.client.clone(),
-            auth: self.auth.clone(),
-            builder: self.client.request(method, url),
+            transport: self.transport.clone(),
+            builder: self.transport.client.request(method, url),
             query_params: HashMap::new(),
+            limits: RequestLimits {
+                timeout: self.timeout,
+                cancel: None,
+            },
+            observers: self.observers.clone(),
         }
     }
 
+    /// Base URL of the current credentials' host, or the host the client was
+    /// created with if it is not a valid URL
+    fn base_url(&self) -> Url {
+        Url::parse(&self.credentials().current().host).unwrap_or_else(|_| self.base_url.clone())
+    }
+
+    /// Poll the long-running operation whose status is at `link`
+    pub fn operation<T: OperationStatus>(&self, link: &str) -> Operation<T> {
+        Operation::new(self.clone(), link)
+    }
+
     /// Convenience method for GET requests
     pub fn get(&self, path: &str) -> EdgeGridRequestBuilder {
         self.request(Method::GET, path)
@@ -93,12 +204,42 @@ impl EdgeGridClient {
     }
 }
 
+/// Deadline and cancellation applied to a single request
+#[derive(Clone, Default)]
+struct RequestLimits {
+    timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+}
+
+impl RequestLimits {
+    async fn run<T>(self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        let limited = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, future)
+                    .await
+                    .map_err(|_| EdgeGridError::Timeout(timeout))?,
+                None => future.await,
+            }
+        };
+
+        match self.cancel {
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(EdgeGridError::Cancelled),
+                result = limited => result,
+            },
+            None => limited.await,
+        }
+    }
+}
+
 /// Builder for EdgeGrid requests
 pub struct EdgeGridRequestBuilder {
-    client: Client,
-    auth: EdgeGridAuth,
+    transport: Transport,
     builder: RequestBuilder,
     query_params: HashMap<String, String>,
+    limits: RequestLimits,
+    observers: Arc<Observers>,
 }
 
 impl EdgeGridRequestBuilder {
@@ -152,69 +293,279 @@ impl EdgeGridRequestBuilder {
         self
     }
 
-    /// Send the request and return the response
-    pub async fn send(mut self) -> Result<Response> {
-        // Add query parameters
-        for (key, value) in self.query_params {
-            self.builder = self.builder.query(&[(key, value)]);
-        }
+    /// Set a deadline for this request, overriding the client default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.limits.timeout = Some(timeout);
+        self
+    }
 
-        // Build the request
-        let mut request = self.builder
-            .build()
-            .map_err(EdgeGridError::HttpError)?;
+    /// Abort the request with [`EdgeGridError::Cancelled`] once `token` is cancelled
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.limits.cancel = Some(token);
+        self
+    }
 
-        // Sign the request
-        self.auth.sign_request(&mut request)?;
+    /// Build and sign the request without sending it
+    ///
+    /// The interceptors' `before_sign` and `after_sign` hooks run as they
+    /// would for [`send`](Self::send), so headers and query parameters they
+    /// add are part of the signed request. A synthetic response from an
+    /// interceptor is discarded. Cassettes and HAR recording are not applied.
+    pub fn sign_only(self) -> Result<SignedRequest> {
+        let mut request = build_request(self.builder, self.query_params)?;
+        let interceptors = &self.transport.interceptors;
+        let config = self.transport.auth.credentials().current();
 
-        // Send the request
-        self.client
-            .execute(request)
-            .await
-            .map_err(EdgeGridError::HttpError)
+        interceptors.before_sign(&mut request)?;
+        self.transport
+            .auth
+            .sign_request_with(&mut request, &config)?;
+        interceptors.after_sign(&mut request)?;
+        Ok(SignedRequest::new(request, config))
+    }
+
+    /// Sign the request and render it as a curl command with credentials redacted
+    ///
+    /// Use [`sign_only`](Self::sign_only) and [`SignedRequest::curl`] to
+    /// include the real `Authorization` header.
+    pub fn to_curl(self) -> Result<String> {
+        Ok(self.sign_only()?.curl(Redaction::Credentials))
+    }
+
+    /// Send the request and return the response
+    pub async fn send(self) -> Result<Response> {
+        self.run(|response| async move { Ok(response) }).await
     }
 
     /// Send the request and deserialize the JSON response
+    ///
+    /// Error responses become an [`EdgeGridError::Api`] carrying the parsed
+    /// [`Problem`](crate::Problem).
     pub async fn send_json<T: DeserializeOwned>(self) -> Result<T> {
-        let response = self.send().await?;
-        let status = response.status();
-        
-        if status.is_success() {
-            response
-                .json()
-                .await
-                .map_err(EdgeGridError::HttpError)
-        } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Failed to read error response".to_string());
-            
-            // Create a custom error using anyhow
-            Err(EdgeGridError::Config(format!("HTTP {}: {}", status, error_text)))
-        }
+        self.run(read_json::<T>).await
     }
 
     /// Send the request and return the response as text
     pub async fn send_text(self) -> Result<String> {
-        let response = self.send().await?;
-        response
-            .text()
+        self.run(|response| async move { response.text().await.map_err(EdgeGridError::HttpError) })
             .await
-            .map_err(EdgeGridError::HttpError)
     }
 
     /// Send the request and return the response as bytes
     pub async fn send_bytes(self) -> Result<Vec<u8>> {
-        let response = self.send().await?;
-        response
-            .bytes()
-            .await
-            .map(|b| b.to_vec())
-            .map_err(EdgeGridError::HttpError)
+        self.run(|response| async move {
+            response
+                .bytes()
+                .await
+                .map(|b| b.to_vec())
+                .map_err(EdgeGridError::HttpError)
+        })
+        .await
+    }
+
+    /// Build the request, sign and send it within the deadline, and hand the
+    /// response to `read`; observers see the outcome of the whole exchange
+    async fn run<T, F, Fut>(self, read: F) -> Result<T>
+    where
+        F: FnOnce(Response) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let EdgeGridRequestBuilder {
+            transport,
+            builder,
+            query_params,
+            limits,
+            observers,
+        } = self;
+
+        let request = build_request(builder, query_params)?;
+
+        let info = RequestInfo::new(&request);
+        let span = tracing::info_span!(
+            "edgegrid.request",
+            method = %info.method,
+            path = %info.path,
+            attempt = 1,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            request_id = tracing::field::Empty,
+        );
+
+        observers.on_request(&info);
+        let started = Instant::now();
+
+        let exchange = limits
+            .run(async {
+                let response = transport.dispatch(&info, request).await?;
+                let status = response.status();
+                Ok((status, read(response).await))
+            })
+            .instrument(span)
+            .await;
+
+        let (status, result) = match exchange {
+            Ok((status, result)) => (Some(status), result),
+            Err(e) => (None, Err(e)),
+        };
+
+        if !observers.is_empty() {
+            let outcome = RequestOutcome::new(status, result.as_ref().err(), started.elapsed());
+            observers.on_response(&info, &outcome);
+        }
+
+        result
+    }
+}
+
+impl Transport {
+    /// Run a built request through the interceptors and cassette, signing
+    /// and sending it unless one of them answers it first
+    async fn dispatch(&self, info: &RequestInfo, mut request: Request) -> Result<Response> {
+        let mut response = match self.interceptors.before_sign(&mut request)? {
+            Some(response) => response,
+            None => match &self.cassette {
+                Some(cassette) => {
+                    let config = self.auth.credentials().current();
+                    cassette
+                        .exchange(request, &config, |request| self.sign_and_send(request))
+                        .await?
+                }
+                None => self.sign_and_send(request).await?,
+            },
+        };
+
+        self.interceptors.on_response(info, &mut response)?;
+        Ok(response)
+    }
+
+    /// Sign and send a request, retrying once with the previous credentials
+    /// if it is rejected during a rotation overlap
+    async fn sign_and_send(&self, mut request: Request) -> Result<Response> {
+        let auth = &self.auth;
+
+        // Keep an unsigned copy in case the credentials were just rotated
+        let fallback = auth
+            .credentials()
+            .previous()
+            .and_then(|previous| request.try_clone().map(|retry| (previous, retry)));
+
+        // Sign the request
+        let config = auth.credentials().current();
+        auth.sign_request_with(&mut request, &config)?;
+        if let Some(response) = self.interceptors.after_sign(&mut request)? {
+            return Ok(response);
+        }
+
+        // Send the request
+        let response = self.send(request, &config).await?;
+
+        // Retry once with the previous credentials during a rotation overlap
+        if response.status() == StatusCode::UNAUTHORIZED {
+            if let Some((previous, mut retry)) = fallback {
+                tracing::warn!("Request rejected with 401, retrying with previous credentials");
+                Span::current().record("attempt", 2);
+                auth.sign_request_with(&mut retry, &previous)?;
+                if let Some(response) = self.interceptors.after_sign(&mut retry)? {
+                    return Ok(response);
+                }
+                return self.send(retry, &previous).await;
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Send a signed request, capturing it in the HAR recorder if there is one
+    async fn send(&self, request: Request, config: &EdgeGridConfig) -> Result<Response> {
+        match &self.har {
+            Some(har) => {
+                har.capture(request, config, |request| {
+                    send_signed(&self.client, request, config)
+                })
+                .await
+            }
+            None => send_signed(&self.client, request, config).await,
+        }
     }
 }
 
+/// Send a signed request and record its outcome on the current span
+async fn send_signed(
+    client: &Client,
+    request: Request,
+    config: &EdgeGridConfig,
+) -> Result<Response> {
+    let span = Span::current();
+
+    if config.debug {
+        tracing::debug!(
+            "{} {}\n{}",
+            request.method(),
+            request.url(),
+            redact_headers(request.headers(), config)
+        );
+    }
+
+    let started = Instant::now();
+    let result = client
+        .execute(request)
+        .await
+        .map_err(EdgeGridError::HttpError);
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+    match &result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            if let Some(id) = request_id(response.headers()) {
+                span.record("request_id", id.as_str());
+            }
+            tracing::debug!(
+                status = response.status().as_u16(),
+                "EdgeGrid response received"
+            );
+        }
+        Err(e) => tracing::warn!(error = %e, "EdgeGrid request failed"),
+    }
+
+    result
+}
+
+/// Add the collected query parameters and build the request
+fn build_request(
+    mut builder: RequestBuilder,
+    query_params: HashMap<String, String>,
+) -> Result<Request> {
+    // Add query parameters
+    for (key, value) in query_params {
+        builder = builder.query(&[(key, value)]);
+    }
+
+    // Build the request
+    builder.build().map_err(EdgeGridError::HttpError)
+}
+
+/// Read the whole body of `response` and return it with an equivalent,
+/// still unread response
+pub(crate) async fn buffer_response(response: Response) -> Result<(Response, Vec<u8>)> {
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .version(response.version())
+        .url(response.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+
+    let body = response
+        .bytes()
+        .await
+        .map_err(EdgeGridError::HttpError)?
+        .to_vec();
+    let rebuilt = builder
+        .body(body.clone())
+        .map_err(|e| EdgeGridError::Config(e.to_string()))?;
+    Ok((Response::from(rebuilt), body))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,4 +582,98 @@ mod tests {
         let client = EdgeGridClient::new(config);
         assert!(client.is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_debug_output_redacts_credentials() {
+        let config = EdgeGridConfig::new(
+            "test-client-token".to_string(),
+            "test-client-secret".to_string(),
+            "test-access-token".to_string(),
+            "https://test.luna.akamaiapis.net".to_string(),
+        );
+
+        let debug = format!("{:?}", EdgeGridClient::new(config).unwrap());
+        assert!(!debug.contains("test-client-secret"));
+        assert!(!debug.contains("test-access-token"));
+        assert!(!debug.contains("test-client-token"));
+    }
+
+    #[test]
+    fn test_requests_follow_rotated_host() {
+        let config = |host: &str| {
+            EdgeGridConfig::new(
+                "test-client-token".to_string(),
+                "test-client-secret".to_string(),
+                "test-access-token".to_string(),
+                host.to_string(),
+            )
+        };
+
+        let client = EdgeGridClient::new(config("https://old.luna.akamaiapis.net")).unwrap();
+        client
+            .credentials()
+            .rotate(config("https://new.luna.akamaiapis.net"))
+            .unwrap();
+
+        let signed = client.get("/test").sign_only().unwrap();
+        assert_eq!(
+            signed.request().url().as_str(),
+            "https://new.luna.akamaiapis.net/test"
+        );
+    }
+
+    #[test]
+    fn test_sign_only_applies_interceptors() {
+        use crate::intercept::{InterceptAction, Interceptor};
+
+        #[derive(Debug)]
+        struct Tag;
+
+        impl Interceptor for Tag {
+            fn before_sign(&self, request: &mut Request) -> Result<InterceptAction> {
+                request
+                    .url_mut()
+                    .query_pairs_mut()
+                    .append_pair("accountSwitchKey", "1-ABC");
+                request
+                    .headers_mut()
+                    .insert("x-team", reqwest::header::HeaderValue::from_static("edge"));
+                Ok(InterceptAction::Continue)
+            }
+        }
+
+        let config = EdgeGridConfig::new(
+            "test-client-token".to_string(),
+            "test-client-secret".to_string(),
+            "test-access-token".to_string(),
+            "https://test.luna.akamaiapis.net".to_string(),
+        );
+        let client = EdgeGridClient::new(config).unwrap().with_interceptor(Tag);
+
+        let curl = client.get("/papi/v1/groups").to_curl().unwrap();
+        assert!(
+            curl.contains("/papi/v1/groups?accountSwitchKey=1-ABC"),
+            "{}",
+            curl
+        );
+        assert!(curl.contains("-H 'x-team: edge'"), "{}", curl);
+    }
+
+    #[test]
+    fn test_plain_http_is_rejected_for_remote_hosts() {
+        let config = |host: &str| {
+            EdgeGridConfig::new(
+                "test-client-token".to_string(),
+                "test-client-secret".to_string(),
+                "test-access-token".to_string(),
+                host.to_string(),
+            )
+        };
+
+        assert!(EdgeGridClient::new(config("http://127.0.0.1:8080")).is_ok());
+        assert!(matches!(
+            EdgeGridClient::new(config("http://test.luna.akamaiapis.net")),
+            Err(EdgeGridError::Config(_))
+        ));
+    }
+}
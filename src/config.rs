@@ -1,24 +1,36 @@
 //! Configuration types and .edgerc file parsing
 
+use crate::edgerc::Edgerc;
 use crate::error::{EdgeGridError, Result};
+use crate::secret::{
+    provider_from_values, serialize_exposed, serialize_redacted, Secret, SecretProvider,
+    CLIENT_SECRET_COMMAND_KEY, CLIENT_SECRET_FILE_KEY,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use url::{Host, Url};
 
 /// Maximum body size for request signing (128KB)
 pub const MAX_BODY: usize = 131072;
 
 /// EdgeGrid configuration containing authentication credentials
+///
+/// Credentials are redacted in `Debug` output and when serialized; use
+/// [`EdgeGridConfig::exposed`] to serialize them in full.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeGridConfig {
     /// Client token for authentication
-    pub client_token: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    pub client_token: Secret,
     /// Client secret for signing requests
-    pub client_secret: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    pub client_secret: Secret,
     /// Access token for API access
-    pub access_token: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    pub access_token: Secret,
     /// API host (e.g., "akab-xxxxx.luna.akamaiapis.net")
     pub host: String,
     /// Maximum body size for signing (defaults to MAX_BODY)
@@ -36,24 +48,40 @@ fn default_max_body() -> usize {
     MAX_BODY
 }
 
+/// Serializable view of an [`EdgeGridConfig`] that includes the credentials
+#[derive(Serialize)]
+pub struct ExposedConfig<'a> {
+    #[serde(serialize_with = "serialize_exposed")]
+    client_token: &'a Secret,
+    #[serde(serialize_with = "serialize_exposed")]
+    client_secret: &'a Secret,
+    #[serde(serialize_with = "serialize_exposed")]
+    access_token: &'a Secret,
+    host: &'a str,
+    max_body: usize,
+    debug: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account_switch_key: Option<&'a str>,
+}
+
 impl EdgeGridConfig {
     /// Create a new EdgeGrid configuration
     pub fn new(
-        client_token: String,
-        client_secret: String,
-        access_token: String,
+        client_token: impl Into<Secret>,
+        client_secret: impl Into<Secret>,
+        access_token: impl Into<Secret>,
         host: String,
     ) -> Self {
-        let host = if host.starts_with("https://") {
+        let host = if host.starts_with("https://") || host.starts_with("http://") {
             host
         } else {
             format!("https://{}", host)
         };
 
         Self {
-            client_token,
-            client_secret,
-            access_token,
+            client_token: client_token.into(),
+            client_secret: client_secret.into(),
+            access_token: access_token.into(),
             host,
             max_body: MAX_BODY,
             debug: false,
@@ -64,23 +92,18 @@ impl EdgeGridConfig {
     /// Load configuration from .edgerc file
     pub fn from_edgerc(path: impl AsRef<Path>, section: &str) -> Result<Self> {
         let path = resolve_home_path(path)?;
-        
+
         // First try environment variables
         if let Ok(config) = Self::from_env(section) {
-            println!("Using configuration from environment variables");
+            tracing::info!("Using configuration from environment variables");
             return Ok(config);
         }
-        
+
         // Then try .edgerc file
         let content = fs::read_to_string(&path)
             .map_err(|e| EdgeGridError::Config(format!("Cannot read .edgerc file: {}", e)))?;
-        
-        let edgerc = parse_edgerc(&content)?;
-        
-        edgerc
-            .get(section)
-            .ok_or_else(|| EdgeGridError::InvalidSection(section.to_string()))
-            .and_then(|config| Self::validate_config(config.clone()))
+
+        Edgerc::parse(&content).config(section)
     }
 
     /// Load configuration from environment variables
@@ -95,31 +118,77 @@ impl EdgeGridConfig {
             .map_err(|_| EdgeGridError::EnvError(format!("{}HOST not set", prefix)))?;
         let client_token = env::var(format!("{}CLIENT_TOKEN", prefix))
             .map_err(|_| EdgeGridError::EnvError(format!("{}CLIENT_TOKEN not set", prefix)))?;
-        let client_secret = env::var(format!("{}CLIENT_SECRET", prefix))
-            .map_err(|_| EdgeGridError::EnvError(format!("{}CLIENT_SECRET not set", prefix)))?;
+        let client_secret = match env::var(format!("{}CLIENT_SECRET", prefix)) {
+            Ok(secret) => Secret::from(secret),
+            Err(_) => {
+                let mut values = HashMap::new();
+                if let Ok(path) = env::var(format!("{}CLIENT_SECRET_FILE", prefix)) {
+                    values.insert(CLIENT_SECRET_FILE_KEY.to_string(), path);
+                }
+                if let Ok(command) = env::var(format!("{}CLIENT_SECRET_COMMAND", prefix)) {
+                    values.insert(CLIENT_SECRET_COMMAND_KEY.to_string(), command);
+                }
+                provider_from_values(&values)?
+                    .ok_or_else(|| {
+                        EdgeGridError::EnvError(format!("{}CLIENT_SECRET not set", prefix))
+                    })?
+                    .secret()?
+            }
+        };
         let access_token = env::var(format!("{}ACCESS_TOKEN", prefix))
             .map_err(|_| EdgeGridError::EnvError(format!("{}ACCESS_TOKEN not set", prefix)))?;
 
         Ok(Self::new(client_token, client_secret, access_token, host))
     }
 
+    /// Replace the client secret with one resolved from `provider`
+    pub fn with_secret_provider(mut self, provider: &dyn SecretProvider) -> Result<Self> {
+        self.client_secret = provider.secret()?;
+        Ok(self)
+    }
+
+    /// Borrow the configuration as a serializable value that includes credentials
+    ///
+    /// Only use this when the output is itself stored securely, e.g. when
+    /// writing credentials to a file readable only by its owner.
+    pub fn exposed(&self) -> ExposedConfig<'_> {
+        ExposedConfig {
+            client_token: &self.client_token,
+            client_secret: &self.client_secret,
+            access_token: &self.access_token,
+            host: &self.host,
+            max_body: self.max_body,
+            debug: self.debug,
+            account_switch_key: self.account_switch_key.as_deref(),
+        }
+    }
+
     /// Validate that all required fields are present
-    fn validate_config(mut config: EdgeGridConfig) -> Result<Self> {
-        if config.client_token.trim().is_empty() {
+    pub(crate) fn validate_config(mut config: EdgeGridConfig) -> Result<Self> {
+        if config.client_token.is_blank() {
             return Err(EdgeGridError::MissingCredential("client_token".to_string()));
         }
-        if config.client_secret.trim().is_empty() {
-            return Err(EdgeGridError::MissingCredential("client_secret".to_string()));
+        if config.client_secret.is_blank() {
+            return Err(EdgeGridError::MissingCredential(
+                "client_secret".to_string(),
+            ));
         }
-        if config.access_token.trim().is_empty() {
+        if config.access_token.is_blank() {
             return Err(EdgeGridError::MissingCredential("access_token".to_string()));
         }
         if config.host.trim().is_empty() {
             return Err(EdgeGridError::MissingCredential("host".to_string()));
         }
 
-        // Ensure host has https://
-        if !config.host.starts_with("https://") {
+        // Ensure host has https://; plain http is only allowed for loopback hosts
+        if config.host.starts_with("http://") {
+            if !is_loopback_http(&config.host) {
+                return Err(EdgeGridError::Config(format!(
+                    "host must use https: {}",
+                    config.host
+                )));
+            }
+        } else if !config.host.starts_with("https://") {
             config.host = format!("https://{}", config.host);
         }
 
@@ -132,69 +201,54 @@ impl EdgeGridConfig {
     }
 }
 
-/// Parse .edgerc file format
-fn parse_edgerc(content: &str) -> Result<HashMap<String, EdgeGridConfig>> {
-    let mut sections = HashMap::new();
-    let mut current_section = None;
-    let mut current_config = HashMap::new();
-
-    for line in content.lines() {
-        let line = line.trim();
-        
-        // Skip comments and empty lines
-        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
-            continue;
-        }
-
-        // Check for section header
-        if line.starts_with('[') && line.ends_with(']') {
-            // Save previous section if exists
-            if let Some(section) = current_section.take() {
-                if let Ok(config) = parse_section_config(&current_config) {
-                    sections.insert(section, config);
-                }
-            }
-            
-            current_section = Some(line[1..line.len()-1].to_string());
-            current_config.clear();
-            continue;
-        }
-
-        // Parse key-value pairs
-        if let Some(eq_pos) = line.find('=') {
-            let key = line[..eq_pos].trim();
-            let value = line[eq_pos + 1..].trim();
-            
-            // Remove quotes and inline comments
-            let value = parse_value(value);
-            
-            // Handle max-body -> max_body conversion
-            let key = if key == "max-body" { "max_body" } else { key };
-            
-            current_config.insert(key.to_string(), value);
-        }
+/// Whether `host` is a plain `http://` URL of this machine, such as a local test server
+///
+/// Credentials are never sent over plain http to any other host.
+pub(crate) fn is_loopback_http(host: &str) -> bool {
+    if !host.starts_with("http://") {
+        return false;
     }
 
-    // Save last section
-    if let Some(section) = current_section {
-        if let Ok(config) = parse_section_config(&current_config) {
-            sections.insert(section, config);
-        }
+    match Url::parse(host).ok().as_ref().and_then(Url::host) {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
     }
+}
+
+/// Parse .edgerc file format, keeping only the sections with valid credentials
+#[cfg(test)]
+fn parse_edgerc(content: &str) -> Result<HashMap<String, EdgeGridConfig>> {
+    let edgerc = Edgerc::parse(content);
+    let sections: HashMap<String, EdgeGridConfig> = edgerc
+        .sections()
+        .into_iter()
+        .filter_map(|name| Some((name.to_string(), edgerc.config(name).ok()?)))
+        .collect();
 
     if sections.is_empty() {
-        Err(EdgeGridError::Config("No valid sections found in .edgerc".to_string()))
+        Err(EdgeGridError::Config(
+            "No valid sections found in .edgerc".to_string(),
+        ))
     } else {
         Ok(sections)
     }
 }
 
 /// Parse a section's key-value pairs into EdgeGridConfig
-fn parse_section_config(values: &HashMap<String, String>) -> Result<EdgeGridConfig> {
+pub(crate) fn parse_section_config(values: &HashMap<String, String>) -> Result<EdgeGridConfig> {
+    let mut client_secret = Secret::from(values.get("client_secret").cloned().unwrap_or_default());
+    if client_secret.is_blank() {
+        if let Some(provider) = provider_from_values(values)? {
+            client_secret = provider.secret()?;
+        }
+    }
+
     let config = EdgeGridConfig {
-        client_token: values.get("client_token").cloned().unwrap_or_default(),
-        client_secret: values.get("client_secret").cloned().unwrap_or_default(),
-        access_token: values.get("access_token").cloned().unwrap_or_default(),
+        client_token: Secret::from(values.get("client_token").cloned().unwrap_or_default()),
+        client_secret,
+        access_token: Secret::from(values.get("access_token").cloned().unwrap_or_default()),
         host: values.get("host").cloned().unwrap_or_default(),
         max_body: values
             .get("max_body")
@@ -208,17 +262,17 @@ fn parse_section_config(values: &HashMap<String, String>) -> Result<EdgeGridConf
 }
 
 /// Parse value from .edgerc, handling quotes and comments
-fn parse_value(value: &str) -> String {
+pub(crate) fn parse_value(value: &str) -> String {
     let value = value.trim();
-    
-    // Remove surrounding quotes
-    let value = if (value.starts_with('"') && value.ends_with('"')) ||
-                   (value.starts_with('\'') && value.ends_with('\'')) {
-        &value[1..value.len()-1]
-    } else {
-        value
-    };
-    
+
+    // Quoted values are taken literally, so they may contain ';'
+    if value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')))
+    {
+        return value[1..value.len() - 1].to_string();
+    }
+
     // Remove inline comments
     if let Some(comment_pos) = value.find(';') {
         value[..comment_pos].trim().to_string()
@@ -228,9 +282,9 @@ fn parse_value(value: &str) -> String {
 }
 
 /// Resolve ~ in file paths
-fn resolve_home_path(path: impl AsRef<Path>) -> Result<PathBuf> {
+pub(crate) fn resolve_home_path(path: impl AsRef<Path>) -> Result<PathBuf> {
     let path = path.as_ref();
-    
+
     if path.starts_with("~") {
         let home = dirs::home_dir()
             .ok_or_else(|| EdgeGridError::Config("Cannot determine home directory".to_string()))?;
@@ -263,16 +317,71 @@ max_body = 2048
 
         let sections = parse_edgerc(content).unwrap();
         assert_eq!(sections.len(), 2);
-        
+
         let default = sections.get("default").unwrap();
-        assert_eq!(default.client_token, "akab-XXXXXXXXXXXXXXXX-XXXXXXXXXXXXXXXX");
+        assert_eq!(
+            default.client_token,
+            "akab-XXXXXXXXXXXXXXXX-XXXXXXXXXXXXXXXX"
+        );
         assert_eq!(default.max_body, MAX_BODY);
-        
+
         let section1 = sections.get("section1").unwrap();
         assert_eq!(section1.client_token, "client1");
         assert_eq!(section1.max_body, 2048);
     }
 
+    #[test]
+    fn test_parse_section_with_secret_file() {
+        use std::io::Write;
+
+        let mut secret_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(secret_file, "secret-from-file").unwrap();
+
+        let content = format!(
+            "[default]\nclient_secret_file = {}\nhost = host.luna.akamaiapis.net\naccess_token = token\nclient_token = client\n",
+            secret_file.path().display()
+        );
+
+        let config = Edgerc::parse(&content).config("default").unwrap();
+        assert_eq!(config.client_secret.expose(), "secret-from-file");
+    }
+
+    #[test]
+    fn test_credentials_are_redacted() {
+        let config = EdgeGridConfig::new(
+            "akab-client",
+            "super-secret",
+            "akab-access",
+            "host.luna.akamaiapis.net".to_string(),
+        );
+
+        let debug = format!("{:?}", config);
+        let json = serde_json::to_string(&config).unwrap();
+        for leaked in ["akab-client", "super-secret", "akab-access"] {
+            assert!(!debug.contains(leaked), "{}", debug);
+            assert!(!json.contains(leaked), "{}", json);
+        }
+
+        let exposed = serde_json::to_value(config.exposed()).unwrap();
+        assert_eq!(exposed["client_secret"], "super-secret");
+        let round_trip: EdgeGridConfig = serde_json::from_value(exposed).unwrap();
+        assert_eq!(round_trip.client_secret.expose(), "super-secret");
+    }
+
+    #[test]
+    fn test_redacted_config_does_not_deserialize() {
+        let config = EdgeGridConfig::new(
+            "akab-client",
+            "super-secret",
+            "akab-access",
+            "host.luna.akamaiapis.net".to_string(),
+        );
+
+        let json = serde_json::to_string(&config).unwrap();
+        let err = serde_json::from_str::<EdgeGridConfig>(&json).unwrap_err();
+        assert!(err.to_string().contains("redacted"), "{}", err);
+    }
+
     #[test]
     fn test_parse_value() {
         assert_eq!(parse_value("simple"), "simple");
@@ -281,4 +390,45 @@ max_body = 2048
         assert_eq!(parse_value("value ; comment"), "value");
         assert_eq!(parse_value("  spaced  "), "spaced");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_quoted_value_is_literal() {
+        assert_eq!(parse_value("\"a;b # c\""), "a;b # c");
+        assert_eq!(parse_value("' padded '"), " padded ");
+        assert_eq!(parse_value("\""), "\"");
+    }
+
+    #[test]
+    fn test_plain_http_only_for_loopback_hosts() {
+        let section = |host: &str| {
+            let values = HashMap::from([
+                ("client_token".to_string(), "client".to_string()),
+                ("client_secret".to_string(), "secret".to_string()),
+                ("access_token".to_string(), "token".to_string()),
+                ("host".to_string(), host.to_string()),
+            ]);
+            parse_section_config(&values)
+        };
+
+        assert_eq!(
+            section("http://127.0.0.1:8080").unwrap().host,
+            "http://127.0.0.1:8080"
+        );
+        assert_eq!(
+            section("http://localhost:8080/").unwrap().host,
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            section("http://[::1]:8080").unwrap().host,
+            "http://[::1]:8080"
+        );
+        assert!(matches!(
+            section("http://akab-host.luna.akamaiapis.net"),
+            Err(EdgeGridError::Config(_))
+        ));
+        assert!(matches!(
+            section("http://127.0.0.1.example.com"),
+            Err(EdgeGridError::Config(_))
+        ));
+    }
+}
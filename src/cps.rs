@@ -0,0 +1,670 @@
+//! Certificate Provisioning System (CPS v2) API
+//!
+//! Enrollments, change status, domain validation challenges, third-party
+//! CSRs and certificate upload.
+//!
+//! Every CPS resource has its own versioned media type, such as
+//! `application/vnd.akamai.cps.enrollment.v11+json`, and requests without the
+//! right `Accept` and `Content-Type` headers are rejected. The methods here
+//! set them automatically.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::cps::{Cps, InputType};
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let cps = Cps::new(client);
+//!
+//! let enrollment = cps.enrollment(10000).await?;
+//! for change_id in enrollment.pending_change_ids() {
+//!     let status = cps.change_operation(10000, change_id).wait().await?;
+//!     if status.requires_input(InputType::LetsEncryptChallenges) {
+//!         for domain in cps.dv_challenges(10000, change_id).await? {
+//!             for challenge in &domain.challenges {
+//!                 println!("{} {} {:?}", domain.domain, challenge.challenge_type, challenge.token);
+//!             }
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json};
+use crate::client::{EdgeGridClient, EdgeGridRequestBuilder};
+use crate::error::{EdgeGridError, Result};
+use crate::operation::{Operation, OperationState, OperationStatus};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+const ENROLLMENTS_MEDIA_TYPE: &str = "application/vnd.akamai.cps.enrollments.v11+json";
+const ENROLLMENT_MEDIA_TYPE: &str = "application/vnd.akamai.cps.enrollment.v11+json";
+const ENROLLMENT_STATUS_MEDIA_TYPE: &str = "application/vnd.akamai.cps.enrollment-status.v1+json";
+const CHANGE_MEDIA_TYPE: &str = "application/vnd.akamai.cps.change.v2+json";
+const CHANGE_ID_MEDIA_TYPE: &str = "application/vnd.akamai.cps.change-id.v1+json";
+const DV_CHALLENGES_MEDIA_TYPE: &str = "application/vnd.akamai.cps.dv-challenges.v2+json";
+const CSR_MEDIA_TYPE: &str = "application/vnd.akamai.cps.csr.v2+json";
+const WARNINGS_MEDIA_TYPE: &str = "application/vnd.akamai.cps.warnings.v1+json";
+const CHANGE_MANAGEMENT_MEDIA_TYPE: &str =
+    "application/vnd.akamai.cps.change-management-info.v5+json";
+const ACKNOWLEDGEMENT_MEDIA_TYPE: &str = "application/vnd.akamai.cps.acknowledgement.v1+json";
+const CERTIFICATE_MEDIA_TYPE: &str =
+    "application/vnd.akamai.cps.certificate-and-trust-chain.v2+json";
+
+/// Certificate Provisioning System API client
+#[derive(Debug, Clone)]
+pub struct Cps {
+    client: EdgeGridClient,
+}
+
+/// A certificate enrollment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Enrollment {
+    /// Enrollment ID, absent until created
+    #[serde(default, skip_serializing)]
+    pub id: Option<i64>,
+    /// Certificate authority: `lets-encrypt`, `third-party` and so on
+    pub ra: String,
+    /// `dv`, `ov`, `ev` or `third-party`
+    pub validation_type: String,
+    /// `san`, `single` or `wildcard`
+    pub certificate_type: String,
+    /// Subject of the certificate
+    pub csr: CsrInfo,
+    /// Changes in progress
+    #[serde(default, skip_serializing)]
+    pub pending_changes: Vec<PendingChange>,
+    /// Members not modelled above, e.g. `networkConfiguration`, `org` or
+    /// `adminContact`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Enrollment {
+    /// IDs of the changes in progress
+    pub fn pending_change_ids(&self) -> Vec<i64> {
+        self.pending_changes
+            .iter()
+            .filter_map(|change| link_id(&change.location))
+            .collect()
+    }
+}
+
+/// Subject of an enrollment's certificate
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsrInfo {
+    /// Common name
+    pub cn: String,
+    /// Subject alternative names
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sans: Vec<String>,
+    /// Members not modelled above, e.g. `c`, `o` or `ou`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A change in progress on an enrollment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingChange {
+    /// Link to the change, e.g. `/cps/v2/enrollments/10000/changes/10001`
+    pub location: String,
+    /// `new-certificate`, `renewal` and so on
+    #[serde(default)]
+    pub change_type: String,
+}
+
+/// Links returned when an enrollment is created or updated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrollmentChange {
+    /// Link to the enrollment
+    pub enrollment: String,
+    /// Links to the changes started by the request
+    #[serde(default)]
+    pub changes: Vec<String>,
+}
+
+impl EnrollmentChange {
+    /// ID of the enrollment
+    pub fn enrollment_id(&self) -> Option<i64> {
+        link_id(&self.enrollment)
+    }
+
+    /// IDs of the changes started by the request
+    pub fn change_ids(&self) -> Vec<i64> {
+        self.changes
+            .iter()
+            .filter_map(|link| link_id(link))
+            .collect()
+    }
+}
+
+/// Input a change can wait for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputType {
+    /// Let's Encrypt DV challenges to fulfil
+    LetsEncryptChallenges,
+    /// A CSR to have signed by a third-party CA
+    ThirdPartyCsr,
+    /// Warnings to acknowledge before the certificate is validated
+    PreVerificationWarnings,
+    /// Warnings to acknowledge before the certificate is deployed
+    PostVerificationWarnings,
+    /// A staging deployment to approve before production
+    ChangeManagementInfo,
+}
+
+impl InputType {
+    /// Name used in `allowedInput` and input links
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputType::LetsEncryptChallenges => "lets-encrypt-challenges",
+            InputType::ThirdPartyCsr => "third-party-csr",
+            InputType::PreVerificationWarnings => "pre-verification-warnings",
+            InputType::PostVerificationWarnings => "post-verification-warnings",
+            InputType::ChangeManagementInfo => "change-management-info",
+        }
+    }
+
+    /// Media type of the input's info document
+    fn info_media_type(&self) -> &'static str {
+        match self {
+            InputType::LetsEncryptChallenges => DV_CHALLENGES_MEDIA_TYPE,
+            InputType::ThirdPartyCsr => CSR_MEDIA_TYPE,
+            InputType::PreVerificationWarnings | InputType::PostVerificationWarnings => {
+                WARNINGS_MEDIA_TYPE
+            }
+            InputType::ChangeManagementInfo => CHANGE_MANAGEMENT_MEDIA_TYPE,
+        }
+    }
+
+    /// Name of the update that answers the input
+    fn update_name(&self) -> &'static str {
+        match self {
+            InputType::LetsEncryptChallenges => "lets-encrypt-challenges-completed",
+            InputType::ThirdPartyCsr => "third-party-cert-and-trust-chain",
+            InputType::PreVerificationWarnings => "pre-verification-warnings-ack",
+            InputType::PostVerificationWarnings => "post-verification-warnings-ack",
+            InputType::ChangeManagementInfo => "change-management-ack",
+        }
+    }
+}
+
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Status of a change
+///
+/// As an [`OperationStatus`], a change waiting for required input counts as
+/// succeeded, so that [`Operation::wait`] returns and the input can be given.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeStatus {
+    /// Where the change stands
+    pub status_info: StatusInfo,
+    /// Input the change accepts or is waiting for
+    #[serde(default)]
+    pub allowed_input: Vec<AllowedInput>,
+}
+
+/// Where a change stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusInfo {
+    /// Detailed step, e.g. `wait-upload-third-party`
+    pub status: String,
+    /// `running`, `awaiting-input`, `completed`, `error` or `cancelled`
+    pub state: String,
+    /// Description of the step
+    #[serde(default)]
+    pub description: String,
+    /// Error details when `state` is `error`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+/// Input a change accepts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowedInput {
+    /// Input name, see [`InputType::as_str`]
+    #[serde(rename = "type")]
+    pub input_type: String,
+    /// Whether the change waits until the input is given
+    #[serde(default)]
+    pub required_to_proceed: bool,
+    /// Link to the input's info document
+    #[serde(default)]
+    pub info: String,
+    /// Link to post the input to
+    #[serde(default)]
+    pub update: String,
+}
+
+impl ChangeStatus {
+    /// Whether the change waits for `input_type`
+    pub fn requires_input(&self, input_type: InputType) -> bool {
+        self.allowed_input
+            .iter()
+            .any(|input| input.required_to_proceed && input.input_type == input_type.as_str())
+    }
+}
+
+impl OperationStatus for ChangeStatus {
+    fn from_body(body: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn state(&self) -> OperationState {
+        match self.status_info.state.as_str() {
+            "completed" => OperationState::Succeeded,
+            "error" | "cancelled" => OperationState::Failed(format!(
+                "change is {}: {}",
+                self.status_info.state, self.status_info.description
+            )),
+            _ if self
+                .allowed_input
+                .iter()
+                .any(|input| input.required_to_proceed) =>
+            {
+                OperationState::Succeeded
+            }
+            _ => OperationState::Pending,
+        }
+    }
+}
+
+/// Let's Encrypt validation of one domain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainValidation {
+    /// Domain being validated
+    pub domain: String,
+    /// Validation status, e.g. `Awaiting user` or `Valid`
+    #[serde(default)]
+    pub status: String,
+    /// Ways the domain can be validated
+    #[serde(default)]
+    pub challenges: Vec<Challenge>,
+    /// Members not modelled above, e.g. `validationStatus` or `expires`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A DV challenge; fulfilling either the HTTP or the DNS one is enough
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Challenge {
+    /// `http-01` or `dns-01`
+    #[serde(rename = "type")]
+    pub challenge_type: String,
+    /// Challenge status
+    #[serde(default)]
+    pub status: String,
+    /// For `http-01`, the URL to serve; for `dns-01`, the TXT record name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_path: Option<String>,
+    /// Content to serve at `full_path` or put in the TXT record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+    /// Challenge token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Members not modelled above, e.g. `redirectFullPath`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Challenge {
+    /// Whether this is an HTTP challenge
+    pub fn is_http(&self) -> bool {
+        self.challenge_type == "http-01"
+    }
+
+    /// Whether this is a DNS challenge
+    pub fn is_dns(&self) -> bool {
+        self.challenge_type == "dns-01"
+    }
+}
+
+/// A CSR to have signed by a third-party CA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThirdPartyCsr {
+    /// PEM-encoded CSR
+    pub csr: String,
+    /// `RSA` or `ECDSA`
+    #[serde(default)]
+    pub key_algorithm: String,
+}
+
+/// A signed certificate to upload for a third-party enrollment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateUpload {
+    /// PEM-encoded certificate
+    pub certificate: String,
+    /// PEM-encoded intermediate certificates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_chain: Option<String>,
+    /// `RSA` or `ECDSA`, matching the CSR
+    pub key_algorithm: String,
+}
+
+#[derive(Deserialize)]
+struct EnrollmentsResponse {
+    enrollments: Vec<Enrollment>,
+}
+
+#[derive(Deserialize)]
+struct DvChallengesResponse {
+    dv: Vec<DomainValidation>,
+}
+
+#[derive(Deserialize)]
+struct CsrsResponse {
+    csrs: Vec<ThirdPartyCsr>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificatesBody<'a> {
+    certificates_and_trust_chains: &'a [CertificateUpload],
+}
+
+impl Cps {
+    /// Use `client` for Certificate Provisioning System requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// Enrollments, optionally limited to one contract
+    pub async fn enrollments(&self, contract_id: Option<&str>) -> Result<Vec<Enrollment>> {
+        let mut request = self
+            .client
+            .get("/cps/v2/enrollments")
+            .header(ACCEPT.as_str(), ENROLLMENTS_MEDIA_TYPE);
+        if let Some(contract_id) = contract_id {
+            request = request.query("contractId", contract_id);
+        }
+        let response: EnrollmentsResponse = read_json(request.send().await?).await?;
+        Ok(response.enrollments)
+    }
+
+    /// A single enrollment
+    pub async fn enrollment(&self, enrollment_id: i64) -> Result<Enrollment> {
+        let response = self
+            .client
+            .get(&enrollment_path(enrollment_id))
+            .header(ACCEPT.as_str(), ENROLLMENT_MEDIA_TYPE)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create an enrollment, starting its first certificate
+    pub async fn create_enrollment(
+        &self,
+        contract_id: &str,
+        enrollment: &Enrollment,
+    ) -> Result<EnrollmentChange> {
+        let request = self
+            .client
+            .post("/cps/v2/enrollments")
+            .query("contractId", contract_id);
+        self.send_enrollment(request, enrollment).await
+    }
+
+    /// Update an enrollment, starting a change if the certificate is affected
+    ///
+    /// With `cancel_pending_changes`, a change already in progress is
+    /// cancelled instead of the update being rejected.
+    pub async fn update_enrollment(
+        &self,
+        enrollment_id: i64,
+        enrollment: &Enrollment,
+        cancel_pending_changes: bool,
+    ) -> Result<EnrollmentChange> {
+        let request = self.client.put(&enrollment_path(enrollment_id)).query(
+            "allow-cancel-pending-changes",
+            cancel_pending_changes.to_string(),
+        );
+        self.send_enrollment(request, enrollment).await
+    }
+
+    /// Status of a change
+    pub async fn change_status(&self, enrollment_id: i64, change_id: i64) -> Result<ChangeStatus> {
+        let response = self
+            .client
+            .get(&change_path(enrollment_id, change_id))
+            .header(ACCEPT.as_str(), CHANGE_MEDIA_TYPE)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Poll a change until it completes, fails or waits for input
+    ///
+    /// Certificate changes take hours, so the timeout is best raised.
+    pub fn change_operation(&self, enrollment_id: i64, change_id: i64) -> Operation<ChangeStatus> {
+        self.client
+            .operation(&change_path(enrollment_id, change_id))
+            .header(ACCEPT.as_str(), CHANGE_MEDIA_TYPE)
+    }
+
+    /// DV challenges of a Let's Encrypt change
+    pub async fn dv_challenges(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+    ) -> Result<Vec<DomainValidation>> {
+        let response: DvChallengesResponse = self
+            .input_info(enrollment_id, change_id, InputType::LetsEncryptChallenges)
+            .await?;
+        Ok(response.dv)
+    }
+
+    /// CSRs of a third-party change, one per key algorithm
+    pub async fn third_party_csrs(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+    ) -> Result<Vec<ThirdPartyCsr>> {
+        let response: CsrsResponse = self
+            .input_info(enrollment_id, change_id, InputType::ThirdPartyCsr)
+            .await?;
+        Ok(response.csrs)
+    }
+
+    /// The info document of any input, as JSON
+    pub async fn input_info<T: DeserializeOwned>(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+        input_type: InputType,
+    ) -> Result<T> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/input/info/{}",
+                change_path(enrollment_id, change_id),
+                input_type
+            ))
+            .header(ACCEPT.as_str(), input_type.info_media_type())
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Upload certificates signed from the third-party CSRs
+    pub async fn upload_certificate(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+        certificates: &[CertificateUpload],
+    ) -> Result<()> {
+        let body = serde_json::to_vec(&CertificatesBody {
+            certificates_and_trust_chains: certificates,
+        })?;
+        self.update_input(
+            enrollment_id,
+            change_id,
+            InputType::ThirdPartyCsr,
+            CERTIFICATE_MEDIA_TYPE,
+            body,
+        )
+        .await
+    }
+
+    /// Acknowledge an input: DV challenges as fulfilled, warnings as
+    /// reviewed or a staging deployment as approved
+    ///
+    /// Third-party CSRs are answered with [`Cps::upload_certificate`].
+    pub async fn acknowledge(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+        input_type: InputType,
+    ) -> Result<()> {
+        if input_type == InputType::ThirdPartyCsr {
+            return Err(EdgeGridError::Config(
+                "third-party CSRs are answered by uploading a certificate".to_string(),
+            ));
+        }
+        let body = serde_json::to_vec(&serde_json::json!({ "acknowledgement": "acknowledge" }))?;
+        self.update_input(
+            enrollment_id,
+            change_id,
+            input_type,
+            ACKNOWLEDGEMENT_MEDIA_TYPE,
+            body,
+        )
+        .await
+    }
+
+    async fn update_input(
+        &self,
+        enrollment_id: i64,
+        change_id: i64,
+        input_type: InputType,
+        media_type: &str,
+        body: Vec<u8>,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .post(&format!(
+                "{}/input/update/{}",
+                change_path(enrollment_id, change_id),
+                input_type.update_name()
+            ))
+            .header(CONTENT_TYPE.as_str(), media_type)
+            .header(ACCEPT.as_str(), CHANGE_ID_MEDIA_TYPE)
+            .body(body)
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    async fn send_enrollment(
+        &self,
+        request: EdgeGridRequestBuilder,
+        enrollment: &Enrollment,
+    ) -> Result<EnrollmentChange> {
+        let response = request
+            .header(CONTENT_TYPE.as_str(), ENROLLMENT_MEDIA_TYPE)
+            .header(ACCEPT.as_str(), ENROLLMENT_STATUS_MEDIA_TYPE)
+            .body(serde_json::to_vec(enrollment)?)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+}
+
+fn enrollment_path(enrollment_id: i64) -> String {
+    format!("/cps/v2/enrollments/{}", enrollment_id)
+}
+
+fn change_path(enrollment_id: i64, change_id: i64) -> String {
+    format!("{}/changes/{}", enrollment_path(enrollment_id), change_id)
+}
+
+/// Numeric ID at the end of a link such as `/cps/v2/enrollments/10000/changes/10001`
+fn link_id(link: &str) -> Option<i64> {
+    link.split('?').next()?.rsplit('/').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_state() {
+        let status = |state: &str, required: bool| ChangeStatus {
+            status_info: StatusInfo {
+                status: "wait-upload-third-party".into(),
+                state: state.into(),
+                description: "Waiting".into(),
+                error: None,
+            },
+            allowed_input: vec![AllowedInput {
+                input_type: "third-party-csr".into(),
+                required_to_proceed: required,
+                info: String::new(),
+                update: String::new(),
+            }],
+        };
+
+        assert_eq!(status("running", false).state(), OperationState::Pending);
+        assert_eq!(
+            status("awaiting-input", true).state(),
+            OperationState::Succeeded
+        );
+        assert!(status("awaiting-input", true).requires_input(InputType::ThirdPartyCsr));
+        assert!(!status("awaiting-input", true).requires_input(InputType::LetsEncryptChallenges));
+        assert_eq!(
+            status("completed", false).state(),
+            OperationState::Succeeded
+        );
+        assert_eq!(
+            status("error", false).state(),
+            OperationState::Failed("change is error: Waiting".into())
+        );
+    }
+
+    #[test]
+    fn test_enrollment_links() {
+        let enrollment: Enrollment = serde_json::from_value(serde_json::json!({
+            "id": 10000,
+            "ra": "lets-encrypt",
+            "validationType": "dv",
+            "certificateType": "san",
+            "csr": {"cn": "www.example.com", "sans": ["example.com"], "c": "SE"},
+            "pendingChanges": [{"location": "/cps/v2/enrollments/10000/changes/10001", "changeType": "renewal"}],
+            "networkConfiguration": {"geography": "core"}
+        }))
+        .unwrap();
+        assert_eq!(enrollment.pending_change_ids(), vec![10001]);
+
+        let body = serde_json::to_value(&enrollment).unwrap();
+        assert!(body.get("id").is_none());
+        assert!(body.get("pendingChanges").is_none());
+        assert_eq!(body["csr"]["c"], "SE");
+
+        let created = EnrollmentChange {
+            enrollment: "/cps/v2/enrollments/10002".into(),
+            changes: vec!["/cps/v2/enrollments/10002/changes/10003".into()],
+        };
+        assert_eq!(created.enrollment_id(), Some(10002));
+        assert_eq!(created.change_ids(), vec![10003]);
+    }
+}
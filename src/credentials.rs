@@ -0,0 +1,283 @@
+//! Shared credentials that can be rotated while clients are in use
+//!
+//! Every clone of an [`EdgeGridAuth`](crate::auth::EdgeGridAuth) or
+//! [`EdgeGridClient`](crate::EdgeGridClient) reads its credentials through the
+//! same [`SharedCredentials`] handle, so a rotation is picked up by all of them
+//! atomically. After a rotation the previous credentials stay available for an
+//! overlap window, and a request rejected with `401 Unauthorized` is retried
+//! once with them.
+
+use crate::config::EdgeGridConfig;
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default time the previous credentials remain usable after a rotation
+pub const DEFAULT_OVERLAP: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct State {
+    current: Arc<EdgeGridConfig>,
+    previous: Option<(Arc<EdgeGridConfig>, Instant)>,
+    overlap: Duration,
+}
+
+/// A shared, swappable handle to the credentials used for signing
+#[derive(Debug, Clone)]
+pub struct SharedCredentials {
+    state: Arc<RwLock<State>>,
+}
+
+impl SharedCredentials {
+    /// Create a handle holding `config`
+    pub fn new(config: EdgeGridConfig) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(State {
+                current: Arc::new(config),
+                previous: None,
+                overlap: DEFAULT_OVERLAP,
+            })),
+        }
+    }
+
+    /// Set how long the previous credentials stay usable after a rotation
+    pub fn with_overlap(self, overlap: Duration) -> Self {
+        self.write().overlap = overlap;
+        self
+    }
+
+    /// The credentials currently used for signing
+    pub fn current(&self) -> Arc<EdgeGridConfig> {
+        self.read().current.clone()
+    }
+
+    /// The credentials replaced by the last rotation, while still inside the overlap window
+    pub fn previous(&self) -> Option<Arc<EdgeGridConfig>> {
+        let state = self.read();
+        state
+            .previous
+            .as_ref()
+            .filter(|(_, rotated_at)| rotated_at.elapsed() < state.overlap)
+            .map(|(config, _)| config.clone())
+    }
+
+    /// Replace the current credentials, keeping the old ones for the overlap window
+    ///
+    /// `config` is checked like a loaded configuration: blank credentials and
+    /// plain `http://` hosts other than loopback ones are rejected.
+    pub fn rotate(&self, config: EdgeGridConfig) -> Result<()> {
+        let config = EdgeGridConfig::validate_config(config)?;
+        let mut state = self.write();
+        let previous = std::mem::replace(&mut state.current, Arc::new(config));
+        state.previous = Some((previous, Instant::now()));
+        tracing::info!("EdgeGrid credentials rotated");
+        Ok(())
+    }
+
+    /// Re-read `section` from an `.edgerc` file and rotate if the configuration changed
+    ///
+    /// Credentials are resolved the same way as [`EdgeGridConfig::from_edgerc`],
+    /// so `AKAMAI_*` environment variables still take precedence over the file
+    /// and a reload never switches to a different source. Any change to the
+    /// tokens, secret, host, `max_body` or `account_switch_key` counts.
+    /// Returns whether a rotation happened.
+    pub fn reload_from_edgerc(&self, path: impl AsRef<Path>, section: &str) -> Result<bool> {
+        let config = EdgeGridConfig::from_edgerc(path, section)?;
+        if same_config(&self.current(), &config) {
+            return Ok(false);
+        }
+
+        self.rotate(config)?;
+        Ok(true)
+    }
+
+    /// Watch an `.edgerc` file and reload `section` whenever it changes
+    ///
+    /// The file's modification time is checked every `interval`. The task
+    /// stops once every handle to these credentials has been dropped, or when
+    /// the returned handle is aborted. Must be called from within a Tokio
+    /// runtime.
+    pub fn watch_edgerc(
+        &self,
+        path: impl AsRef<Path>,
+        section: &str,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let path = path.as_ref().to_path_buf();
+        let section = section.to_string();
+        let weak = Arc::downgrade(&self.state);
+
+        tokio::spawn(watch_loop(weak, path, section, interval))
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, State> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, State> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<EdgeGridConfig> for SharedCredentials {
+    fn from(config: EdgeGridConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+async fn watch_loop(weak: Weak<RwLock<State>>, path: PathBuf, section: String, interval: Duration) {
+    let mut last_modified = modified_time(&path);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        let Some(state) = weak.upgrade() else {
+            return;
+        };
+        let credentials = SharedCredentials { state };
+
+        let modified = modified_time(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        if let Err(e) = credentials.reload_from_edgerc(&path, &section) {
+            tracing::warn!(
+                "Failed to reload EdgeGrid credentials from {} [{}]: {}",
+                path.display(),
+                section,
+                e
+            );
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    crate::config::resolve_home_path(path)
+        .ok()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok())
+}
+
+/// Whether two configs sign the same way against the same host; `debug` is ignored
+fn same_config(a: &EdgeGridConfig, b: &EdgeGridConfig) -> bool {
+    a.client_token == b.client_token
+        && a.client_secret == b.client_secret
+        && a.access_token == b.access_token
+        && a.host == b.host
+        && a.max_body == b.max_body
+        && a.account_switch_key == b.account_switch_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(secret: &str) -> EdgeGridConfig {
+        EdgeGridConfig::new(
+            "client",
+            secret,
+            "access",
+            "host.luna.akamaiapis.net".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_rotate_keeps_previous_within_overlap() {
+        let credentials = SharedCredentials::new(config("old"));
+        let clone = credentials.clone();
+        assert!(credentials.previous().is_none());
+
+        credentials.rotate(config("new")).unwrap();
+        assert_eq!(clone.current().client_secret.expose(), "new");
+        assert_eq!(clone.previous().unwrap().client_secret.expose(), "old");
+
+        let credentials = credentials.with_overlap(Duration::ZERO);
+        assert!(credentials.previous().is_none());
+    }
+
+    #[test]
+    fn test_rotate_rejects_invalid_config() {
+        let credentials = SharedCredentials::new(config("old"));
+        let remote = EdgeGridConfig::new(
+            "client",
+            "new",
+            "access",
+            "http://api.example.com".to_string(),
+        );
+        let err = credentials.rotate(remote).unwrap_err();
+        assert!(err.to_string().contains("host must use https"), "{}", err);
+        assert!(credentials.rotate(config(" ")).is_err());
+        assert_eq!(credentials.current().client_secret.expose(), "old");
+        assert!(credentials.previous().is_none());
+
+        let local = EdgeGridConfig::new(
+            "client",
+            "new",
+            "access",
+            "http://127.0.0.1:8080".to_string(),
+        );
+        credentials.rotate(local).unwrap();
+        assert_eq!(credentials.current().host, "http://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn test_reload_from_edgerc_only_rotates_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".edgerc");
+        let write = |secret: &str| {
+            std::fs::write(
+                &path,
+                format!(
+                    "[default]\nclient_secret = {}\nhost = host.luna.akamaiapis.net\naccess_token = access\nclient_token = client\n",
+                    secret
+                ),
+            )
+            .unwrap();
+        };
+
+        write("old");
+        let credentials = SharedCredentials::new(config("old"));
+        assert!(!credentials.reload_from_edgerc(&path, "default").unwrap());
+
+        write("new");
+        assert!(credentials.reload_from_edgerc(&path, "default").unwrap());
+        assert_eq!(credentials.current().client_secret.expose(), "new");
+    }
+
+    #[test]
+    fn test_reload_from_edgerc_rotates_on_host_and_switch_key_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".edgerc");
+        let write = |host: &str, extra: &str| {
+            std::fs::write(
+                &path,
+                format!(
+                    "[default]\nclient_secret = old\nhost = {}\naccess_token = access\nclient_token = client\n{}",
+                    host, extra
+                ),
+            )
+            .unwrap();
+        };
+
+        let credentials = SharedCredentials::new(config("old"));
+        write("other.luna.akamaiapis.net", "");
+        assert!(credentials.reload_from_edgerc(&path, "default").unwrap());
+        assert_eq!(
+            credentials.current().host,
+            "https://other.luna.akamaiapis.net"
+        );
+
+        write("other.luna.akamaiapis.net", "account_switch_key = 1-ABC\n");
+        assert!(credentials.reload_from_edgerc(&path, "default").unwrap());
+        assert_eq!(
+            credentials.current().account_switch_key.as_deref(),
+            Some("1-ABC")
+        );
+        assert!(!credentials.reload_from_edgerc(&path, "default").unwrap());
+    }
+}
@@ -0,0 +1,1313 @@
+//! Edge DNS (Config DNS v2) API
+//!
+//! Typed zones and record sets, bulk zone creation, changelists for batching
+//! record changes into one zone version, and zone files in RFC 1035 master
+//! format, either through the API or converted locally with
+//! [`to_zone_file`] and [`parse_zone_file`].
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::dns::{Dns, RecordData, RecordSet, RecordSetChange};
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "dns")?;
+//! let dns = Dns::new(client);
+//!
+//! let www = RecordSet::from_data(
+//!     "www.example.com",
+//!     300,
+//!     vec![RecordData::A("192.0.2.10".parse().unwrap())],
+//! )?;
+//! dns.create_changelist("example.com").await?;
+//! dns.add_change("example.com", &RecordSetChange::add(www)).await?;
+//! dns.submit_changelist("example.com").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json};
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use crate::operation::{Operation, OperationState, OperationStatus};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+const ZONE_FILE_MEDIA_TYPE: &str = "text/dns";
+
+/// Record classes that may appear in zone files
+const CLASSES: [&str; 3] = ["IN", "CH", "HS"];
+
+/// Longest character-string allowed in TXT and CAA data
+const MAX_CHARACTER_STRING: usize = 255;
+
+/// Edge DNS API client
+#[derive(Debug, Clone)]
+pub struct Dns {
+    client: EdgeGridClient,
+}
+
+/// How a zone is served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ZoneType {
+    /// Records are managed in Edge DNS
+    Primary,
+    /// Records are transferred from master name servers
+    Secondary,
+    /// Records are those of another zone
+    Alias,
+}
+
+/// A DNS zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    /// Zone name, e.g. `example.com`
+    pub zone: String,
+    /// How the zone is served
+    #[serde(rename = "type")]
+    pub zone_type: ZoneType,
+    /// Contract the zone belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_id: Option<String>,
+    /// Free-form comment, required when creating or updating a zone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Master name servers of a secondary zone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masters: Vec<String>,
+    /// Zone an alias zone points to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Whether DNSSEC signing is enabled
+    #[serde(default)]
+    pub sign_and_serve: bool,
+    /// `PENDING`, `ACTIVE`, `INACTIVE` and so on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation_state: Option<String>,
+    /// When the zone last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<String>,
+    /// Current zone version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Zone {
+    fn new(zone: impl Into<String>, zone_type: ZoneType) -> Self {
+        Self {
+            zone: zone.into(),
+            zone_type,
+            contract_id: None,
+            comment: None,
+            masters: Vec::new(),
+            target: None,
+            sign_and_serve: false,
+            activation_state: None,
+            last_modified_date: None,
+            version_id: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// A primary zone
+    pub fn primary(zone: impl Into<String>) -> Self {
+        Self::new(zone, ZoneType::Primary)
+    }
+
+    /// A secondary zone transferred from `masters`
+    pub fn secondary<I, S>(zone: impl Into<String>, masters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut zone = Self::new(zone, ZoneType::Secondary);
+        zone.masters = masters.into_iter().map(Into::into).collect();
+        zone
+    }
+
+    /// An alias of the zone `target`
+    pub fn alias(zone: impl Into<String>, target: impl Into<String>) -> Self {
+        let mut zone = Self::new(zone, ZoneType::Alias);
+        zone.target = Some(target.into());
+        zone
+    }
+
+    /// Attach a comment
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+}
+
+/// A DNS record type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RecordType {
+    /// IPv4 address
+    A,
+    /// IPv6 address
+    Aaaa,
+    /// Akamai edge hostname at the zone apex
+    AkamaiCdn,
+    /// Akamai top-level CNAME
+    AkamaiTlc,
+    /// Certification authority authorization
+    Caa,
+    /// Canonical name
+    Cname,
+    /// Mail exchange
+    Mx,
+    /// Name server
+    Ns,
+    /// Pointer for reverse lookups
+    Ptr,
+    /// Start of authority
+    Soa,
+    /// Service location
+    Srv,
+    /// Text
+    Txt,
+    /// Any other type, by its uppercase name
+    Other(String),
+}
+
+impl RecordType {
+    /// Uppercase name as used by the API and in zone files
+    pub fn as_str(&self) -> &str {
+        match self {
+            RecordType::A => "A",
+            RecordType::Aaaa => "AAAA",
+            RecordType::AkamaiCdn => "AKAMAICDN",
+            RecordType::AkamaiTlc => "AKAMAITLC",
+            RecordType::Caa => "CAA",
+            RecordType::Cname => "CNAME",
+            RecordType::Mx => "MX",
+            RecordType::Ns => "NS",
+            RecordType::Ptr => "PTR",
+            RecordType::Soa => "SOA",
+            RecordType::Srv => "SRV",
+            RecordType::Txt => "TXT",
+            RecordType::Other(name) => name,
+        }
+    }
+}
+
+impl FromStr for RecordType {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.to_ascii_uppercase();
+        Ok(match name.as_str() {
+            "A" => RecordType::A,
+            "AAAA" => RecordType::Aaaa,
+            "AKAMAICDN" => RecordType::AkamaiCdn,
+            "AKAMAITLC" => RecordType::AkamaiTlc,
+            "CAA" => RecordType::Caa,
+            "CNAME" => RecordType::Cname,
+            "MX" => RecordType::Mx,
+            "NS" => RecordType::Ns,
+            "PTR" => RecordType::Ptr,
+            "SOA" => RecordType::Soa,
+            "SRV" => RecordType::Srv,
+            "TXT" => RecordType::Txt,
+            _ => RecordType::Other(name),
+        })
+    }
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for RecordType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RecordType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(name.parse().unwrap_or_else(|never| match never {}))
+    }
+}
+
+/// All records of one name and type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordSet {
+    /// Fully qualified owner name without the trailing dot, e.g. `www.example.com`
+    pub name: String,
+    /// Record type
+    #[serde(rename = "type")]
+    pub record_type: RecordType,
+    /// Time to live in seconds
+    pub ttl: u32,
+    /// Record data in presentation format, one entry per record
+    pub rdata: Vec<String>,
+}
+
+impl RecordSet {
+    /// A record set from presentation-format data
+    pub fn new<I, S>(name: impl Into<String>, record_type: RecordType, ttl: u32, rdata: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            record_type,
+            ttl,
+            rdata: rdata.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// A record set from typed data, which must all be of one type
+    pub fn from_data(name: impl Into<String>, ttl: u32, data: Vec<RecordData>) -> Result<Self> {
+        let name = name.into();
+        let record_type = match data.first() {
+            Some(first) => first.record_type(),
+            None => {
+                return Err(EdgeGridError::Config(format!(
+                    "record set {} has no records",
+                    name
+                )))
+            }
+        };
+        if let Some(other) = data.iter().find(|d| d.record_type() != record_type) {
+            return Err(EdgeGridError::Config(format!(
+                "record set {} mixes {} and {} records",
+                name,
+                record_type,
+                other.record_type()
+            )));
+        }
+
+        Ok(Self {
+            name,
+            record_type,
+            ttl,
+            rdata: data.iter().map(RecordData::to_string).collect(),
+        })
+    }
+
+    /// The record data parsed according to the record type
+    pub fn data(&self) -> Result<Vec<RecordData>> {
+        self.rdata
+            .iter()
+            .map(|rdata| RecordData::parse(&self.record_type, rdata))
+            .collect()
+    }
+}
+
+/// Typed data of a single record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordData {
+    /// IPv4 address
+    A(Ipv4Addr),
+    /// IPv6 address
+    Aaaa(Ipv6Addr),
+    /// Canonical name
+    Cname(String),
+    /// Name server
+    Ns(String),
+    /// Reverse lookup target
+    Ptr(String),
+    /// Text, split into 255-byte strings when written
+    Txt(String),
+    /// Mail exchange; lower preferences are tried first
+    Mx { preference: u16, exchange: String },
+    /// Service location
+    Srv {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    /// Certification authority authorization, e.g. tag `issue`
+    Caa {
+        flags: u8,
+        tag: String,
+        value: String,
+    },
+    /// Data of any other type in presentation format
+    Other(RecordType, String),
+}
+
+impl RecordData {
+    /// Type of record this data belongs to
+    pub fn record_type(&self) -> RecordType {
+        match self {
+            RecordData::A(_) => RecordType::A,
+            RecordData::Aaaa(_) => RecordType::Aaaa,
+            RecordData::Cname(_) => RecordType::Cname,
+            RecordData::Ns(_) => RecordType::Ns,
+            RecordData::Ptr(_) => RecordType::Ptr,
+            RecordData::Txt(_) => RecordType::Txt,
+            RecordData::Mx { .. } => RecordType::Mx,
+            RecordData::Srv { .. } => RecordType::Srv,
+            RecordData::Caa { .. } => RecordType::Caa,
+            RecordData::Other(record_type, _) => record_type.clone(),
+        }
+    }
+
+    /// Parse presentation-format data of `record_type`
+    pub fn parse(record_type: &RecordType, rdata: &str) -> Result<Self> {
+        let invalid =
+            || EdgeGridError::Config(format!("invalid {} record data '{}'", record_type, rdata));
+        let fields = tokenize(rdata).map_err(|_| invalid())?;
+        let name = |index: usize| fields.get(index).cloned().ok_or_else(invalid);
+
+        Ok(match record_type {
+            RecordType::A => RecordData::A(rdata.trim().parse().map_err(|_| invalid())?),
+            RecordType::Aaaa => RecordData::Aaaa(rdata.trim().parse().map_err(|_| invalid())?),
+            RecordType::Cname => RecordData::Cname(name(0)?),
+            RecordType::Ns => RecordData::Ns(name(0)?),
+            RecordType::Ptr => RecordData::Ptr(name(0)?),
+            RecordType::Txt => RecordData::Txt(
+                fields
+                    .iter()
+                    .map(|field| unquote(field))
+                    .collect::<Vec<_>>()
+                    .concat(),
+            ),
+            RecordType::Mx => RecordData::Mx {
+                preference: number(&fields, 0).ok_or_else(invalid)?,
+                exchange: name(1)?,
+            },
+            RecordType::Srv => RecordData::Srv {
+                priority: number(&fields, 0).ok_or_else(invalid)?,
+                weight: number(&fields, 1).ok_or_else(invalid)?,
+                port: number(&fields, 2).ok_or_else(invalid)?,
+                target: name(3)?,
+            },
+            RecordType::Caa => RecordData::Caa {
+                flags: number(&fields, 0).ok_or_else(invalid)?,
+                tag: name(1)?,
+                value: unquote(&name(2)?),
+            },
+            other => RecordData::Other(other.clone(), rdata.trim().to_string()),
+        })
+    }
+}
+
+impl fmt::Display for RecordData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordData::A(address) => write!(f, "{}", address),
+            RecordData::Aaaa(address) => write!(f, "{}", address),
+            RecordData::Cname(name) | RecordData::Ns(name) | RecordData::Ptr(name) => {
+                f.write_str(name)
+            }
+            RecordData::Txt(text) => {
+                let strings: Vec<String> = chunks(text).into_iter().map(quote).collect();
+                f.write_str(&strings.join(" "))
+            }
+            RecordData::Mx {
+                preference,
+                exchange,
+            } => write!(f, "{} {}", preference, exchange),
+            RecordData::Srv {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{} {} {} {}", priority, weight, port, target),
+            RecordData::Caa { flags, tag, value } => {
+                write!(f, "{} {} {}", flags, tag, quote(value))
+            }
+            RecordData::Other(_, rdata) => f.write_str(rdata),
+        }
+    }
+}
+
+/// Operation recorded in a changelist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChangeOp {
+    /// Add a record set that does not exist yet
+    Add,
+    /// Replace an existing record set
+    Edit,
+    /// Remove a record set
+    Delete,
+}
+
+/// A record set change to add to a changelist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordSetChange {
+    /// Fully qualified owner name
+    pub name: String,
+    /// Record type
+    #[serde(rename = "type")]
+    pub record_type: RecordType,
+    /// What to do with the record set
+    pub op: ChangeOp,
+    /// Time to live, absent for deletions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u32>,
+    /// Record data, empty for deletions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rdata: Vec<String>,
+}
+
+impl RecordSetChange {
+    fn with(op: ChangeOp, record_set: RecordSet) -> Self {
+        Self {
+            name: record_set.name,
+            record_type: record_set.record_type,
+            op,
+            ttl: Some(record_set.ttl),
+            rdata: record_set.rdata,
+        }
+    }
+
+    /// Add `record_set`
+    pub fn add(record_set: RecordSet) -> Self {
+        Self::with(ChangeOp::Add, record_set)
+    }
+
+    /// Replace the record set of the same name and type with `record_set`
+    pub fn edit(record_set: RecordSet) -> Self {
+        Self::with(ChangeOp::Edit, record_set)
+    }
+
+    /// Delete the record set of `name` and `record_type`
+    pub fn delete(name: impl Into<String>, record_type: RecordType) -> Self {
+        Self {
+            name: name.into(),
+            record_type,
+            op: ChangeOp::Delete,
+            ttl: None,
+            rdata: Vec::new(),
+        }
+    }
+}
+
+/// Pending changes to a zone, applied together when submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changelist {
+    /// Zone the changes apply to
+    pub zone: String,
+    /// Identifies this set of changes
+    #[serde(default)]
+    pub change_tag: String,
+    /// Zone version the changelist was created from
+    #[serde(default)]
+    pub zone_version_id: String,
+    /// When the changelist last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_date: Option<String>,
+    /// Whether the zone changed since the changelist was created
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// A submitted bulk zone creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkZoneRequest {
+    /// Identifier for polling the request
+    pub request_id: String,
+    /// When the request and its results expire
+    #[serde(default)]
+    pub expiration_date: String,
+}
+
+/// Progress of a bulk zone creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkZoneStatus {
+    /// Identifier of the request
+    pub request_id: String,
+    /// Number of zones in the request
+    #[serde(default)]
+    pub zones_submitted: u32,
+    /// Zones created so far
+    #[serde(default)]
+    pub success_count: u32,
+    /// Zones that could not be created
+    #[serde(default)]
+    pub failure_count: u32,
+    /// Whether every zone has been processed
+    #[serde(default)]
+    pub is_complete: bool,
+    /// When the request and its results expire
+    #[serde(default)]
+    pub expiration_date: String,
+}
+
+impl OperationStatus for BulkZoneStatus {
+    fn from_body(body: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    /// Complete even if some zones failed; see [`Dns::bulk_create_result`]
+    fn state(&self) -> OperationState {
+        if self.is_complete {
+            OperationState::Succeeded
+        } else {
+            OperationState::Pending
+        }
+    }
+}
+
+/// Outcome of a bulk zone creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkZoneResult {
+    /// Identifier of the request
+    pub request_id: String,
+    /// Zones that were created
+    #[serde(default)]
+    pub successfully_created_zones: Vec<String>,
+    /// Zones that were not created, and why
+    #[serde(default)]
+    pub failed_zones: Vec<FailedZone>,
+}
+
+/// A zone a bulk request could not create
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedZone {
+    /// Zone name
+    pub zone: String,
+    /// Why it was not created
+    pub failure_reason: String,
+}
+
+#[derive(Deserialize)]
+struct ZonesResponse {
+    #[serde(default)]
+    zones: Vec<Zone>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordSetsBody {
+    #[serde(default)]
+    recordsets: Vec<RecordSet>,
+}
+
+#[derive(Serialize)]
+struct BulkZonesBody<'a> {
+    zones: &'a [Zone],
+}
+
+impl Dns {
+    /// Use `client` for Edge DNS requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// All zones, optionally limited to one contract
+    pub async fn zones(&self, contract_id: Option<&str>) -> Result<Vec<Zone>> {
+        let mut request = self
+            .client
+            .get("/config-dns/v2/zones")
+            .query("showAll", "true");
+        if let Some(contract_id) = contract_id {
+            request = request.query("contractIds", contract_id);
+        }
+        let response: ZonesResponse = read_json(request.send().await?).await?;
+        Ok(response.zones)
+    }
+
+    /// A single zone
+    pub async fn zone(&self, zone: &str) -> Result<Zone> {
+        let response = self.client.get(&zone_path(zone)).send().await?;
+        read_json(response).await
+    }
+
+    /// Create a zone in a contract and, optionally, a group
+    pub async fn create_zone(
+        &self,
+        contract_id: &str,
+        group_id: Option<&str>,
+        zone: &Zone,
+    ) -> Result<Zone> {
+        let mut request = self
+            .client
+            .post("/config-dns/v2/zones")
+            .query("contractId", contract_id);
+        if let Some(group_id) = group_id {
+            request = request.query("gid", group_id);
+        }
+        read_json(request.json(zone).send().await?).await
+    }
+
+    /// Update a zone's settings
+    pub async fn update_zone(&self, zone: &Zone) -> Result<Zone> {
+        let response = self
+            .client
+            .put(&zone_path(&zone.zone))
+            .json(zone)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Start creating many zones at once
+    ///
+    /// Poll the request with [`Dns::bulk_create_operation`] and read which
+    /// zones were created with [`Dns::bulk_create_result`].
+    pub async fn create_zones(
+        &self,
+        contract_id: &str,
+        group_id: Option<&str>,
+        zones: &[Zone],
+    ) -> Result<BulkZoneRequest> {
+        let mut request = self
+            .client
+            .post("/config-dns/v2/zones/create-requests")
+            .query("contractId", contract_id);
+        if let Some(group_id) = group_id {
+            request = request.query("gid", group_id);
+        }
+        let response = request.json(&BulkZonesBody { zones }).send().await?;
+        read_json(response).await
+    }
+
+    /// Poll a bulk zone creation until every zone has been processed
+    pub fn bulk_create_operation(&self, request_id: &str) -> Operation<BulkZoneStatus> {
+        self.client.operation(&format!(
+            "/config-dns/v2/zones/create-requests/{}",
+            request_id
+        ))
+    }
+
+    /// Which zones a completed bulk creation created
+    pub async fn bulk_create_result(&self, request_id: &str) -> Result<BulkZoneResult> {
+        let response = self
+            .client
+            .get(&format!(
+                "/config-dns/v2/zones/create-requests/{}/result",
+                request_id
+            ))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// All record sets of a zone, optionally limited to some types
+    pub async fn record_sets(&self, zone: &str, types: &[RecordType]) -> Result<Vec<RecordSet>> {
+        let mut request = self
+            .client
+            .get(&format!("{}/recordsets", zone_path(zone)))
+            .query("showAll", "true");
+        if !types.is_empty() {
+            let types: Vec<&str> = types.iter().map(RecordType::as_str).collect();
+            request = request.query("types", types.join(","));
+        }
+        let response: RecordSetsBody = read_json(request.send().await?).await?;
+        Ok(response.recordsets)
+    }
+
+    /// Replace every record set of a zone
+    pub async fn replace_record_sets(&self, zone: &str, record_sets: &[RecordSet]) -> Result<()> {
+        let body = RecordSetsBody {
+            recordsets: record_sets.to_vec(),
+        };
+        let response = self
+            .client
+            .put(&format!("{}/recordsets", zone_path(zone)))
+            .json(&body)
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// The record set of `name` and `record_type`
+    pub async fn record_set(
+        &self,
+        zone: &str,
+        name: &str,
+        record_type: &RecordType,
+    ) -> Result<RecordSet> {
+        let response = self
+            .client
+            .get(&record_set_path(zone, name, record_type))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a record set, applying it to the zone immediately
+    pub async fn create_record_set(&self, zone: &str, record_set: &RecordSet) -> Result<RecordSet> {
+        let response = self
+            .client
+            .post(&record_set_path(
+                zone,
+                &record_set.name,
+                &record_set.record_type,
+            ))
+            .json(record_set)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Replace a record set, applying it to the zone immediately
+    pub async fn update_record_set(&self, zone: &str, record_set: &RecordSet) -> Result<RecordSet> {
+        let response = self
+            .client
+            .put(&record_set_path(
+                zone,
+                &record_set.name,
+                &record_set.record_type,
+            ))
+            .json(record_set)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Delete a record set, applying it to the zone immediately
+    pub async fn delete_record_set(
+        &self,
+        zone: &str,
+        name: &str,
+        record_type: &RecordType,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .delete(&record_set_path(zone, name, record_type))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Start a changelist from the zone's current version
+    pub async fn create_changelist(&self, zone: &str) -> Result<Changelist> {
+        let response = self
+            .client
+            .post("/config-dns/v2/changelists")
+            .query("zone", zone)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// The open changelist of a zone
+    pub async fn changelist(&self, zone: &str) -> Result<Changelist> {
+        let response = self.client.get(&changelist_path(zone)).send().await?;
+        read_json(response).await
+    }
+
+    /// Record sets of the zone as they will be once the changelist is submitted
+    pub async fn changelist_record_sets(&self, zone: &str) -> Result<Vec<RecordSet>> {
+        let response = self
+            .client
+            .get(&format!("{}/recordsets", changelist_path(zone)))
+            .send()
+            .await?;
+        let response: RecordSetsBody = read_json(response).await?;
+        Ok(response.recordsets)
+    }
+
+    /// Record a change in the zone's changelist
+    pub async fn add_change(&self, zone: &str, change: &RecordSetChange) -> Result<()> {
+        let response = self
+            .client
+            .post(&format!("{}/recordsets/add-change", changelist_path(zone)))
+            .json(change)
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Apply the changelist as a new zone version
+    pub async fn submit_changelist(&self, zone: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(&format!("{}/submit", changelist_path(zone)))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Discard the changelist
+    pub async fn delete_changelist(&self, zone: &str) -> Result<()> {
+        let response = self.client.delete(&changelist_path(zone)).send().await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// The zone's records as an RFC 1035 master file
+    pub async fn export_zone_file(&self, zone: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(&format!("{}/zone-file", zone_path(zone)))
+            .header(ACCEPT.as_str(), ZONE_FILE_MEDIA_TYPE)
+            .send()
+            .await?;
+        Ok(check_status(response).await?.text().await?)
+    }
+
+    /// Replace the zone's records with those of an RFC 1035 master file
+    pub async fn import_zone_file(&self, zone: &str, zone_file: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(&format!("{}/zone-file", zone_path(zone)))
+            .header(CONTENT_TYPE.as_str(), ZONE_FILE_MEDIA_TYPE)
+            .body(zone_file.to_string())
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
+
+fn zone_path(zone: &str) -> String {
+    format!("/config-dns/v2/zones/{}", zone)
+}
+
+fn record_set_path(zone: &str, name: &str, record_type: &RecordType) -> String {
+    format!("{}/names/{}/types/{}", zone_path(zone), name, record_type)
+}
+
+fn changelist_path(zone: &str) -> String {
+    format!("/config-dns/v2/changelists/{}", zone)
+}
+
+/// Write record sets as an RFC 1035 master file with absolute owner names
+pub fn to_zone_file(origin: &str, record_sets: &[RecordSet]) -> String {
+    let mut text = format!("$ORIGIN {}\n", absolute(origin));
+    for record_set in record_sets {
+        for rdata in &record_set.rdata {
+            text.push_str(&format!(
+                "{} {} IN {} {}\n",
+                absolute(&record_set.name),
+                record_set.ttl,
+                record_set.record_type,
+                rdata
+            ));
+        }
+    }
+    text
+}
+
+/// Read an RFC 1035 master file into record sets
+///
+/// Supports `$ORIGIN` and `$TTL`, comments, parenthesized multi-line records,
+/// `@`, relative and omitted owner names, and TTL and class in either order.
+/// Records of the same name and type are grouped in the order they first
+/// appear, taking the TTL of the first. `$INCLUDE` is not supported.
+pub fn parse_zone_file(text: &str, origin: &str) -> Result<Vec<RecordSet>> {
+    let mut origin = origin.trim_end_matches('.').to_string();
+    let mut default_ttl: Option<u32> = None;
+    let mut last_owner: Option<String> = None;
+    let mut last_ttl: Option<u32> = None;
+    let mut record_sets: Vec<RecordSet> = Vec::new();
+
+    for (number, line) in logical_lines(text)? {
+        let error = |message: &str| {
+            EdgeGridError::Config(format!("zone file line {}: {}", number, message))
+        };
+        let inherits_owner = line.starts_with([' ', '\t']);
+        let mut tokens = tokenize(&line)
+            .map_err(|message| error(&message))?
+            .into_iter();
+
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+        match first.to_ascii_uppercase().as_str() {
+            "$ORIGIN" => {
+                let name = tokens.next().ok_or_else(|| error("$ORIGIN needs a name"))?;
+                origin = qualify(&name, &origin);
+                continue;
+            }
+            "$TTL" => {
+                let ttl = tokens.next().and_then(|ttl| ttl.parse().ok());
+                default_ttl = Some(ttl.ok_or_else(|| error("$TTL needs a number of seconds"))?);
+                continue;
+            }
+            directive if directive.starts_with('$') => {
+                return Err(error(&format!("unsupported directive {}", first)));
+            }
+            _ => {}
+        }
+
+        let mut tokens: Vec<String> = if inherits_owner {
+            std::iter::once(first).chain(tokens).collect()
+        } else {
+            last_owner = Some(qualify(&first, &origin));
+            tokens.collect()
+        };
+        let owner = last_owner
+            .clone()
+            .ok_or_else(|| error("record has no owner name"))?;
+
+        // Up to two of TTL and class, in either order, before the type
+        let mut ttl = None;
+        while tokens.len() > 1 {
+            let token = tokens[0].to_ascii_uppercase();
+            if let Ok(seconds) = token.parse::<u32>() {
+                ttl = Some(seconds);
+            } else if !CLASSES.contains(&token.as_str()) {
+                break;
+            }
+            tokens.remove(0);
+        }
+        if tokens.is_empty() {
+            return Err(error("record has no type"));
+        }
+
+        let record_type: RecordType = tokens
+            .remove(0)
+            .parse()
+            .unwrap_or_else(|never| match never {});
+        let ttl = ttl
+            .or(default_ttl)
+            .or(last_ttl)
+            .ok_or_else(|| error("record has no TTL and no $TTL was set"))?;
+        last_ttl = Some(ttl);
+        if tokens.is_empty() {
+            return Err(error("record has no data"));
+        }
+        let rdata = tokens.join(" ");
+
+        match record_sets
+            .iter_mut()
+            .find(|set| set.name == owner && set.record_type == record_type)
+        {
+            Some(set) => set.rdata.push(rdata),
+            None => record_sets.push(RecordSet::new(owner, record_type, ttl, [rdata])),
+        }
+    }
+
+    Ok(record_sets)
+}
+
+/// Join parenthesized records into one line and drop comments, keeping the
+/// number of the line each record starts on
+fn logical_lines(text: &str) -> Result<Vec<(usize, String)>> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+
+    for (index, line) in text.lines().enumerate() {
+        if depth == 0 {
+            start = index + 1;
+        }
+        let mut in_quotes = false;
+        let mut escaped = false;
+        for c in line.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => break,
+                '(' if !in_quotes => {
+                    depth += 1;
+                    current.push(' ');
+                    continue;
+                }
+                ')' if !in_quotes => {
+                    depth = depth.checked_sub(1).ok_or_else(|| {
+                        EdgeGridError::Config(format!(
+                            "zone file line {}: unbalanced ')'",
+                            index + 1
+                        ))
+                    })?;
+                    current.push(' ');
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+
+        if depth == 0 {
+            let line = std::mem::take(&mut current);
+            if !line.trim().is_empty() {
+                lines.push((start, line));
+            }
+        } else {
+            current.push(' ');
+        }
+    }
+
+    if depth != 0 {
+        return Err(EdgeGridError::Config(format!(
+            "zone file line {}: unclosed '('",
+            start
+        )));
+    }
+    Ok(lines)
+}
+
+fn number<T: FromStr>(fields: &[String], index: usize) -> Option<T> {
+    fields.get(index)?.parse().ok()
+}
+
+/// Split on whitespace, keeping quoted strings (with their quotes) together
+fn tokenize(text: &str) -> std::result::Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = String::new();
+        if c == '"' {
+            token.push(chars.next().unwrap_or('"'));
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                token.push(c);
+                match c {
+                    '\\' => token.extend(chars.next()),
+                    '"' => {
+                        closed = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            if !closed {
+                return Err("unterminated quoted string".to_string());
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Resolve `@` and relative names against `origin`, without a trailing dot
+fn qualify(name: &str, origin: &str) -> String {
+    if name == "@" {
+        origin.to_string()
+    } else if let Some(absolute) = name.strip_suffix('.') {
+        absolute.to_string()
+    } else if origin.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, origin)
+    }
+}
+
+fn absolute(name: &str) -> String {
+    format!("{}.", name.trim_end_matches('.'))
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(token: &str) -> String {
+    let Some(inner) = token
+        .strip_prefix('"')
+        .and_then(|token| token.strip_suffix('"'))
+    else {
+        return token.to_string();
+    };
+
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+/// Split text into character-strings of at most 255 bytes on char boundaries
+fn chunks(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        return vec![""];
+    }
+
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = rest.len().min(MAX_CHARACTER_STRING);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_data_round_trip() {
+        let data = [
+            RecordData::A("192.0.2.1".parse().unwrap()),
+            RecordData::Aaaa("2001:db8::1".parse().unwrap()),
+            RecordData::Cname("target.example.net.".to_string()),
+            RecordData::Txt("v=spf1 include:\"x\" -all".to_string()),
+            RecordData::Mx {
+                preference: 10,
+                exchange: "mail.example.com.".to_string(),
+            },
+            RecordData::Srv {
+                priority: 0,
+                weight: 5,
+                port: 5060,
+                target: "sip.example.com.".to_string(),
+            },
+            RecordData::Caa {
+                flags: 0,
+                tag: "issue".to_string(),
+                value: "letsencrypt.org".to_string(),
+            },
+        ];
+
+        for record in data {
+            let text = record.to_string();
+            let parsed = RecordData::parse(&record.record_type(), &text).unwrap();
+            assert_eq!(parsed, record, "{}", text);
+        }
+        assert_eq!(
+            RecordData::Caa {
+                flags: 128,
+                tag: "iodef".to_string(),
+                value: "mailto:security@example.com".to_string()
+            }
+            .to_string(),
+            r#"128 iodef "mailto:security@example.com""#
+        );
+    }
+
+    #[test]
+    fn test_long_txt_is_split_into_character_strings() {
+        let key = "k".repeat(300);
+        let text = RecordData::Txt(key.clone()).to_string();
+        assert_eq!(
+            text,
+            format!("\"{}\" \"{}\"", "k".repeat(255), "k".repeat(45))
+        );
+        assert_eq!(
+            RecordData::parse(&RecordType::Txt, &text).unwrap(),
+            RecordData::Txt(key)
+        );
+    }
+
+    #[test]
+    fn test_record_set_from_data_rejects_mixed_types() {
+        let err = RecordSet::from_data(
+            "www.example.com",
+            300,
+            vec![
+                RecordData::A("192.0.2.1".parse().unwrap()),
+                RecordData::Cname("x.example.com.".to_string()),
+            ],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("mixes A and CNAME"));
+    }
+
+    #[test]
+    fn test_parse_zone_file() {
+        let text = r#"
+$TTL 3600
+@   IN SOA a1-1.akam.net. hostmaster.example.com. (
+        2024010101 ; serial
+        3600 600 604800 300 )
+    IN NS  a1-1.akam.net.
+    IN NS  a2-2.akam.net.
+www 300 IN A 192.0.2.1
+    IN 300 A 192.0.2.2
+txt     TXT "v=spf1 -all" ; trailing comment
+$ORIGIN sub.example.com.
+api     CNAME  www.example.com.
+"#;
+        let sets = parse_zone_file(text, "example.com").unwrap();
+        let summary: Vec<_> = sets
+            .iter()
+            .map(|s| {
+                (
+                    s.name.as_str(),
+                    s.record_type.as_str(),
+                    s.ttl,
+                    s.rdata.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("example.com", "SOA", 3600, 1),
+                ("example.com", "NS", 3600, 2),
+                ("www.example.com", "A", 300, 2),
+                ("txt.example.com", "TXT", 3600, 1),
+                ("api.sub.example.com", "CNAME", 3600, 1),
+            ]
+        );
+        assert_eq!(
+            sets[0].rdata[0],
+            "a1-1.akam.net. hostmaster.example.com. 2024010101 3600 600 604800 300"
+        );
+        assert_eq!(sets[3].rdata[0], "\"v=spf1 -all\"");
+    }
+
+    #[test]
+    fn test_zone_file_round_trip() {
+        let sets = vec![
+            RecordSet::new(
+                "example.com",
+                RecordType::Mx,
+                3600,
+                ["10 mail.example.com."],
+            ),
+            RecordSet::new(
+                "www.example.com",
+                RecordType::A,
+                300,
+                ["192.0.2.1", "192.0.2.2"],
+            ),
+            RecordSet::new("txt.example.com", RecordType::Txt, 60, ["\"a; b\""]),
+        ];
+        let text = to_zone_file("example.com", &sets);
+        assert!(text
+            .starts_with("$ORIGIN example.com.\nexample.com. 3600 IN MX 10 mail.example.com.\n"));
+        assert_eq!(parse_zone_file(&text, "ignored.example").unwrap(), sets);
+    }
+
+    #[test]
+    fn test_parse_zone_file_errors() {
+        assert!(parse_zone_file("www IN A 192.0.2.1", "example.com")
+            .unwrap_err()
+            .to_string()
+            .contains("line 1: record has no TTL"));
+        assert!(parse_zone_file("$TTL 60\n@ SOA ( a b", "example.com")
+            .unwrap_err()
+            .to_string()
+            .contains("unclosed"));
+        assert!(parse_zone_file("$INCLUDE other.zone", "example.com").is_err());
+    }
+}
@@ -0,0 +1,727 @@
+//! EdgeKV (v1) API
+//!
+//! Namespaces, groups and items of the EdgeKV key-value store, access tokens
+//! for EdgeWorkers, and an [`Importer`] that uploads many items at once.
+//!
+//! EdgeKV is eventually consistent: a namespace or group can take a while to
+//! become visible after it is created, and concurrent writes to an item can
+//! conflict. The importer retries those errors, see [`is_consistency_error`].
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::edgekv::EdgeKv;
+//! use akamai_edgegrid::{EdgeGridClient, Network};
+//! use serde_json::json;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let edgekv = EdgeKv::new(client);
+//!
+//! edgekv
+//!     .put_item(Network::Staging, "config", "flags", "checkout", &json!({"enabled": true}))
+//!     .await?;
+//! let flags: serde_json::Value = edgekv
+//!     .item(Network::Staging, "config", "flags", "checkout")
+//!     .await?;
+//!
+//! let report = edgekv
+//!     .importer(Network::Staging, "config", "pages")
+//!     .concurrency(4)
+//!     .import_dir("./pages")
+//!     .await?;
+//! println!("{} imported, {} failed", report.imported.len(), report.failed.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json, Network};
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use crate::secret::Secret;
+use chrono::NaiveDate;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::CONTENT_TYPE;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Characters escaped in a path segment: all but the RFC 3986 unreserved ones
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Items uploaded at the same time by default
+pub const DEFAULT_CONCURRENCY: usize = 8;
+/// Retries of an item after a consistency error by default
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// EdgeKV API client
+#[derive(Debug, Clone)]
+pub struct EdgeKv {
+    client: EdgeGridClient,
+}
+
+/// An EdgeKV namespace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespace {
+    /// Namespace name
+    #[serde(rename = "namespace")]
+    pub name: String,
+    /// How long items are kept after their last update; 0 keeps them forever
+    #[serde(default)]
+    pub retention_in_seconds: u64,
+    /// Where the data is stored, e.g. `US` or `EU`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo_location: Option<String>,
+    /// Access group allowed to manage the namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Namespace {
+    /// A namespace that keeps items forever
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            retention_in_seconds: 0,
+            geo_location: None,
+            group_id: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// Delete items `retention` after their last update
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention_in_seconds = retention.as_secs();
+        self
+    }
+
+    /// Store the data in `geo_location`, e.g. `EU`
+    pub fn geo_location(mut self, geo_location: impl Into<String>) -> Self {
+        self.geo_location = Some(geo_location.into());
+        self
+    }
+
+    /// Restrict management to an access group
+    pub fn group_id(mut self, group_id: i64) -> Self {
+        self.group_id = Some(group_id);
+        self
+    }
+}
+
+/// What an access token allows in a namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    /// Read items
+    #[serde(rename = "r")]
+    Read,
+    /// Write items
+    #[serde(rename = "w")]
+    Write,
+    /// Delete items
+    #[serde(rename = "d")]
+    Delete,
+}
+
+/// An access token to create for EdgeWorkers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenRequest {
+    /// Token name
+    pub name: String,
+    /// Whether the token works on the production network
+    pub allow_on_production: bool,
+    /// Whether the token works on the staging network
+    pub allow_on_staging: bool,
+    /// Last day the token is valid, as `YYYY-MM-DD`
+    pub expiry: String,
+    /// Permissions per namespace
+    pub namespace_permissions: BTreeMap<String, Vec<Permission>>,
+}
+
+impl TokenRequest {
+    /// A token valid until `expiry` on no network and in no namespace
+    pub fn new(name: impl Into<String>, expiry: NaiveDate) -> Self {
+        Self {
+            name: name.into(),
+            allow_on_production: false,
+            allow_on_staging: false,
+            expiry: expiry.format("%Y-%m-%d").to_string(),
+            namespace_permissions: BTreeMap::new(),
+        }
+    }
+
+    /// Allow the token on `network`
+    pub fn allow_on(mut self, network: Network) -> Self {
+        match network {
+            Network::Staging => self.allow_on_staging = true,
+            Network::Production => self.allow_on_production = true,
+        }
+        self
+    }
+
+    /// Grant `permissions` in `namespace`
+    pub fn permit(mut self, namespace: impl Into<String>, permissions: &[Permission]) -> Self {
+        self.namespace_permissions
+            .insert(namespace.into(), permissions.to_vec());
+        self
+    }
+}
+
+/// A newly created access token, the only time its value is available
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessToken {
+    /// Token name
+    pub name: String,
+    /// Last day the token is valid
+    pub expiry: String,
+    /// Token ID
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// Token value, for the EdgeWorkers bundle's `edgekv_tokens.js`
+    pub value: Secret,
+}
+
+/// An existing access token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    /// Token name
+    pub name: String,
+    /// Last day the token is valid
+    #[serde(default)]
+    pub expiry: String,
+    /// Token ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An item value to upload
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemValue {
+    /// Stored as JSON
+    Json(Value),
+    /// Stored as is
+    Text(String),
+}
+
+impl From<Value> for ItemValue {
+    /// JSON strings become text items; other values are stored as JSON
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(text) => ItemValue::Text(text),
+            value => ItemValue::Json(value),
+        }
+    }
+}
+
+/// Uploads many items into one group with bounded concurrency
+#[derive(Debug, Clone)]
+pub struct Importer {
+    edgekv: EdgeKv,
+    network: Network,
+    namespace: String,
+    group: String,
+    concurrency: usize,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+/// Outcome of an import
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Keys uploaded successfully
+    pub imported: Vec<String>,
+    /// Keys that could not be uploaded, with the last error
+    pub failed: Vec<(String, EdgeGridError)>,
+}
+
+impl ImportReport {
+    /// Whether every item was uploaded
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+#[derive(Deserialize)]
+struct NamespacesResponse {
+    namespaces: Vec<Namespace>,
+}
+
+#[derive(Deserialize)]
+struct TokensResponse {
+    tokens: Vec<TokenInfo>,
+}
+
+impl EdgeKv {
+    /// Use `client` for EdgeKV requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// Namespaces on a network
+    pub async fn namespaces(&self, network: Network) -> Result<Vec<Namespace>> {
+        let response = self
+            .client
+            .get(&namespaces_path(network))
+            .query("details", "on")
+            .send()
+            .await?;
+        let response: NamespacesResponse = read_json(response).await?;
+        Ok(response.namespaces)
+    }
+
+    /// A single namespace
+    pub async fn namespace(&self, network: Network, namespace: &str) -> Result<Namespace> {
+        let response = self
+            .client
+            .get(&namespace_path(network, namespace))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a namespace
+    pub async fn create_namespace(
+        &self,
+        network: Network,
+        namespace: &Namespace,
+    ) -> Result<Namespace> {
+        let response = self
+            .client
+            .post(&namespaces_path(network))
+            .json(namespace)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Change a namespace's retention or access group
+    pub async fn update_namespace(
+        &self,
+        network: Network,
+        namespace: &Namespace,
+    ) -> Result<Namespace> {
+        let response = self
+            .client
+            .put(&namespace_path(network, &namespace.name))
+            .json(namespace)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Groups in a namespace; a group exists once it has an item
+    pub async fn groups(&self, network: Network, namespace: &str) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(&format!("{}/groups", namespace_path(network, namespace)))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Keys of the items in a group
+    pub async fn items(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+    ) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(&items_path(network, namespace, group))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// An item, deserialized from JSON
+    pub async fn item<T: DeserializeOwned>(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+    ) -> Result<T> {
+        let response = self
+            .client
+            .get(&item_path(network, namespace, group, key))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// An item as text
+    pub async fn item_text(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+    ) -> Result<String> {
+        let response = self
+            .client
+            .get(&item_path(network, namespace, group, key))
+            .send()
+            .await?;
+        Ok(check_status(response).await?.text().await?)
+    }
+
+    /// Create or replace an item with a JSON value
+    pub async fn put_item<T: Serialize + ?Sized>(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+        value: &T,
+    ) -> Result<()> {
+        let request = self
+            .client
+            .put(&item_path(network, namespace, group, key))
+            .json(value);
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Create or replace an item with a text value
+    pub async fn put_item_text(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+        value: impl Into<String>,
+    ) -> Result<()> {
+        let request = self
+            .client
+            .put(&item_path(network, namespace, group, key))
+            .header(CONTENT_TYPE.as_str(), "text/plain")
+            .body(value.into());
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Delete an item
+    pub async fn delete_item(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .delete(&item_path(network, namespace, group, key))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Create an access token for EdgeWorkers
+    pub async fn create_token(&self, request: &TokenRequest) -> Result<AccessToken> {
+        let response = self
+            .client
+            .post("/edgekv/v1/tokens")
+            .json(request)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Access tokens, without their values
+    pub async fn tokens(&self) -> Result<Vec<TokenInfo>> {
+        let response: TokensResponse =
+            read_json(self.client.get("/edgekv/v1/tokens").send().await?).await?;
+        Ok(response.tokens)
+    }
+
+    /// Revoke an access token
+    pub async fn revoke_token(&self, name: &str) -> Result<()> {
+        let response = self.client.delete(&token_path(name)).send().await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Upload items into a group in bulk
+    pub fn importer(
+        &self,
+        network: Network,
+        namespace: impl Into<String>,
+        group: impl Into<String>,
+    ) -> Importer {
+        Importer {
+            edgekv: self.clone(),
+            network,
+            namespace: namespace.into(),
+            group: group.into(),
+            concurrency: DEFAULT_CONCURRENCY,
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+
+    async fn put_value(
+        &self,
+        network: Network,
+        namespace: &str,
+        group: &str,
+        key: &str,
+        value: &ItemValue,
+    ) -> Result<()> {
+        match value {
+            ItemValue::Json(value) => self.put_item(network, namespace, group, key, value).await,
+            ItemValue::Text(text) => {
+                self.put_item_text(network, namespace, group, key, text.as_str())
+                    .await
+            }
+        }
+    }
+}
+
+impl Importer {
+    /// Upload at most `concurrency` items at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Retry an item up to `max_retries` times, waiting `initial_delay`
+    /// before the first retry and doubling the wait after each one
+    pub fn retries(mut self, max_retries: u32, initial_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = initial_delay;
+        self
+    }
+
+    /// Upload each member of a JSON object as an item
+    ///
+    /// String members are stored as text, anything else as JSON.
+    pub async fn import_map(&self, items: Map<String, Value>) -> Result<ImportReport> {
+        Ok(self
+            .import(items.into_iter().map(|(key, value)| (key, value.into())))
+            .await)
+    }
+
+    /// Upload the members of a JSON object read from a file
+    pub async fn import_json_file(&self, path: impl AsRef<Path>) -> Result<ImportReport> {
+        let path = path.as_ref();
+        let content = tokio::fs::read(path).await?;
+        match serde_json::from_slice(&content)? {
+            Value::Object(items) => self.import_map(items).await,
+            _ => Err(EdgeGridError::Config(format!(
+                "{} does not contain a JSON object",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Upload every file in a directory, keyed by file name
+    ///
+    /// Files ending in `.json` must be valid JSON and are stored as JSON;
+    /// other files are stored as text. Subdirectories and hidden files are
+    /// skipped.
+    pub async fn import_dir(&self, path: impl AsRef<Path>) -> Result<ImportReport> {
+        let mut items = Vec::new();
+        let mut entries = tokio::fs::read_dir(path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let key = entry.file_name().to_string_lossy().into_owned();
+            if key.starts_with('.') || !entry.file_type().await?.is_file() {
+                continue;
+            }
+
+            let content = tokio::fs::read(entry.path()).await?;
+            let value = if key.ends_with(".json") {
+                ItemValue::Json(serde_json::from_slice(&content)?)
+            } else {
+                ItemValue::Text(String::from_utf8(content).map_err(|_| {
+                    EdgeGridError::Config(format!("{} is not UTF-8", entry.path().display()))
+                })?)
+            };
+            items.push((key, value));
+        }
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(self.import(items).await)
+    }
+
+    /// Upload `items`, collecting failures instead of stopping at the first
+    pub async fn import(
+        &self,
+        items: impl IntoIterator<Item = (String, ItemValue)>,
+    ) -> ImportReport {
+        let mut report = ImportReport::default();
+        let mut uploads = JoinSet::new();
+
+        for (key, value) in items {
+            if uploads.len() >= self.concurrency {
+                if let Some(outcome) = uploads.join_next().await {
+                    record(&mut report, outcome);
+                }
+            }
+            let importer = self.clone();
+            uploads.spawn(async move {
+                let result = importer.upload(&key, &value).await;
+                (key, result)
+            });
+        }
+        while let Some(outcome) = uploads.join_next().await {
+            record(&mut report, outcome);
+        }
+
+        report.imported.sort();
+        report
+    }
+
+    async fn upload(&self, key: &str, value: &ItemValue) -> Result<()> {
+        let mut delay = self.retry_delay;
+        let mut retries = 0;
+        loop {
+            match self
+                .edgekv
+                .put_value(self.network, &self.namespace, &self.group, key, value)
+                .await
+            {
+                Err(e) if retries < self.max_retries && is_consistency_error(&e) => {
+                    retries += 1;
+                    tracing::debug!(key, retries, error = %e, "retrying EdgeKV item");
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn record(
+    report: &mut ImportReport,
+    outcome: std::result::Result<(String, Result<()>), tokio::task::JoinError>,
+) {
+    match outcome {
+        Ok((key, Ok(()))) => report.imported.push(key),
+        Ok((key, Err(e))) => report.failed.push((key, e)),
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Whether `error` is worth retrying because EdgeKV has not caught up yet
+///
+/// Besides rate limiting and server errors, EdgeKV answers 404 while a new
+/// namespace or group propagates and 409 when writes to an item overlap.
+pub fn is_consistency_error(error: &EdgeGridError) -> bool {
+    error.is_retryable()
+        || error
+            .problem()
+            .is_some_and(|problem| matches!(problem.status, 404 | 409))
+}
+
+fn namespaces_path(network: Network) -> String {
+    format!("/edgekv/v1/networks/{}/namespaces", network)
+}
+
+fn namespace_path(network: Network, namespace: &str) -> String {
+    format!("{}/{}", namespaces_path(network), segment(namespace))
+}
+
+fn items_path(network: Network, namespace: &str, group: &str) -> String {
+    format!(
+        "{}/groups/{}/items",
+        namespace_path(network, namespace),
+        segment(group)
+    )
+}
+
+fn item_path(network: Network, namespace: &str, group: &str, key: &str) -> String {
+    format!("{}/{}", items_path(network, namespace, group), segment(key))
+}
+
+fn token_path(name: &str) -> String {
+    format!("/edgekv/v1/tokens/{}", segment(name))
+}
+
+/// Percent-encode a name for use as a single path segment
+fn segment(name: &str) -> String {
+    utf8_percent_encode(name, SEGMENT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_token_request_body() {
+        let request = TokenRequest::new("worker", NaiveDate::from_ymd_opt(2027, 3, 1).unwrap())
+            .allow_on(Network::Staging)
+            .permit("config", &[Permission::Read, Permission::Write]);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "name": "worker",
+                "allowOnProduction": false,
+                "allowOnStaging": true,
+                "expiry": "2027-03-01",
+                "namespacePermissions": {"config": ["r", "w"]}
+            })
+        );
+    }
+
+    #[test]
+    fn test_item_values_from_json() {
+        assert_eq!(
+            ItemValue::from(json!("plain")),
+            ItemValue::Text("plain".into())
+        );
+        assert_eq!(
+            ItemValue::from(json!({"a": 1})),
+            ItemValue::Json(json!({"a": 1}))
+        );
+    }
+
+    #[test]
+    fn test_consistency_errors() {
+        let api = |status| EdgeGridError::Api(Box::new(crate::Problem::from_body(status, b"")));
+        assert!(is_consistency_error(&api(404)));
+        assert!(is_consistency_error(&api(409)));
+        assert!(is_consistency_error(&api(429)));
+        assert!(!is_consistency_error(&api(400)));
+        assert!(!is_consistency_error(&EdgeGridError::Config("bad".into())));
+    }
+
+    #[test]
+    fn test_item_path_encodes_names() {
+        let path = item_path(Network::Staging, "config", "page flags", "a b?c#d/é");
+        assert_eq!(
+            path,
+            "/edgekv/v1/networks/staging/namespaces/config/groups/page%20flags/items/a%20b%3Fc%23d%2F%C3%A9"
+        );
+
+        let url = url::Url::parse("https://example.luna.akamaiapis.net")
+            .unwrap()
+            .join(&path)
+            .unwrap();
+        assert_eq!(url.path(), path);
+        assert_eq!(url.query(), None);
+        assert_eq!(url.fragment(), None);
+
+        assert_eq!(token_path("worker #2"), "/edgekv/v1/tokens/worker%20%232");
+    }
+}
@@ -0,0 +1,623 @@
+//! Editable `.edgerc` documents
+//!
+//! [`Edgerc`] keeps every line of the file it was parsed from, so sections can
+//! be added, updated and removed without losing comments, blank lines or the
+//! original ordering. Lines that are not touched are written back verbatim.
+
+use crate::config::{
+    parse_section_config, parse_value, resolve_home_path, EdgeGridConfig, MAX_BODY,
+};
+use crate::error::{EdgeGridError, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A single line of an `.edgerc` file
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    /// Blank line, comment or anything else that is written back verbatim
+    Other(String),
+    /// Section header such as `[default]`
+    Section { raw: String, name: String },
+    /// `key = value` pair; `raw` is `None` once the value has been modified
+    Entry {
+        raw: Option<String>,
+        key: String,
+        value: String,
+    },
+}
+
+impl Line {
+    fn parse(raw: &str) -> Self {
+        let line = raw.trim();
+
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            return Line::Other(raw.to_string());
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            return Line::Section {
+                raw: raw.to_string(),
+                name: line[1..line.len() - 1].to_string(),
+            };
+        }
+
+        if let Some(eq_pos) = line.find('=') {
+            return Line::Entry {
+                raw: Some(raw.to_string()),
+                key: line[..eq_pos].trim().to_string(),
+                value: parse_value(&line[eq_pos + 1..]),
+            };
+        }
+
+        Line::Other(raw.to_string())
+    }
+
+    fn is_trivia(&self) -> bool {
+        matches!(self, Line::Other(_))
+    }
+}
+
+/// Quote `value` when it would not otherwise read back unchanged
+///
+/// Unquoted values lose surrounding whitespace and everything after a `;`,
+/// and values wrapped in quotes have them removed.
+fn format_value(value: &str) -> String {
+    let needs_quotes = value != value.trim()
+        || value.contains([';', '#'])
+        || value.starts_with(['"', '\''])
+        || value.ends_with(['"', '\'']);
+    if needs_quotes {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Normalize key aliases the same way the `.edgerc` parser does
+fn canonical_key(key: &str) -> &str {
+    if key == "max-body" {
+        "max_body"
+    } else {
+        key
+    }
+}
+
+/// An `.edgerc` file that can be inspected, edited and saved
+///
+/// ```rust,no_run
+/// use akamai_edgegrid::{EdgeGridConfig, Edgerc};
+///
+/// let mut edgerc = Edgerc::load("~/.edgerc")?;
+/// let config = EdgeGridConfig::new(
+///     "akab-client-token".to_string(),
+///     "client-secret".to_string(),
+///     "akab-access-token".to_string(),
+///     "akab-host.luna.akamaiapis.net".to_string(),
+/// );
+/// edgerc.set_section("team-a", &config)?;
+/// edgerc.save()?;
+/// # Ok::<(), akamai_edgegrid::EdgeGridError>(())
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Edgerc {
+    lines: Vec<Line>,
+    path: Option<PathBuf>,
+    newline: &'static str,
+    trailing_newline: bool,
+}
+
+impl Edgerc {
+    /// Create an empty document
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            path: None,
+            newline: "\n",
+            trailing_newline: true,
+        }
+    }
+
+    /// Parse a document from the contents of an `.edgerc` file
+    pub fn parse(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(Line::parse).collect(),
+            path: None,
+            newline: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+        }
+    }
+
+    /// Load a document from disk, remembering the path for [`Edgerc::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = resolve_home_path(path)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| EdgeGridError::Config(format!("Cannot read .edgerc file: {}", e)))?;
+
+        let mut edgerc = Self::parse(&content);
+        edgerc.path = Some(path);
+        Ok(edgerc)
+    }
+
+    /// Load a document from disk, or start an empty one if the file does not exist yet
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        let path = resolve_home_path(path)?;
+        if path.exists() {
+            return Self::load(path);
+        }
+
+        let mut edgerc = Self::new();
+        edgerc.path = Some(path);
+        Ok(edgerc)
+    }
+
+    /// Path the document was loaded from, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Names of all sections, in file order
+    pub fn sections(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            if let Line::Section { name, .. } = line {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Whether a section with the given name exists
+    pub fn contains_section(&self, section: &str) -> bool {
+        self.section_header(section).is_some()
+    }
+
+    /// Get the value of a key within a section
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        let key = canonical_key(key);
+        self.section_entries(section)
+            .into_iter()
+            .rev()
+            .find_map(|idx| match &self.lines[idx] {
+                Line::Entry { key: k, value, .. } if canonical_key(k) == key => {
+                    Some(value.as_str())
+                }
+                _ => None,
+            })
+    }
+
+    /// All key-value pairs of a section, with later duplicates taking precedence
+    pub fn section_values(&self, section: &str) -> Option<HashMap<String, String>> {
+        self.section_header(section)?;
+
+        let mut values = HashMap::new();
+        for idx in self.section_entries(section) {
+            if let Line::Entry { key, value, .. } = &self.lines[idx] {
+                values.insert(canonical_key(key).to_string(), value.clone());
+            }
+        }
+        Some(values)
+    }
+
+    /// Build an [`EdgeGridConfig`] from a section
+    pub fn config(&self, section: &str) -> Result<EdgeGridConfig> {
+        let values = self
+            .section_values(section)
+            .ok_or_else(|| EdgeGridError::InvalidSection(section.to_string()))?;
+        parse_section_config(&values)
+    }
+
+    /// Set a key within a section, creating the section if needed
+    ///
+    /// An existing key is updated in place; a new key is added after the
+    /// last key of the section. Values are quoted when needed so that they
+    /// read back unchanged; values containing line breaks are rejected.
+    pub fn set(&mut self, section: &str, key: &str, value: impl Into<String>) -> Result<()> {
+        let value = value.into();
+        if value.contains(['\n', '\r']) {
+            return Err(EdgeGridError::Config(format!(
+                "Value for '{}' contains a line break",
+                key
+            )));
+        }
+        let wanted = canonical_key(key);
+
+        let existing = self
+            .section_entries(section)
+            .into_iter()
+            .rev()
+            .find(|&idx| matches!(&self.lines[idx], Line::Entry { key: k, .. } if canonical_key(k) == wanted));
+
+        if let Some(idx) = existing {
+            if let Line::Entry { raw, value: v, .. } = &mut self.lines[idx] {
+                if *v != value {
+                    *v = value;
+                    *raw = None;
+                }
+            }
+            return Ok(());
+        }
+
+        let insert_at = match self.section_header(section) {
+            Some(header) => {
+                let end = self.section_end(header);
+                let mut at = end;
+                while at > header + 1 && self.lines[at - 1].is_trivia() {
+                    at -= 1;
+                }
+                at
+            }
+            None => {
+                self.push_section(section);
+                self.lines.len()
+            }
+        };
+
+        self.lines.insert(
+            insert_at,
+            Line::Entry {
+                raw: None,
+                key: key.to_string(),
+                value,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a key from a section, returning its previous value
+    pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+        let wanted = canonical_key(key);
+        let mut removed = None;
+
+        for idx in self.section_entries(section).into_iter().rev() {
+            if matches!(&self.lines[idx], Line::Entry { key: k, .. } if canonical_key(k) == wanted)
+            {
+                if let Line::Entry { value, .. } = self.lines.remove(idx) {
+                    removed.get_or_insert(value);
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Add or update a section with the credentials from `config`
+    ///
+    /// The host is written without the default `https://` scheme; a plain
+    /// `http://` loopback host keeps its scheme so that it reads back the
+    /// same. `max_body` and `account_switch_key` are only written when they
+    /// differ from the defaults.
+    pub fn set_section(&mut self, section: &str, config: &EdgeGridConfig) -> Result<()> {
+        let host = config
+            .host
+            .strip_prefix("https://")
+            .unwrap_or(&config.host)
+            .trim_end_matches('/');
+
+        self.set(section, "client_secret", config.client_secret.expose())?;
+        self.set(section, "host", host)?;
+        self.set(section, "access_token", config.access_token.expose())?;
+        self.set(section, "client_token", config.client_token.expose())?;
+
+        if config.max_body != MAX_BODY {
+            self.set(section, "max_body", config.max_body.to_string())?;
+        } else if self.get(section, "max_body").is_some() {
+            self.remove(section, "max_body");
+        }
+
+        match &config.account_switch_key {
+            Some(key) => self.set(section, "account_switch_key", key.as_str())?,
+            None => {
+                self.remove(section, "account_switch_key");
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a section and all of its keys, returning whether it existed
+    ///
+    /// Comments directly above the next section header are kept, since they
+    /// usually describe that section.
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        let mut removed = false;
+
+        while let Some(header) = self.section_header(section) {
+            let end = self.section_end(header);
+            let mut keep_from = end;
+            if end < self.lines.len() {
+                while keep_from > header + 1 && self.lines[keep_from - 1].is_trivia() {
+                    keep_from -= 1;
+                }
+            }
+            self.lines.drain(header..keep_from);
+            removed = true;
+        }
+
+        removed
+    }
+
+    /// Save the document back to the path it was loaded from
+    pub fn save(&self) -> Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| EdgeGridError::Config("No path to save .edgerc to".to_string()))?;
+        self.save_to(path)
+    }
+
+    /// Save the document to `path`
+    ///
+    /// The file is written to a temporary file next to `path` and renamed into
+    /// place, and is only readable and writable by its owner on Unix.
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = resolve_home_path(path)?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let file_name = path.file_name().ok_or_else(|| {
+            EdgeGridError::Config(format!("Invalid .edgerc path: {}", path.display()))
+        })?;
+
+        let tmp_path = dir.join(format!(".{}.tmp", file_name.to_string_lossy()));
+        let result = write_private(&tmp_path, self.to_string().as_bytes())
+            .and_then(|_| fs::rename(&tmp_path, &path));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result.map_err(EdgeGridError::FileError)
+    }
+
+    fn section_header(&self, section: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .rposition(|line| matches!(line, Line::Section { name, .. } if name == section))
+    }
+
+    /// Index one past the last line belonging to the section starting at `header`
+    fn section_end(&self, header: usize) -> usize {
+        self.lines[header + 1..]
+            .iter()
+            .position(|line| matches!(line, Line::Section { .. }))
+            .map(|pos| header + 1 + pos)
+            .unwrap_or(self.lines.len())
+    }
+
+    /// Indices of all entries in every occurrence of `section`
+    fn section_entries(&self, section: &str) -> Vec<usize> {
+        let mut entries = Vec::new();
+        let mut in_section = false;
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            match line {
+                Line::Section { name, .. } => in_section = name == section,
+                Line::Entry { .. } if in_section => entries.push(idx),
+                _ => {}
+            }
+        }
+
+        entries
+    }
+
+    fn push_section(&mut self, section: &str) {
+        if matches!(self.lines.last(), Some(line) if !matches!(line, Line::Other(raw) if raw.trim().is_empty()))
+        {
+            self.lines.push(Line::Other(String::new()));
+        }
+        self.lines.push(Line::Section {
+            raw: format!("[{}]", section),
+            name: section.to_string(),
+        });
+    }
+}
+
+impl Default for Edgerc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Edgerc {
+    /// Only the path and section names are shown, since values may be credentials
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Edgerc")
+            .field("path", &self.path)
+            .field("sections", &self.sections())
+            .finish()
+    }
+}
+
+impl fmt::Display for Edgerc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, line) in self.lines.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(self.newline)?;
+            }
+            match line {
+                Line::Other(raw) | Line::Section { raw, .. } => f.write_str(raw)?,
+                Line::Entry { raw: Some(raw), .. } => f.write_str(raw)?,
+                Line::Entry {
+                    raw: None,
+                    key,
+                    value,
+                } => write!(f, "{} = {}", key, format_value(value))?,
+            }
+        }
+
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(self.newline)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Edgerc {
+    type Err = EdgeGridError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self::parse(s))
+    }
+}
+
+/// Write `contents` to `path`, creating it with owner-only permissions
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "; Akamai credentials\n\
+[default]\n\
+client_secret = secret0\n\
+host = akab-host0.luna.akamaiapis.net\n\
+access_token = akab-access0\n\
+client_token = akab-client0\n\
+\n\
+# staging account\n\
+[staging]\n\
+client_secret = secret1 ; quoted\n\
+host = akab-host1.luna.akamaiapis.net\n\
+access_token = akab-access1\n\
+client_token = akab-client1\n\
+max-body = 2048\n";
+
+    #[test]
+    fn test_round_trip_is_lossless() {
+        let edgerc = Edgerc::parse(SAMPLE);
+        assert_eq!(edgerc.to_string(), SAMPLE);
+        assert_eq!(edgerc.sections(), vec!["default", "staging"]);
+
+        let staging = edgerc.config("staging").unwrap();
+        assert_eq!(staging.client_secret.expose(), "secret1");
+        assert_eq!(staging.max_body, 2048);
+    }
+
+    #[test]
+    fn test_update_and_add_keys_in_place() {
+        let mut edgerc = Edgerc::parse(SAMPLE);
+        edgerc.set("default", "client_secret", "rotated").unwrap();
+        edgerc
+            .set("default", "account_switch_key", "1-ABC")
+            .unwrap();
+        edgerc.set("staging", "max_body", "4096").unwrap();
+
+        let expected = SAMPLE
+            .replace("client_secret = secret0", "client_secret = rotated")
+            .replace(
+                "client_token = akab-client0\n",
+                "client_token = akab-client0\naccount_switch_key = 1-ABC\n",
+            )
+            .replace("max-body = 2048", "max-body = 4096");
+        assert_eq!(edgerc.to_string(), expected);
+        assert_eq!(edgerc.get("staging", "max-body"), Some("4096"));
+    }
+
+    #[test]
+    fn test_add_and_remove_sections() {
+        let mut edgerc = Edgerc::parse(SAMPLE);
+        let config = EdgeGridConfig::new(
+            "akab-client2".to_string(),
+            "secret2".to_string(),
+            "akab-access2".to_string(),
+            "akab-host2.luna.akamaiapis.net".to_string(),
+        );
+        edgerc.set_section("team", &config).unwrap();
+        assert!(edgerc.to_string().ends_with(
+            "max-body = 2048\n\n[team]\nclient_secret = secret2\nhost = akab-host2.luna.akamaiapis.net\naccess_token = akab-access2\nclient_token = akab-client2\n"
+        ));
+        assert_eq!(edgerc.config("team").unwrap().host, config.host);
+
+        assert!(edgerc.remove_section("default"));
+        assert!(!edgerc.remove_section("default"));
+        assert_eq!(edgerc.sections(), vec!["staging", "team"]);
+        assert!(edgerc
+            .to_string()
+            .starts_with("; Akamai credentials\n\n# staging account\n[staging]\n"));
+    }
+
+    #[test]
+    fn test_values_needing_quotes_round_trip() {
+        let mut edgerc = Edgerc::parse(SAMPLE);
+        for value in ["abc ;def", "abc#def", " padded ", "\"quoted\"", "it's"] {
+            edgerc.set("default", "client_secret", value).unwrap();
+            let reparsed = Edgerc::parse(&edgerc.to_string());
+            assert_eq!(reparsed.get("default", "client_secret"), Some(value));
+        }
+        assert!(edgerc.to_string().contains("client_secret = it's\n"));
+
+        assert!(edgerc
+            .set("default", "client_secret", "line\nbreak")
+            .is_err());
+        assert_eq!(edgerc.get("default", "client_secret"), Some("it's"));
+    }
+
+    #[test]
+    fn test_set_section_round_trips_loopback_http_host() {
+        let mut edgerc = Edgerc::new();
+        let config = EdgeGridConfig::new(
+            "akab-client".to_string(),
+            "secret;with#comment chars".to_string(),
+            "akab-access".to_string(),
+            "http://127.0.0.1:8080".to_string(),
+        );
+        edgerc.set_section("local", &config).unwrap();
+
+        let reparsed = Edgerc::parse(&edgerc.to_string());
+        assert_eq!(reparsed.get("local", "host"), Some("http://127.0.0.1:8080"));
+        let round_trip = reparsed.config("local").unwrap();
+        assert_eq!(round_trip.host, config.host);
+        assert_eq!(round_trip.client_secret, config.client_secret);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_uses_owner_only_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".edgerc");
+        fs::write(&path, SAMPLE).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut edgerc = Edgerc::load(&path).unwrap();
+        edgerc.set("default", "client_secret", "rotated").unwrap();
+        edgerc.save().unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            Edgerc::load(&path).unwrap().get("default", "client_secret"),
+            Some("rotated")
+        );
+    }
+}
@@ -0,0 +1,555 @@
+//! EdgeWorkers (v1) API
+//!
+//! Builds code bundles, uploads them as versions, activates versions on a
+//! network and rolls back to the previously active version.
+//!
+//! A [`Bundle`] is a gzipped tarball holding `bundle.json`, `main.js` and
+//! any modules they import. [`Bundle::from_dir`] builds one reproducibly:
+//! entries are sorted and carry no timestamps or owners, so the same sources
+//! always produce the same archive and checksum.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::edgeworkers::{Bundle, EdgeWorkers};
+//! use akamai_edgegrid::{EdgeGridClient, Network};
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let edgeworkers = EdgeWorkers::new(client);
+//!
+//! let bundle = Bundle::from_dir("./worker")?;
+//! edgeworkers.upload(4242, &bundle).await?;
+//! let activation = edgeworkers
+//!     .activate(4242, Network::Staging, bundle.version())
+//!     .await?;
+//! edgeworkers
+//!     .activation_operation(4242, activation.activation_id)
+//!     .wait()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json, Network};
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use crate::operation::{Operation, OperationState, OperationStatus};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Largest compressed bundle the API accepts
+pub const MAX_BUNDLE_BYTES: usize = 1024 * 1024;
+
+/// Manifest file every bundle must contain
+pub const MANIFEST: &str = "bundle.json";
+/// Entry point every bundle must contain
+pub const MAIN: &str = "main.js";
+
+/// Functions the EdgeWorkers runtime calls; `main.js` must export at least one
+const EVENT_HANDLERS: &[&str] = &[
+    "onClientRequest",
+    "onOriginRequest",
+    "onOriginResponse",
+    "onClientResponse",
+    "responseProvider",
+];
+
+/// EdgeWorkers API client
+#[derive(Debug, Clone)]
+pub struct EdgeWorkers {
+    client: EdgeGridClient,
+}
+
+/// An EdgeWorker ID, the container for versions of one worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeWorker {
+    /// EdgeWorker ID
+    pub edge_worker_id: i64,
+    /// Worker name
+    pub name: String,
+    /// Access group owning the worker
+    #[serde(default)]
+    pub group_id: i64,
+    /// Resource tier limiting CPU and memory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_tier_id: Option<i64>,
+    /// Members not modelled above, e.g. `createdBy` or `lastModifiedTime`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An uploaded bundle version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    /// EdgeWorker the version belongs to
+    pub edge_worker_id: i64,
+    /// `edgeworker-version` from the bundle's manifest
+    pub version: String,
+    /// SHA-256 of the uploaded archive
+    #[serde(default)]
+    pub checksum: String,
+    /// Order in which versions were uploaded
+    #[serde(default)]
+    pub sequence_number: u64,
+    /// When the version was uploaded
+    #[serde(default)]
+    pub created_time: String,
+    /// Members not modelled above, e.g. `createdBy`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A version activation on a network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activation {
+    /// Activation ID
+    pub activation_id: i64,
+    /// EdgeWorker being activated
+    pub edge_worker_id: i64,
+    /// Version being activated
+    pub version: String,
+    /// Network the version is activated on
+    pub network: Network,
+    /// `PRESUBMIT`, `PENDING`, `IN_PROGRESS`, `COMPLETE` or `ABORTED`
+    pub status: String,
+    /// When the activation was requested
+    #[serde(default)]
+    pub created_time: String,
+    /// Members not modelled above, e.g. `createdBy` or `note`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl OperationStatus for Activation {
+    fn from_body(body: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn state(&self) -> OperationState {
+        match self.status.as_str() {
+            "COMPLETE" => OperationState::Succeeded,
+            "ABORTED" | "ERROR" => OperationState::Failed(format!(
+                "activation {} of version {} is {}",
+                self.activation_id, self.version, self.status
+            )),
+            _ => OperationState::Pending,
+        }
+    }
+}
+
+/// The manifest of a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version the bundle is uploaded as
+    #[serde(rename = "edgeworker-version")]
+    pub edgeworker_version: String,
+    /// Description of the version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Members not modelled above, e.g. `api-version` or `misc`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A validated code bundle ready to upload
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    manifest: Manifest,
+    files: Vec<String>,
+    archive: Vec<u8>,
+}
+
+impl Bundle {
+    /// Build a bundle from every file under `dir`
+    ///
+    /// Hidden files and directories such as `.git` are left out. The
+    /// manifest and `main.js` must be at the top of `dir`.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut files = Vec::new();
+        collect_files(dir, Path::new(""), &mut files)?;
+        files.sort();
+
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::best()));
+        let mut contents = Vec::with_capacity(files.len());
+        for relative in &files {
+            let data = fs::read(dir.join(relative))?;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            header.set_entry_type(tar::EntryType::Regular);
+            builder.append_data(&mut header, relative, data.as_slice())?;
+            contents.push((path_name(relative), data));
+        }
+        let archive = builder.into_inner()?.finish()?;
+
+        Self::validate(contents, archive)
+    }
+
+    /// Check an already built `.tgz` bundle
+    pub fn from_archive(archive: Vec<u8>) -> Result<Self> {
+        let mut contents = Vec::new();
+        let mut entries = tar::Archive::new(GzDecoder::new(archive.as_slice()));
+        for entry in entries.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = path_name(&entry.path()?);
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            contents.push((name, data));
+        }
+
+        Self::validate(contents, archive)
+    }
+
+    /// The bundle's manifest
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// The version the bundle is uploaded as
+    pub fn version(&self) -> &str {
+        &self.manifest.edgeworker_version
+    }
+
+    /// Paths of the files in the bundle
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// The gzipped tarball
+    pub fn archive(&self) -> &[u8] {
+        &self.archive
+    }
+
+    /// Write the gzipped tarball to `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(fs::write(path, &self.archive)?)
+    }
+
+    fn validate(contents: Vec<(String, Vec<u8>)>, archive: Vec<u8>) -> Result<Self> {
+        let invalid =
+            |message: String| EdgeGridError::Config(format!("invalid bundle: {}", message));
+        let file = |name: &str| {
+            contents
+                .iter()
+                .find(|(path, _)| path == name)
+                .map(|(_, data)| data)
+                .ok_or_else(|| invalid(format!("{} is missing", name)))
+        };
+
+        let manifest: Manifest = serde_json::from_slice(file(MANIFEST)?)
+            .map_err(|e| invalid(format!("{}: {}", MANIFEST, e)))?;
+        if manifest.edgeworker_version.trim().is_empty() {
+            return Err(invalid(format!(
+                "{} has an empty edgeworker-version",
+                MANIFEST
+            )));
+        }
+
+        let main = String::from_utf8_lossy(file(MAIN)?);
+        if !EVENT_HANDLERS.iter().any(|handler| main.contains(handler)) {
+            return Err(invalid(format!(
+                "{} does not define any of {}",
+                MAIN,
+                EVENT_HANDLERS.join(", ")
+            )));
+        }
+
+        if archive.len() > MAX_BUNDLE_BYTES {
+            return Err(invalid(format!(
+                "archive is {} bytes, the limit is {}",
+                archive.len(),
+                MAX_BUNDLE_BYTES
+            )));
+        }
+
+        Ok(Self {
+            manifest,
+            files: contents.into_iter().map(|(path, _)| path).collect(),
+            archive,
+        })
+    }
+}
+
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = relative.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// A relative path with `/` separators and no `./` prefix
+fn path_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EdgeWorkersResponse {
+    edge_worker_ids: Vec<EdgeWorker>,
+}
+
+#[derive(Deserialize)]
+struct VersionsResponse {
+    versions: Vec<Version>,
+}
+
+#[derive(Deserialize)]
+struct ActivationsResponse {
+    activations: Vec<Activation>,
+}
+
+#[derive(Serialize)]
+struct ActivationBody<'a> {
+    network: Network,
+    version: &'a str,
+}
+
+impl EdgeWorkers {
+    /// Use `client` for EdgeWorkers requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// EdgeWorker IDs, optionally limited to one access group
+    pub async fn edgeworkers(&self, group_id: Option<i64>) -> Result<Vec<EdgeWorker>> {
+        let mut request = self.client.get("/edgeworkers/v1/ids");
+        if let Some(group_id) = group_id {
+            request = request.query("groupId", group_id.to_string());
+        }
+        let response: EdgeWorkersResponse = read_json(request.send().await?).await?;
+        Ok(response.edge_worker_ids)
+    }
+
+    /// A single EdgeWorker ID
+    pub async fn edgeworker(&self, id: i64) -> Result<EdgeWorker> {
+        read_json(self.client.get(&worker_path(id)).send().await?).await
+    }
+
+    /// Uploaded versions of a worker
+    pub async fn versions(&self, id: i64) -> Result<Vec<Version>> {
+        let response = self
+            .client
+            .get(&format!("{}/versions", worker_path(id)))
+            .send()
+            .await?;
+        let response: VersionsResponse = read_json(response).await?;
+        Ok(response.versions)
+    }
+
+    /// A single version
+    pub async fn version(&self, id: i64, version: &str) -> Result<Version> {
+        let response = self
+            .client
+            .get(&format!("{}/versions/{}", worker_path(id), version))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Upload a bundle as a new version
+    pub async fn upload(&self, id: i64, bundle: &Bundle) -> Result<Version> {
+        let response = self
+            .client
+            .post(&format!("{}/versions", worker_path(id)))
+            .header(CONTENT_TYPE.as_str(), "application/gzip")
+            .body(bundle.archive.clone())
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Download the bundle of a version
+    pub async fn download(&self, id: i64, version: &str) -> Result<Bundle> {
+        let response = self
+            .client
+            .get(&format!("{}/versions/{}/content", worker_path(id), version))
+            .send()
+            .await?;
+        let archive = check_status(response).await?.bytes().await?;
+        Bundle::from_archive(archive.to_vec())
+    }
+
+    /// Start activating a version on a network
+    pub async fn activate(&self, id: i64, network: Network, version: &str) -> Result<Activation> {
+        let response = self
+            .client
+            .post(&format!("{}/activations", worker_path(id)))
+            .json(&ActivationBody { network, version })
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Activations of a worker, newest first
+    pub async fn activations(&self, id: i64) -> Result<Vec<Activation>> {
+        let response = self
+            .client
+            .get(&format!("{}/activations", worker_path(id)))
+            .send()
+            .await?;
+        let mut response: ActivationsResponse = read_json(response).await?;
+        // Timestamps are ISO 8601 in UTC, so they sort as strings
+        response
+            .activations
+            .sort_by(|a, b| b.created_time.cmp(&a.created_time));
+        Ok(response.activations)
+    }
+
+    /// A single activation
+    pub async fn activation(&self, id: i64, activation_id: i64) -> Result<Activation> {
+        read_json(
+            self.client
+                .get(&activation_path(id, activation_id))
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    /// Poll an activation until it is complete or aborted
+    pub fn activation_operation(&self, id: i64, activation_id: i64) -> Operation<Activation> {
+        self.client.operation(&activation_path(id, activation_id))
+    }
+
+    /// Reactivate the version that was active on `network` before the
+    /// current one
+    ///
+    /// Poll the returned activation with [`EdgeWorkers::activation_operation`].
+    pub async fn rollback(&self, id: i64, network: Network) -> Result<Activation> {
+        let completed: Vec<Activation> = self
+            .activations(id)
+            .await?
+            .into_iter()
+            .filter(|activation| activation.network == network && activation.status == "COMPLETE")
+            .collect();
+        let current = completed.first().ok_or_else(|| {
+            EdgeGridError::Config(format!(
+                "EdgeWorker {} has never been activated on {}",
+                id, network
+            ))
+        })?;
+        let previous = completed
+            .iter()
+            .find(|activation| activation.version != current.version)
+            .ok_or_else(|| {
+                EdgeGridError::Config(format!(
+                    "EdgeWorker {} has no earlier version to roll back to on {}",
+                    id, network
+                ))
+            })?;
+
+        tracing::info!(
+            id,
+            network = %network,
+            from = %current.version,
+            to = %previous.version,
+            "rolling back EdgeWorker"
+        );
+        self.activate(id, network, &previous.version).await
+    }
+}
+
+fn worker_path(id: i64) -> String {
+    format!("/edgeworkers/v1/ids/{}", id)
+}
+
+fn activation_path(id: i64, activation_id: i64) -> String {
+    format!("{}/activations/{}", worker_path(id), activation_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_worker(dir: &Path, main: &str) {
+        fs::write(
+            dir.join(MANIFEST),
+            r#"{"edgeworker-version": "1.2.0", "description": "Redirects"}"#,
+        )
+        .unwrap();
+        fs::write(dir.join(MAIN), main).unwrap();
+        fs::create_dir(dir.join("lib")).unwrap();
+        fs::write(dir.join("lib/rules.js"), "export const rules = [];").unwrap();
+        fs::create_dir(dir.join(".git")).unwrap();
+        fs::write(dir.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+    }
+
+    #[test]
+    fn test_bundle_from_dir_is_reproducible() {
+        let dir = tempfile::tempdir().unwrap();
+        write_worker(dir.path(), "export function onClientRequest(request) {}");
+
+        let bundle = Bundle::from_dir(dir.path()).unwrap();
+        assert_eq!(bundle.version(), "1.2.0");
+        assert_eq!(bundle.files(), ["bundle.json", "lib/rules.js", "main.js"]);
+        assert_eq!(bundle.archive()[..2], [0x1f, 0x8b]);
+
+        assert_eq!(Bundle::from_dir(dir.path()).unwrap(), bundle);
+        let reread = Bundle::from_archive(bundle.archive().to_vec()).unwrap();
+        assert_eq!(reread.files(), bundle.files());
+        assert_eq!(reread.manifest(), bundle.manifest());
+    }
+
+    #[test]
+    fn test_bundle_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        write_worker(dir.path(), "console.log('no handlers');");
+        let err = Bundle::from_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("main.js does not define any of"));
+
+        fs::remove_file(dir.path().join(MAIN)).unwrap();
+        let err = Bundle::from_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("main.js is missing"));
+
+        fs::write(
+            dir.path().join(MAIN),
+            "export function responseProvider() {}",
+        )
+        .unwrap();
+        fs::write(dir.path().join(MANIFEST), r#"{"description": "x"}"#).unwrap();
+        let err = Bundle::from_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("bundle.json: missing field"));
+    }
+
+    #[test]
+    fn test_activation_state() {
+        let activation: Activation = serde_json::from_str(
+            r#"{"activationId": 3, "edgeWorkerId": 42, "version": "1.2.0", "network": "STAGING", "status": "ABORTED"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            activation.state(),
+            OperationState::Failed("activation 3 of version 1.2.0 is ABORTED".into())
+        );
+    }
+}
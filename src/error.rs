@@ -1,5 +1,7 @@
 //! Error types for the EdgeGrid authentication library
 
+use crate::api::Problem;
+use crate::validate::Finding;
 use thiserror::Error;
 
 /// Main error type for EdgeGrid operations
@@ -8,43 +10,107 @@ pub enum EdgeGridError {
     /// Configuration-related errors
     #[error("Configuration error: {0}")]
     Config(String),
-    
+
     /// Missing required credential
     #[error("Missing credential: {0}")]
     MissingCredential(String),
-    
+
     /// File I/O errors
     #[error("File error: {0}")]
     FileError(#[from] std::io::Error),
-    
+
     /// URL parsing errors
     #[error("URL error: {0}")]
     UrlError(#[from] url::ParseError),
-    
+
     /// HTTP request errors
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
-    
+
     /// Serialization/deserialization errors
     #[error("Serialization error: {0}")]
     SerdeError(#[from] serde_json::Error),
-    
+
     /// TOML parsing errors
     #[error("TOML parsing error: {0}")]
     TomlError(#[from] toml::de::Error),
-    
+
     /// Authentication errors
     #[error("Authentication error: {0}")]
     AuthError(String),
-    
+
     /// Invalid section in .edgerc file
     #[error("Invalid section '{0}' in .edgerc file")]
     InvalidSection(String),
-    
+
     /// Environment variable errors
     #[error("Environment variable error: {0}")]
     EnvError(String),
+
+    /// The request did not complete within its deadline
+    #[error("Request timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    /// The request was cancelled through its cancellation token
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// Cassette recording or replay errors
+    #[error("Cassette error: {0}")]
+    Cassette(String),
+
+    /// An API returned an error response
+    #[error("API error: {0}")]
+    Api(Box<Problem>),
+
+    /// A long-running operation finished unsuccessfully
+    #[error("Operation failed: {0}")]
+    OperationFailed(String),
+
+    /// Credential format validation failed
+    #[error("Invalid credentials: {}", format_findings(.0))]
+    Validation(Vec<Finding>),
+}
+
+impl EdgeGridError {
+    /// Whether the error was caused by a deadline, either ours or the HTTP client's
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            EdgeGridError::Timeout(_) => true,
+            EdgeGridError::HttpError(e) => e.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Whether retrying the same request may succeed
+    ///
+    /// Timeouts, connection failures, rate limiting and server errors are
+    /// retryable; cancellation, configuration and authentication errors are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EdgeGridError::Timeout(_) => true,
+            EdgeGridError::HttpError(e) => e.is_timeout() || e.is_connect(),
+            EdgeGridError::Api(problem) => problem.status == 429 || problem.status >= 500,
+            _ => false,
+        }
+    }
+
+    /// The API's error response, if this error is one
+    pub fn problem(&self) -> Option<&Problem> {
+        match self {
+            EdgeGridError::Api(problem) => Some(problem),
+            _ => None,
+        }
+    }
+}
+
+fn format_findings(findings: &[Finding]) -> String {
+    findings
+        .iter()
+        .map(|finding| finding.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Result type alias for EdgeGrid operations
-pub type Result<T> = std::result::Result<T, EdgeGridError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, EdgeGridError>;
@@ -0,0 +1,228 @@
+//! Export signed requests for use outside Rust
+//!
+//! [`EdgeGridRequestBuilder::sign_only`](crate::EdgeGridRequestBuilder::sign_only)
+//! builds and signs a request without sending it. The resulting
+//! [`SignedRequest`] renders as a curl command line or as raw HTTP/1.1 text,
+//! with the credentials in the `Authorization` header redacted unless
+//! [`Redaction::Disabled`] is requested.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::{EdgeGridClient, Redaction};
+//!
+//! # fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//!
+//! // Safe to paste into a ticket
+//! println!("{}", client.get("/papi/v1/groups").to_curl()?);
+//!
+//! // Runnable as-is until the signature expires
+//! let signed = client.get("/papi/v1/groups").sign_only()?;
+//! println!("{}", signed.curl(Redaction::Disabled));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::EdgeGridConfig;
+use crate::telemetry::redact_credentials;
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::Request;
+use std::sync::Arc;
+
+/// How credentials are shown in exported requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Redaction {
+    /// Replace the tokens and signature with `[REDACTED]`, keeping the timestamp and nonce
+    #[default]
+    Credentials,
+    /// Show the `Authorization` header exactly as signed
+    Disabled,
+}
+
+/// A request that has been built and signed but not sent
+#[derive(Debug)]
+pub struct SignedRequest {
+    request: Request,
+    config: Arc<EdgeGridConfig>,
+}
+
+impl SignedRequest {
+    /// Wrap a request already signed with `config`, e.g. in an interceptor's `after_sign`
+    pub fn new(request: Request, config: Arc<EdgeGridConfig>) -> Self {
+        Self { request, config }
+    }
+
+    /// The signed request
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Take the signed request, e.g. to send it with another `reqwest::Client`
+    pub fn into_request(self) -> Request {
+        self.request
+    }
+
+    /// Render the request as a curl command line
+    pub fn curl(&self, redaction: Redaction) -> String {
+        let mut args: Vec<String> = self
+            .headers(redaction)
+            .into_iter()
+            .map(|(name, value)| format!("-H {}", shell_quote(&format!("{}: {}", name, value))))
+            .collect();
+
+        let mut note = None;
+        match self.body() {
+            Some(Ok(text)) => args.push(format!("--data-raw {}", shell_quote(text))),
+            Some(Err(bytes)) => {
+                note = Some(format!(
+                    "# The {} byte request body is binary; save it as body.bin",
+                    bytes.len()
+                ));
+                args.push("--data-binary @body.bin".to_string());
+            }
+            None => {}
+        }
+
+        let mut command = format!(
+            "curl -X {} {}",
+            self.request.method(),
+            shell_quote(self.request.url().as_str())
+        );
+        for arg in args {
+            command.push_str(" \\\n  ");
+            command.push_str(&arg);
+        }
+
+        match note {
+            Some(note) => format!("{}\n{}", note, command),
+            None => command,
+        }
+    }
+
+    /// Render the request as raw HTTP/1.1 text with CRLF line endings
+    pub fn http(&self, redaction: Redaction) -> String {
+        let url = self.request.url();
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let mut text = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\n",
+            self.request.method(),
+            target,
+            host
+        );
+        for (name, value) in self.headers(redaction) {
+            text.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        let body = self
+            .request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+        if !body.is_empty() {
+            text.push_str(&format!("content-length: {}\r\n", body.len()));
+        }
+        text.push_str("\r\n");
+        text.push_str(&String::from_utf8_lossy(body));
+        text
+    }
+
+    fn headers(&self, redaction: Redaction) -> Vec<(String, String)> {
+        header_pairs(self.request.headers())
+            .into_iter()
+            .map(|(name, value)| match redaction {
+                Redaction::Credentials if name == AUTHORIZATION.as_str() => {
+                    let value = redact_credentials(&value, &self.config);
+                    (name, value)
+                }
+                _ => (name, value),
+            })
+            .collect()
+    }
+
+    /// The body as text, or its bytes when it is not valid UTF-8
+    fn body(&self) -> Option<std::result::Result<&str, &[u8]>> {
+        let bytes = self.request.body()?.as_bytes()?;
+        if bytes.is_empty() {
+            return None;
+        }
+        Some(std::str::from_utf8(bytes).map_err(|_| bytes))
+    }
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Quote `value` for POSIX shells
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::EdgeGridAuth;
+    use reqwest::{Client, Method};
+
+    fn signed(body: &str) -> SignedRequest {
+        let config = EdgeGridConfig::new(
+            "akab-client",
+            "c2VjcmV0",
+            "akab-access",
+            "akab-host.luna.akamaiapis.net".to_string(),
+        );
+        let mut request = Client::new()
+            .request(
+                Method::POST,
+                "https://akab-host.luna.akamaiapis.net/ccu/v3/invalidate/url?x=1",
+            )
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .build()
+            .unwrap();
+        EdgeGridAuth::new(config.clone())
+            .sign_request(&mut request)
+            .unwrap();
+        SignedRequest::new(request, Arc::new(config))
+    }
+
+    #[test]
+    fn test_curl_quotes_and_redacts() {
+        let signed = signed(r#"{"objects":["it's"]}"#);
+
+        let curl = signed.curl(Redaction::Credentials);
+        assert!(curl.starts_with(
+            "curl -X POST 'https://akab-host.luna.akamaiapis.net/ccu/v3/invalidate/url?x=1' \\\n  -H 'content-type: application/json' \\\n"
+        ));
+        assert!(curl.contains("-H 'authorization: EG1-HMAC-SHA256 client_token=[REDACTED];"));
+        assert!(curl.contains(r#"--data-raw '{"objects":["it'\''s"]}'"#));
+        assert!(!curl.contains("akab-access"));
+
+        let exposed = signed.curl(Redaction::Disabled);
+        assert!(exposed.contains("client_token=akab-client;access_token=akab-access;"));
+    }
+
+    #[test]
+    fn test_raw_http() {
+        let http = signed("{}").http(Redaction::Credentials);
+        assert!(http.starts_with(
+            "POST /ccu/v3/invalidate/url?x=1 HTTP/1.1\r\nHost: akab-host.luna.akamaiapis.net\r\n"
+        ));
+        assert!(http.ends_with("content-length: 2\r\n\r\n{}"));
+    }
+}
@@ -0,0 +1,430 @@
+//! HAR 1.2 capture of API traffic
+//!
+//! A [`HarRecorder`] attached with
+//! [`EdgeGridClient::with_har`](crate::EdgeGridClient::with_har) captures
+//! every request sent over the network, including retries, with timings,
+//! headers and bodies. The `Authorization` header keeps its timestamp and
+//! nonce but has its tokens and signature redacted, cookies are redacted, and
+//! credentials are scrubbed from bodies, so the file can be attached to a
+//! support ticket or opened in browser devtools.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::{EdgeGridClient, HarRecorder};
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let har = HarRecorder::new();
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?.with_har(har.clone());
+//!
+//! client.get("/papi/v1/groups").send().await?;
+//! har.save("papi-groups.har")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::buffer_response;
+use crate::config::EdgeGridConfig;
+use crate::error::Result;
+use crate::secret::REDACTED;
+use crate::telemetry::{redact_credentials, Scrubber};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Request, Response};
+use serde::Serialize;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Headers whose values are always replaced with `[REDACTED]`
+const REDACTED_HEADERS: &[&str] = &["cookie", "set-cookie", "proxy-authorization"];
+
+/// Collects exchanges made by a client as HAR 1.2 entries
+///
+/// Clones share the same entries, so keep one to write the file while the
+/// client uses another.
+#[derive(Debug, Clone)]
+pub struct HarRecorder {
+    entries: Arc<Mutex<Vec<Entry>>>,
+    include_bodies: bool,
+    redacted_headers: Vec<String>,
+}
+
+impl Default for HarRecorder {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Vec::new())),
+            include_bodies: true,
+            redacted_headers: REDACTED_HEADERS.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+}
+
+impl HarRecorder {
+    /// Create an empty recorder that captures bodies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave request and response bodies out of the capture
+    pub fn without_bodies(mut self) -> Self {
+        self.include_bodies = false;
+        self
+    }
+
+    /// Replace the value of `name` with `[REDACTED]` in captured headers
+    pub fn redact_header(mut self, name: &str) -> Self {
+        self.redacted_headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Number of captured exchanges
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether nothing has been captured yet
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drop all captured exchanges
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// The captured exchanges as a HAR 1.2 document
+    pub fn to_json(&self) -> serde_json::Value {
+        let log = Log {
+            version: "1.2",
+            creator: Creator {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            entries: self.lock().clone(),
+        };
+        serde_json::json!({ "log": log })
+    }
+
+    /// Write the captured exchanges to a `.har` file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    /// Send `request` with `send` and capture the exchange
+    pub(crate) async fn capture<F, Fut>(
+        &self,
+        request: Request,
+        config: &EdgeGridConfig,
+        send: F,
+    ) -> Result<Response>
+    where
+        F: FnOnce(Request) -> Fut,
+        Fut: Future<Output = Result<Response>>,
+    {
+        let scrub = Scrubber::new(config, &[]);
+        let started_date_time = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let har_request = self.request(&request, config, &scrub);
+
+        let started = Instant::now();
+        let result = send(request).await;
+        let wait = started.elapsed();
+
+        let (result, har_response, receive) = match result {
+            Ok(response) => {
+                let buffered = buffer_response(response).await;
+                let receive = started.elapsed().saturating_sub(wait);
+                match buffered {
+                    Ok((response, body)) => {
+                        let har_response = self.response(&response, &body, config, &scrub);
+                        (Ok(response), har_response, receive)
+                    }
+                    Err(e) => (Err(e), HarResponse::failed(), receive),
+                }
+            }
+            Err(e) => (Err(e), HarResponse::failed(), Duration::ZERO),
+        };
+
+        let entry = Entry {
+            started_date_time,
+            time: millis(wait + receive),
+            request: har_request,
+            response: har_response,
+            cache: serde_json::json!({}),
+            timings: Timings {
+                blocked: -1.0,
+                dns: -1.0,
+                connect: -1.0,
+                ssl: -1.0,
+                send: 0.0,
+                wait: millis(wait),
+                receive: millis(receive),
+            },
+            comment: result.as_ref().err().map(|e| e.to_string()),
+        };
+        self.lock().push(entry);
+
+        result
+    }
+
+    fn request(&self, request: &Request, config: &EdgeGridConfig, scrub: &Scrubber) -> HarRequest {
+        let body = request.body().and_then(|body| body.as_bytes());
+
+        HarRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            http_version: format!("{:?}", request.version()),
+            cookies: Vec::new(),
+            headers: self.headers(request.headers(), config, scrub),
+            query_string: request
+                .url()
+                .query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect(),
+            post_data: body.filter(|_| self.include_bodies).map(|bytes| PostData {
+                mime_type: content_type(request.headers()),
+                text: scrub.apply(&String::from_utf8_lossy(bytes)),
+            }),
+            headers_size: -1,
+            body_size: body.map_or(0, |bytes| bytes.len() as i64),
+        }
+    }
+
+    fn response(
+        &self,
+        response: &Response,
+        body: &[u8],
+        config: &EdgeGridConfig,
+        scrub: &Scrubber,
+    ) -> HarResponse {
+        let mime_type = content_type(response.headers());
+        let (text, encoding) = match std::str::from_utf8(body) {
+            _ if !self.include_bodies || body.is_empty() => (None, None),
+            Ok(text) => (Some(scrub.apply(text)), None),
+            Err(_) => (Some(BASE64.encode(body)), Some("base64")),
+        };
+
+        HarResponse {
+            status: response.status().as_u16(),
+            status_text: response
+                .status()
+                .canonical_reason()
+                .unwrap_or_default()
+                .to_string(),
+            http_version: format!("{:?}", response.version()),
+            cookies: Vec::new(),
+            headers: self.headers(response.headers(), config, scrub),
+            content: Content {
+                size: body.len() as i64,
+                mime_type,
+                text,
+                encoding,
+            },
+            redirect_url: String::new(),
+            headers_size: -1,
+            body_size: body.len() as i64,
+        }
+    }
+
+    fn headers(
+        &self,
+        headers: &HeaderMap,
+        config: &EdgeGridConfig,
+        scrub: &Scrubber,
+    ) -> Vec<NameValue> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("<binary>");
+                let value = if name == AUTHORIZATION {
+                    redact_credentials(value, config)
+                } else if self.redacted_headers.iter().any(|h| h == name.as_str()) {
+                    REDACTED.to_string()
+                } else {
+                    scrub.apply(value)
+                };
+                NameValue {
+                    name: name.to_string(),
+                    value,
+                }
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[derive(Debug, Serialize)]
+struct Log {
+    version: &'static str,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: serde_json::Value,
+    timings: Timings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+impl HarResponse {
+    /// Placeholder for an exchange that produced no response; HAR uses status 0
+    fn failed() -> Self {
+        Self {
+            status: 0,
+            status_text: String::new(),
+            http_version: String::new(),
+            cookies: Vec::new(),
+            headers: Vec::new(),
+            content: Content {
+                size: 0,
+                mime_type: String::new(),
+                text: None,
+                encoding: None,
+            },
+            redirect_url: String::new(),
+            headers_size: -1,
+            body_size: -1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Timings {
+    blocked: f64,
+    dns: f64,
+    connect: f64,
+    ssl: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::{Client, Method};
+
+    #[test]
+    fn test_request_headers_and_body_are_redacted() {
+        let config = EdgeGridConfig::new(
+            "akab-client",
+            "secret",
+            "akab-access",
+            "akab-host.luna.akamaiapis.net".to_string(),
+        );
+        let request = Client::new()
+            .request(Method::POST, "https://akab-host.luna.akamaiapis.net/ccu/v3?x=1")
+            .header(
+                AUTHORIZATION,
+                "EG1-HMAC-SHA256 client_token=akab-client;access_token=akab-access;timestamp=t;nonce=n;signature=s",
+            )
+            .header("cookie", "session=1")
+            .header(CONTENT_TYPE, "application/json")
+            .body(r#"{"token":"akab-access"}"#)
+            .build()
+            .unwrap();
+
+        let recorder = HarRecorder::new().redact_header("X-Custom");
+        let entry = recorder.request(&request, &config, &Scrubber::new(&config, &[]));
+        let json = serde_json::to_value(&entry).unwrap();
+
+        assert_eq!(json["queryString"][0]["name"], "x");
+        assert_eq!(json["postData"]["text"], r#"{"token":"[REDACTED]"}"#);
+        let headers = json["headers"].to_string();
+        assert!(headers.contains("timestamp=t;nonce=n"));
+        assert!(!headers.contains("akab-client"));
+        assert!(!headers.contains("session=1"));
+
+        let bodiless = HarRecorder::new().without_bodies();
+        let entry = bodiless.request(&request, &config, &Scrubber::new(&config, &[]));
+        assert!(entry.post_data.is_none());
+    }
+}
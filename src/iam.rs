@@ -0,0 +1,933 @@
+//! Identity and Access Management (IAM v3) API
+//!
+//! Users, groups, roles, API clients and their credentials. API client
+//! methods accept [`SELF`] in place of a client ID to act on the API client
+//! the request is signed with.
+//!
+//! [`Iam::rotate_edgerc_credential`] creates a new credential for the signing
+//! API client, writes it into an `.edgerc` section and switches the client
+//! over to it:
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::iam::{Iam, RotateOptions};
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let iam = Iam::new(client);
+//!
+//! let rotation = iam
+//!     .rotate_edgerc_credential("~/.edgerc", "default", RotateOptions::default())
+//!     .await?;
+//! println!("new credential {} expires {}", rotation.credential.credential_id, rotation.credential.expires_on);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json};
+use crate::client::{EdgeGridClient, EdgeGridRequestBuilder};
+use crate::config::EdgeGridConfig;
+use crate::edgerc::Edgerc;
+use crate::error::{EdgeGridError, Result};
+use crate::secret::{
+    provider_from_values, Secret, SecretProvider, CLIENT_SECRET_COMMAND_KEY,
+    CLIENT_SECRET_FILE_KEY, CLIENT_SECRET_KEYRING_KEY,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Client ID that refers to the API client signing the request
+pub const SELF: &str = "self";
+
+/// How often a rotated credential is checked while waiting for it to propagate
+const PROPAGATION_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+const USER_ADMIN: &str = "/identity-management/v3/user-admin";
+const API_CLIENTS: &str = "/identity-management/v3/api-clients";
+
+/// Identity and Access Management API client
+#[derive(Debug, Clone)]
+pub struct Iam {
+    client: EdgeGridClient,
+}
+
+/// A user of Akamai Control Center
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    /// Unique user ID
+    pub ui_identity_id: String,
+    /// Login name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_user_name: Option<String>,
+    /// First name
+    pub first_name: String,
+    /// Last name
+    pub last_name: String,
+    /// Email address
+    pub email: String,
+    /// Whether the user is locked out
+    #[serde(default)]
+    pub is_locked: bool,
+    /// Whether two-factor authentication is enabled
+    #[serde(default)]
+    pub tfa_enabled: bool,
+    /// When the user last logged in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_date: Option<String>,
+    /// Roles the user has in each group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auth_grants: Vec<AuthGrant>,
+    /// Members not modelled above, e.g. `country` or `timeZone`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A role granted in a group and, unless blocked, its subgroups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthGrant {
+    /// Group the role applies to
+    pub group_id: i64,
+    /// Group name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    /// Role granted, absent when only subgroups have grants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_id: Option<i64>,
+    /// Role name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_name: Option<String>,
+    /// Whether the grant does not extend to subgroups
+    #[serde(default)]
+    pub is_blocked: bool,
+    /// Grants in subgroups
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_groups: Vec<AuthGrant>,
+}
+
+impl AuthGrant {
+    /// Grant `role_id` in `group_id`
+    pub fn new(group_id: i64, role_id: i64) -> Self {
+        Self {
+            group_id,
+            group_name: None,
+            role_id: Some(role_id),
+            role_name: None,
+            is_blocked: false,
+            sub_groups: Vec::new(),
+        }
+    }
+}
+
+/// A user to create
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUser {
+    /// First name
+    pub first_name: String,
+    /// Last name
+    pub last_name: String,
+    /// Email address, also the default login name
+    pub email: String,
+    /// Country, e.g. `Sweden`
+    pub country: String,
+    /// At least one role grant
+    pub auth_grants: Vec<AuthGrant>,
+    /// Optional members, e.g. `phone` or `timeZone`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl NewUser {
+    /// A user with no grants yet
+    pub fn new(
+        first_name: impl Into<String>,
+        last_name: impl Into<String>,
+        email: impl Into<String>,
+        country: impl Into<String>,
+    ) -> Self {
+        Self {
+            first_name: first_name.into(),
+            last_name: last_name.into(),
+            email: email.into(),
+            country: country.into(),
+            auth_grants: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+
+    /// Grant `role_id` in `group_id`
+    pub fn grant(mut self, group_id: i64, role_id: i64) -> Self {
+        self.auth_grants.push(AuthGrant::new(group_id, role_id));
+        self
+    }
+}
+
+/// A group and its subgroups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    /// Group ID
+    pub group_id: i64,
+    /// Group name
+    pub group_name: String,
+    /// Parent group, absent for the top-level group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_group_id: Option<i64>,
+    /// Subgroups
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_groups: Vec<Group>,
+}
+
+/// A role: a named set of granted roles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Role {
+    /// Role ID
+    pub role_id: i64,
+    /// Role name
+    pub role_name: String,
+    /// Role description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_description: Option<String>,
+    /// `standard` for roles provided by Akamai, `custom` otherwise
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub role_type: Option<String>,
+    /// Permissions the role grants
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub granted_roles: Vec<GrantedRole>,
+}
+
+/// A permission granted by a role
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantedRole {
+    /// Granted role ID
+    pub granted_role_id: i64,
+    /// Granted role name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub granted_role_name: Option<String>,
+}
+
+/// A custom role to create
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRole {
+    /// Role name
+    pub role_name: String,
+    /// Role description
+    pub role_description: String,
+    /// Permissions the role grants
+    pub granted_roles: Vec<GrantedRole>,
+}
+
+impl NewRole {
+    /// A role granting `granted_role_ids`
+    pub fn new(
+        role_name: impl Into<String>,
+        role_description: impl Into<String>,
+        granted_role_ids: impl IntoIterator<Item = i64>,
+    ) -> Self {
+        Self {
+            role_name: role_name.into(),
+            role_description: role_description.into(),
+            granted_roles: granted_role_ids
+                .into_iter()
+                .map(|granted_role_id| GrantedRole {
+                    granted_role_id,
+                    granted_role_name: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An API client, the owner of EdgeGrid credentials
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiClient {
+    /// Client ID
+    pub client_id: String,
+    /// Client name
+    pub client_name: String,
+    /// Client description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_description: Option<String>,
+    /// `CLIENT`, `USER_CLIENT` or `SERVICE_ACCOUNT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_type: Option<String>,
+    /// Access token shared by all of the client's credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    /// Whether the client is locked
+    #[serde(default)]
+    pub is_locked: bool,
+    /// Number of active credentials
+    #[serde(default)]
+    pub active_credential_count: u32,
+    /// APIs the client can call, when requested with `apiAccess`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_access: Option<ApiAccess>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// APIs an API client can call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiAccess {
+    /// Whether every API the authorized users can access is allowed
+    #[serde(default)]
+    pub all_accessible_apis: bool,
+    /// Allowed APIs when `all_accessible_apis` is false
+    #[serde(default)]
+    pub apis: Vec<ApiScope>,
+}
+
+/// Access to one API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiScope {
+    /// API ID
+    pub api_id: i64,
+    /// API name, e.g. `Property Manager (PAPI)`
+    #[serde(default)]
+    pub api_name: String,
+    /// `READ-ONLY` or `READ-WRITE`
+    pub access_level: String,
+}
+
+/// An API client to create
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewApiClient {
+    /// Client name
+    pub client_name: String,
+    /// Client description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_description: Option<String>,
+    /// Users whose access the client inherits
+    pub authorized_users: Vec<String>,
+    /// APIs the client can call
+    pub api_access: ApiAccess,
+    /// Groups and roles the client has
+    pub group_access: GroupAccess,
+    /// Whether to create a first credential along with the client
+    #[serde(default)]
+    pub create_credential: bool,
+    /// Optional members, e.g. `notificationEmails` or `ipAcl`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Groups and roles given to an API client
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAccess {
+    /// Use the groups of the authorized user instead of `groups`
+    #[serde(default)]
+    pub clone_authorized_user_groups: bool,
+    /// Group and role grants
+    #[serde(default)]
+    pub groups: Vec<AuthGrant>,
+}
+
+/// An EdgeGrid credential of an API client; the secret is never returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credential {
+    /// Credential ID
+    pub credential_id: i64,
+    /// Client token
+    pub client_token: String,
+    /// `ACTIVE`, `INACTIVE` or `DELETED`
+    pub status: String,
+    /// When the credential was created
+    #[serde(default)]
+    pub created_on: String,
+    /// When the credential expires
+    #[serde(default)]
+    pub expires_on: String,
+    /// Credential description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A newly created credential, the only time its secret is available
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCredential {
+    /// Credential ID
+    pub credential_id: i64,
+    /// Client token
+    pub client_token: String,
+    /// Client secret
+    pub client_secret: Secret,
+    /// `ACTIVE`
+    #[serde(default)]
+    pub status: String,
+    /// When the credential was created
+    #[serde(default)]
+    pub created_on: String,
+    /// When the credential expires
+    #[serde(default)]
+    pub expires_on: String,
+}
+
+/// What [`Iam::rotate_edgerc_credential`] does besides creating the credential
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotateOptions {
+    /// Deactivate the credential being replaced once the new one is accepted
+    ///
+    /// New credentials can take a few minutes to be accepted everywhere, so
+    /// the rotation first retries a request signed with the new credential
+    /// for up to `propagation_timeout`. If it is not accepted by then, the
+    /// previous credential is left active.
+    pub deactivate_previous: bool,
+    /// How long to wait for the new credential before deactivating the previous one
+    pub propagation_timeout: Duration,
+    /// Replace a secret provider that cannot store secrets, such as
+    /// `client_secret_command`, with a plaintext `client_secret`
+    pub replace_secret_provider: bool,
+}
+
+impl Default for RotateOptions {
+    fn default() -> Self {
+        Self {
+            deactivate_previous: false,
+            propagation_timeout: Duration::from_secs(600),
+            replace_secret_provider: false,
+        }
+    }
+}
+
+/// Result of [`Iam::rotate_edgerc_credential`]
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    /// The new credential
+    pub credential: NewCredential,
+    /// The credential that was replaced
+    pub previous: Credential,
+    /// Whether the previous credential was deactivated
+    pub deactivated_previous: bool,
+    /// Configuration now stored in the `.edgerc` section
+    pub config: EdgeGridConfig,
+}
+
+impl Iam {
+    /// Use `client` for Identity and Access Management requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// Users, with their role grants
+    pub async fn users(&self) -> Result<Vec<User>> {
+        let response = self
+            .client
+            .get(&format!("{}/ui-identities", USER_ADMIN))
+            .query("authGrants", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// A single user, with their role grants
+    pub async fn user(&self, ui_identity_id: &str) -> Result<User> {
+        let response = self
+            .client
+            .get(&user_path(ui_identity_id))
+            .query("authGrants", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a user, optionally emailing them an invitation
+    pub async fn create_user(&self, user: &NewUser, send_email: bool) -> Result<User> {
+        let response = self
+            .client
+            .post(&format!("{}/ui-identities", USER_ADMIN))
+            .query("sendEmail", send_email.to_string())
+            .json(user)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Replace a user's role grants
+    pub async fn update_user_grants(
+        &self,
+        ui_identity_id: &str,
+        grants: &[AuthGrant],
+    ) -> Result<Vec<AuthGrant>> {
+        let response = self
+            .client
+            .put(&format!("{}/auth-grants", user_path(ui_identity_id)))
+            .json(grants)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Lock a user out of Control Center
+    pub async fn lock_user(&self, ui_identity_id: &str) -> Result<()> {
+        self.send_empty(
+            self.client
+                .post(&format!("{}/lock", user_path(ui_identity_id))),
+        )
+        .await
+    }
+
+    /// Unlock a locked user
+    pub async fn unlock_user(&self, ui_identity_id: &str) -> Result<()> {
+        self.send_empty(
+            self.client
+                .post(&format!("{}/unlock", user_path(ui_identity_id))),
+        )
+        .await
+    }
+
+    /// Delete a user
+    pub async fn delete_user(&self, ui_identity_id: &str) -> Result<()> {
+        self.send_empty(self.client.delete(&user_path(ui_identity_id)))
+            .await
+    }
+
+    /// The group hierarchy, as top-level groups with their subgroups
+    pub async fn groups(&self) -> Result<Vec<Group>> {
+        let response = self
+            .client
+            .get(&format!("{}/groups", USER_ADMIN))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// A single group and its subgroups
+    pub async fn group(&self, group_id: i64) -> Result<Group> {
+        let response = self
+            .client
+            .get(&format!("{}/groups/{}", USER_ADMIN, group_id))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a subgroup of `parent_group_id`
+    pub async fn create_group(&self, parent_group_id: i64, group_name: &str) -> Result<Group> {
+        let response = self
+            .client
+            .post(&format!("{}/groups/{}", USER_ADMIN, parent_group_id))
+            .json(&serde_json::json!({ "groupName": group_name }))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Roles, with the permissions each grants
+    pub async fn roles(&self) -> Result<Vec<Role>> {
+        let response = self
+            .client
+            .get(&format!("{}/roles", USER_ADMIN))
+            .query("grantedRoles", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// A single role
+    pub async fn role(&self, role_id: i64) -> Result<Role> {
+        let response = self
+            .client
+            .get(&format!("{}/roles/{}", USER_ADMIN, role_id))
+            .query("grantedRoles", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a custom role
+    pub async fn create_role(&self, role: &NewRole) -> Result<Role> {
+        let response = self
+            .client
+            .post(&format!("{}/roles", USER_ADMIN))
+            .json(role)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// API clients the signing client can manage
+    pub async fn api_clients(&self) -> Result<Vec<ApiClient>> {
+        read_json(self.client.get(API_CLIENTS).send().await?).await
+    }
+
+    /// A single API client, with the APIs it can call
+    pub async fn api_client(&self, client_id: &str) -> Result<ApiClient> {
+        let response = self
+            .client
+            .get(&api_client_path(client_id))
+            .query("apiAccess", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create an API client
+    pub async fn create_api_client(&self, api_client: &NewApiClient) -> Result<ApiClient> {
+        let response = self
+            .client
+            .post(API_CLIENTS)
+            .json(api_client)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Lock an API client, rejecting all of its credentials
+    pub async fn lock_api_client(&self, client_id: &str) -> Result<()> {
+        self.send_empty(
+            self.client
+                .put(&format!("{}/lock", api_client_path(client_id))),
+        )
+        .await
+    }
+
+    /// Unlock a locked API client
+    pub async fn unlock_api_client(&self, client_id: &str) -> Result<()> {
+        self.send_empty(
+            self.client
+                .put(&format!("{}/unlock", api_client_path(client_id))),
+        )
+        .await
+    }
+
+    /// Delete an API client and its credentials
+    pub async fn delete_api_client(&self, client_id: &str) -> Result<()> {
+        self.send_empty(self.client.delete(&api_client_path(client_id)))
+            .await
+    }
+
+    /// Credentials of an API client
+    pub async fn credentials(&self, client_id: &str) -> Result<Vec<Credential>> {
+        let response = self.client.get(&credentials_path(client_id)).send().await?;
+        read_json(response).await
+    }
+
+    /// Create a credential for an API client
+    pub async fn create_credential(&self, client_id: &str) -> Result<NewCredential> {
+        let response = self
+            .client
+            .post(&credentials_path(client_id))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Deactivate a credential; it can be reactivated until it is deleted
+    pub async fn deactivate_credential(&self, client_id: &str, credential_id: i64) -> Result<()> {
+        self.send_empty(self.client.post(&format!(
+            "{}/{}/deactivate",
+            credentials_path(client_id),
+            credential_id
+        )))
+        .await
+    }
+
+    /// Delete an inactive credential
+    pub async fn delete_credential(&self, client_id: &str, credential_id: i64) -> Result<()> {
+        self.send_empty(self.client.delete(&format!(
+            "{}/{}",
+            credentials_path(client_id),
+            credential_id
+        )))
+        .await
+    }
+
+    /// Replace the credential in an `.edgerc` section with a new one
+    ///
+    /// Creates a credential for the API client this client signs with, writes
+    /// it into `section`, and rotates this client's
+    /// [`SharedCredentials`](crate::SharedCredentials) so it uses the new
+    /// credential with the old one as a fallback. Other keys and the rest of
+    /// the file are kept.
+    ///
+    /// The section must hold a credential of the signing API client. When its
+    /// secret comes from a [`SecretProvider`], the new
+    /// secret is stored through that provider; providers that cannot store
+    /// secrets, such as `client_secret_command`, are an error unless
+    /// [`RotateOptions::replace_secret_provider`] is set. If the new
+    /// credential cannot be saved, it is deactivated and deleted again, since
+    /// its secret cannot be retrieved later.
+    pub async fn rotate_edgerc_credential(
+        &self,
+        path: impl AsRef<Path>,
+        section: &str,
+        options: RotateOptions,
+    ) -> Result<Rotation> {
+        let mut edgerc = Edgerc::load(path)?;
+        let current = edgerc.config(section)?;
+        let mut values = edgerc.section_values(section).unwrap_or_default();
+
+        let uses_provider = match values.get("client_secret") {
+            Some(secret) if !secret.trim().is_empty() => false,
+            _ => provider_from_values(&values)?.is_some(),
+        };
+        let stores_secret =
+            uses_provider && provider_from_values(&values)?.is_some_and(|p| p.can_store());
+        if uses_provider && !stores_secret && !options.replace_secret_provider {
+            return Err(EdgeGridError::Config(format!(
+                "The client secret of section [{}] comes from a provider that cannot store it; \
+                 set RotateOptions::replace_secret_provider to write it into the .edgerc file",
+                section
+            )));
+        }
+
+        let previous = self
+            .credentials(SELF)
+            .await?
+            .into_iter()
+            .find(|credential| credential.client_token == current.client_token.expose())
+            .ok_or_else(|| {
+                EdgeGridError::Config(format!(
+                    "Section [{}] does not hold a credential of the API client this client signs with",
+                    section
+                ))
+            })?;
+        let credential = self.create_credential(SELF).await?;
+
+        values.insert("client_token".to_string(), credential.client_token.clone());
+        let provider = provider_from_values(&values)?.filter(|_| stores_secret);
+        if let Err(e) = save_credential(&mut edgerc, section, &credential, provider) {
+            self.discard_credential(credential.credential_id).await;
+            return Err(e);
+        }
+
+        let config = edgerc.config(section)?;
+        self.client.credentials().rotate(config.clone())?;
+
+        let deactivated_previous = options.deactivate_previous
+            && accepted_within(&config, options.propagation_timeout).await?;
+        if deactivated_previous {
+            self.deactivate_credential(SELF, previous.credential_id)
+                .await?;
+        } else if options.deactivate_previous {
+            tracing::warn!(
+                "Credential {} not accepted within {:?}, leaving credential {} active",
+                credential.credential_id,
+                options.propagation_timeout,
+                previous.credential_id
+            );
+        }
+
+        Ok(Rotation {
+            credential,
+            previous,
+            deactivated_previous,
+            config,
+        })
+    }
+
+    /// Deactivate and delete a credential of the signing API client that could not be saved
+    async fn discard_credential(&self, credential_id: i64) {
+        let result = match self.deactivate_credential(SELF, credential_id).await {
+            Ok(()) => self.delete_credential(SELF, credential_id).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!(
+                "Cannot remove credential {} after failing to save it: {}",
+                credential_id,
+                e
+            );
+        }
+    }
+
+    async fn send_empty(&self, request: EdgeGridRequestBuilder) -> Result<()> {
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+/// Write a new credential into `section`
+///
+/// The client token is saved before the secret is handed to `provider`, and
+/// the file is restored if storing the secret fails, so the section never
+/// pairs the new token with the old secret. Without a provider, both are
+/// written in one save.
+fn save_credential(
+    edgerc: &mut Edgerc,
+    section: &str,
+    credential: &NewCredential,
+    provider: Option<Box<dyn SecretProvider>>,
+) -> Result<()> {
+    let original = edgerc.clone();
+    edgerc.set(section, "client_token", credential.client_token.as_str())?;
+    match provider {
+        Some(provider) => {
+            edgerc.save()?;
+            if let Err(e) = provider.store(&credential.client_secret) {
+                if let Err(restore) = original.save() {
+                    tracing::warn!("Cannot restore .edgerc section [{}]: {}", section, restore);
+                }
+                return Err(e);
+            }
+        }
+        None => {
+            edgerc.set(section, "client_secret", credential.client_secret.expose())?;
+            for key in [
+                CLIENT_SECRET_FILE_KEY,
+                CLIENT_SECRET_COMMAND_KEY,
+                CLIENT_SECRET_KEYRING_KEY,
+            ] {
+                edgerc.remove(section, key);
+            }
+            edgerc.save()?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a request signed with `config` is accepted within `timeout`
+async fn accepted_within(config: &EdgeGridConfig, timeout: Duration) -> Result<bool> {
+    let probe = Iam::new(EdgeGridClient::new(config.clone())?);
+    let deadline = Instant::now() + timeout;
+    loop {
+        match probe.api_client(SELF).await {
+            Ok(_) => return Ok(true),
+            Err(e) => tracing::debug!("New credential not accepted yet: {}", e),
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        tokio::time::sleep(remaining.min(PROPAGATION_CHECK_INTERVAL)).await;
+    }
+}
+
+fn user_path(ui_identity_id: &str) -> String {
+    format!("{}/ui-identities/{}", USER_ADMIN, ui_identity_id)
+}
+
+fn api_client_path(client_id: &str) -> String {
+    format!("{}/{}", API_CLIENTS, client_id)
+}
+
+fn credentials_path(client_id: &str) -> String {
+    format!("{}/credentials", api_client_path(client_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_user_with_nested_grants() {
+        let user: User = serde_json::from_value(json!({
+            "uiIdentityId": "A-B-123",
+            "uiUserName": "jdoe",
+            "firstName": "J",
+            "lastName": "Doe",
+            "email": "jdoe@example.com",
+            "country": "Sweden",
+            "authGrants": [{
+                "groupId": 10,
+                "groupName": "Top",
+                "roleId": 5,
+                "roleName": "Admin",
+                "isBlocked": false,
+                "subGroups": [{"groupId": 11, "groupName": "Child", "isBlocked": true}]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(user.auth_grants[0].role_id, Some(5));
+        assert!(user.auth_grants[0].sub_groups[0].is_blocked);
+        assert_eq!(user.auth_grants[0].sub_groups[0].role_id, None);
+        assert_eq!(user.extra["country"], "Sweden");
+    }
+
+    #[test]
+    fn test_new_user_and_role_bodies() {
+        let user = NewUser::new("J", "Doe", "jdoe@example.com", "Sweden").grant(10, 5);
+        assert_eq!(
+            serde_json::to_value(&user).unwrap(),
+            json!({
+                "firstName": "J",
+                "lastName": "Doe",
+                "email": "jdoe@example.com",
+                "country": "Sweden",
+                "authGrants": [{"groupId": 10, "roleId": 5, "isBlocked": false}]
+            })
+        );
+
+        let role = NewRole::new("Purgers", "Can purge", [1, 2]);
+        assert_eq!(
+            serde_json::to_value(&role).unwrap()["grantedRoles"],
+            json!([{"grantedRoleId": 1}, {"grantedRoleId": 2}])
+        );
+    }
+
+    #[test]
+    fn test_new_credential_secret_is_redacted() {
+        let credential: NewCredential = serde_json::from_value(json!({
+            "credentialId": 3,
+            "clientToken": "akab-token",
+            "clientSecret": "very-secret",
+            "status": "ACTIVE",
+            "createdOn": "2026-10-18T00:00:00.000Z",
+            "expiresOn": "2028-10-18T00:00:00.000Z"
+        }))
+        .unwrap();
+
+        assert_eq!(credential.client_secret.expose(), "very-secret");
+        assert!(!format!("{:?}", credential).contains("very-secret"));
+    }
+
+    #[test]
+    fn test_save_credential_restores_section_when_secret_is_not_stored() {
+        #[derive(Debug)]
+        struct Unwritable;
+
+        impl SecretProvider for Unwritable {
+            fn secret(&self) -> Result<Secret> {
+                Ok(Secret::new("old-secret"))
+            }
+
+            fn can_store(&self) -> bool {
+                true
+            }
+
+            fn store(&self, _secret: &Secret) -> Result<()> {
+                Err(EdgeGridError::Config("vault is sealed".to_string()))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".edgerc");
+        let original = "[default]\nclient_token = old-token\nclient_secret_command = vault read\n";
+        std::fs::write(&path, original).unwrap();
+
+        let credential: NewCredential = serde_json::from_value(json!({
+            "credentialId": 3,
+            "clientToken": "new-token",
+            "clientSecret": "new-secret"
+        }))
+        .unwrap();
+        let mut edgerc = Edgerc::load(&path).unwrap();
+        let err = save_credential(
+            &mut edgerc,
+            "default",
+            &credential,
+            Some(Box::new(Unwritable)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("vault is sealed"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+    }
+}
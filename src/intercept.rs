@@ -0,0 +1,165 @@
+//! Request and response interceptors
+//!
+//! An [`Interceptor`] registered with
+//! [`EdgeGridClient::with_interceptor`](crate::EdgeGridClient::with_interceptor)
+//! can modify each request before and after it is signed, answer it with a
+//! synthetic response instead of sending it, inspect or modify the response,
+//! or abort the request by returning an error. Interceptors run in the order
+//! they were registered.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::intercept::{InterceptAction, Interceptor};
+//! use akamai_edgegrid::{EdgeGridClient, Result};
+//! use reqwest::Request;
+//!
+//! struct CorrelationId;
+//!
+//! impl Interceptor for CorrelationId {
+//!     fn before_sign(&self, request: &mut Request) -> Result<InterceptAction> {
+//!         request
+//!             .headers_mut()
+//!             .insert("x-correlation-id", "deploy-42".parse().unwrap());
+//!         Ok(InterceptAction::Continue)
+//!     }
+//! }
+//!
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?
+//!     .with_interceptor(CorrelationId);
+//! # Ok::<(), akamai_edgegrid::EdgeGridError>(())
+//! ```
+
+use crate::error::Result;
+use crate::observe::RequestInfo;
+use reqwest::{Request, Response};
+use std::fmt;
+use std::sync::Arc;
+
+/// What to do after an interceptor hook has run
+#[derive(Debug)]
+pub enum InterceptAction {
+    /// Carry on with the next interceptor and the request
+    Continue,
+    /// Skip sending and answer the request with this response
+    Respond(Response),
+}
+
+impl InterceptAction {
+    /// Answer the request with a synthetic response built with the `http` crate
+    pub fn respond<B: Into<reqwest::Body>>(response: http::Response<B>) -> Self {
+        InterceptAction::Respond(Response::from(response))
+    }
+}
+
+/// Hooks around signing and sending a request
+///
+/// All hooks default to doing nothing. Changes to the method, URL or body
+/// must be made in [`before_sign`](Interceptor::before_sign), since they are
+/// covered by the signature; [`after_sign`](Interceptor::after_sign) sees the
+/// final `Authorization` header and may only add unsigned headers.
+pub trait Interceptor: Send + Sync {
+    /// Called with the unsigned request
+    fn before_sign(&self, _request: &mut Request) -> Result<InterceptAction> {
+        Ok(InterceptAction::Continue)
+    }
+
+    /// Called with the signed request just before it is sent
+    fn after_sign(&self, _request: &mut Request) -> Result<InterceptAction> {
+        Ok(InterceptAction::Continue)
+    }
+
+    /// Called with every response, including synthetic ones
+    fn on_response(&self, _request: &RequestInfo, _response: &mut Response) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The interceptors registered on a client
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.0.push(interceptor);
+    }
+
+    /// Run `before_sign` hooks, stopping at the first synthetic response
+    pub(crate) fn before_sign(&self, request: &mut Request) -> Result<Option<Response>> {
+        for interceptor in &self.0 {
+            if let InterceptAction::Respond(response) = interceptor.before_sign(request)? {
+                return Ok(Some(response));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run `after_sign` hooks, stopping at the first synthetic response
+    pub(crate) fn after_sign(&self, request: &mut Request) -> Result<Option<Response>> {
+        for interceptor in &self.0 {
+            if let InterceptAction::Respond(response) = interceptor.after_sign(request)? {
+                return Ok(Some(response));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn on_response(&self, request: &RequestInfo, response: &mut Response) -> Result<()> {
+        for interceptor in &self.0 {
+            interceptor.on_response(request, response)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EdgeGridError;
+    use reqwest::{Client, Method};
+
+    struct Respond(u16);
+
+    impl Interceptor for Respond {
+        fn before_sign(&self, _request: &mut Request) -> Result<InterceptAction> {
+            Ok(InterceptAction::respond(
+                http::Response::builder().status(self.0).body("").unwrap(),
+            ))
+        }
+    }
+
+    struct Reject;
+
+    impl Interceptor for Reject {
+        fn before_sign(&self, _request: &mut Request) -> Result<InterceptAction> {
+            Err(EdgeGridError::Config("rejected".to_string()))
+        }
+    }
+
+    fn request() -> Request {
+        Client::new()
+            .request(Method::GET, "https://example.com/")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_first_synthetic_response_wins() {
+        let mut interceptors = Interceptors::default();
+        interceptors.push(Arc::new(Respond(204)));
+        interceptors.push(Arc::new(Reject));
+
+        let response = interceptors.before_sign(&mut request()).unwrap().unwrap();
+        assert_eq!(response.status(), 204);
+        assert!(interceptors.after_sign(&mut request()).unwrap().is_none());
+
+        let mut interceptors = Interceptors::default();
+        interceptors.push(Arc::new(Reject));
+        interceptors.push(Arc::new(Respond(204)));
+        assert!(interceptors.before_sign(&mut request()).is_err());
+    }
+}
@@ -57,12 +57,51 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod api;
+pub mod appsec;
 pub mod auth;
+pub mod cassette;
+pub mod ccu;
 pub mod client;
 pub mod config;
+pub mod cps;
+pub mod credentials;
+pub mod dns;
+pub mod edgekv;
+pub mod edgerc;
+pub mod edgeworkers;
 pub mod error;
+pub mod export;
+pub mod har;
+pub mod iam;
+pub mod intercept;
+pub mod network_lists;
+pub mod observe;
+pub mod operation;
+pub mod papi;
+pub mod secret;
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod validate;
 
 // Re-export main types
-pub use client::EdgeGridClient;
+pub use api::{Network, Problem};
+pub use cassette::{Cassette, CassetteMode, MatchOn};
+pub use client::{EdgeGridClient, EdgeGridRequestBuilder};
 pub use config::{EdgeGridConfig, MAX_BODY};
-pub use error::{EdgeGridError, Result};
\ No newline at end of file
+pub use credentials::SharedCredentials;
+pub use edgerc::Edgerc;
+pub use error::{EdgeGridError, Result};
+pub use export::{Redaction, SignedRequest};
+pub use har::HarRecorder;
+pub use intercept::{InterceptAction, Interceptor};
+#[cfg(feature = "metrics")]
+pub use observe::MetricsObserver;
+pub use observe::{RequestInfo, RequestObserver, RequestOutcome};
+pub use operation::{Operation, OperationState, OperationStatus};
+#[cfg(feature = "keyring")]
+pub use secret::KeyringSecretProvider;
+pub use secret::{CommandSecretProvider, FileSecretProvider, Secret, SecretProvider};
+pub use tokio_util::sync::CancellationToken;
+pub use validate::{Finding, Severity};
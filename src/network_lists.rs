@@ -0,0 +1,637 @@
+//! Network Lists (v2) API
+//!
+//! Typed IP and GEO lists, bulk element changes and activation. Elements are
+//! validated and normalized before they are sent: CIDR blocks are reduced to
+//! their network address, single-host blocks to the bare address and country
+//! codes to upper case.
+//!
+//! Updates carry the list's sync point, so an edit made elsewhere since the
+//! list was read is rejected instead of overwritten.
+//! [`NetworkLists::modify`] re-reads the list and re-applies the change when
+//! that happens.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::network_lists::{ActivationRequest, NetworkLists};
+//! use akamai_edgegrid::{EdgeGridClient, Network};
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "default")?;
+//! let lists = NetworkLists::new(client);
+//!
+//! lists.append("12345_BLOCKLIST", ["198.51.100.7", "203.0.113.0/24"]).await?;
+//! lists.remove("12345_BLOCKLIST", ["192.0.2.1"]).await?;
+//!
+//! let activation = lists
+//!     .activate("12345_BLOCKLIST", Network::Staging, &ActivationRequest::new("nightly sync"))
+//!     .await?;
+//! lists.activation_operation(activation.activation_id).wait().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{check_status, read_json, Network};
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use crate::operation::{Operation, OperationState, OperationStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Number of times [`NetworkLists::modify`] retries after a sync point conflict
+pub const MAX_CONFLICT_RETRIES: u32 = 3;
+
+const NETWORK_LISTS: &str = "/network-list/v2/network-lists";
+
+/// Network Lists API client
+#[derive(Debug, Clone)]
+pub struct NetworkLists {
+    client: EdgeGridClient,
+}
+
+/// What a list contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ListType {
+    /// IPv4 and IPv6 addresses and CIDR blocks
+    Ip,
+    /// ISO 3166 country codes, optionally with a subdivision
+    Geo,
+}
+
+impl fmt::Display for ListType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ListType::Ip => "IP",
+            ListType::Geo => "GEO",
+        })
+    }
+}
+
+/// A network list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkList {
+    /// List ID, e.g. `12345_BLOCKLIST`
+    pub unique_id: String,
+    /// List name
+    pub name: String,
+    /// What the list contains
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    /// List description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Number of elements
+    #[serde(default)]
+    pub element_count: u32,
+    /// Elements, present when the list was read with its elements
+    #[serde(default)]
+    pub list: Vec<String>,
+    /// Version of the list, incremented by every change
+    #[serde(default)]
+    pub sync_point: u64,
+    /// Whether the list is managed by Akamai and cannot be changed
+    #[serde(default)]
+    pub read_only: bool,
+    /// Activation status on staging, e.g. `ACTIVE` or `INACTIVE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staging_activation_status: Option<String>,
+    /// Activation status on production
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub production_activation_status: Option<String>,
+    /// Members not modelled above, e.g. `links` or `updateDate`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A network list to create
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewNetworkList {
+    /// List name
+    pub name: String,
+    /// What the list contains
+    #[serde(rename = "type")]
+    pub list_type: ListType,
+    /// List description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Initial elements
+    pub list: Vec<String>,
+    /// Contract owning the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_id: Option<String>,
+    /// Group owning the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+}
+
+impl NewNetworkList {
+    /// An empty list
+    pub fn new(name: impl Into<String>, list_type: ListType) -> Self {
+        Self {
+            name: name.into(),
+            list_type,
+            description: None,
+            list: Vec::new(),
+            contract_id: None,
+            group_id: None,
+        }
+    }
+
+    /// Set the description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add initial elements
+    pub fn elements<I, S>(mut self, elements: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.list.extend(elements.into_iter().map(Into::into));
+        self
+    }
+
+    /// Create the list in a contract and group
+    pub fn access_control_group(mut self, contract_id: impl Into<String>, group_id: i64) -> Self {
+        self.contract_id = Some(contract_id.into());
+        self.group_id = Some(group_id);
+        self
+    }
+}
+
+/// Details of an activation request
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationRequest {
+    /// Comment recorded with the activation
+    pub comments: String,
+    /// Addresses emailed when the activation finishes
+    pub notification_recipients: Vec<String>,
+}
+
+impl ActivationRequest {
+    /// An activation with a comment and no notifications
+    pub fn new(comments: impl Into<String>) -> Self {
+        Self {
+            comments: comments.into(),
+            notification_recipients: Vec::new(),
+        }
+    }
+
+    /// Email `recipient` when the activation finishes
+    pub fn notify(mut self, recipient: impl Into<String>) -> Self {
+        self.notification_recipients.push(recipient.into());
+        self
+    }
+}
+
+/// Status of a list activation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListActivation {
+    /// Activation ID
+    pub activation_id: i64,
+    /// `PENDING_ACTIVATION`, `ACTIVE`, `FAILED` and so on
+    pub activation_status: String,
+    /// List that is being activated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<String>,
+    /// Version of the list being activated
+    #[serde(default)]
+    pub sync_point: u64,
+    /// Comment recorded with the activation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation_comments: Option<String>,
+    /// Members not modelled above, e.g. `fast` or `dispatchCount`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl OperationStatus for ListActivation {
+    fn from_body(body: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn state(&self) -> OperationState {
+        match self.activation_status.as_str() {
+            "ACTIVE" => OperationState::Succeeded,
+            "FAILED" => OperationState::Failed(format!(
+                "network list activation {} failed",
+                self.activation_id
+            )),
+            _ => OperationState::Pending,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListsResponse {
+    network_lists: Vec<NetworkList>,
+}
+
+#[derive(Serialize)]
+struct ElementsBody<'a> {
+    list: &'a [String],
+}
+
+impl NetworkLists {
+    /// Use `client` for Network Lists requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self { client }
+    }
+
+    /// Lists without their elements, optionally filtered by type and a
+    /// search string matched against names and elements
+    pub async fn lists(
+        &self,
+        list_type: Option<ListType>,
+        search: Option<&str>,
+    ) -> Result<Vec<NetworkList>> {
+        let mut request = self.client.get(NETWORK_LISTS);
+        if let Some(list_type) = list_type {
+            request = request.query("listType", list_type.to_string());
+        }
+        if let Some(search) = search {
+            request = request.query("search", search);
+        }
+        let response: ListsResponse = read_json(request.send().await?).await?;
+        Ok(response.network_lists)
+    }
+
+    /// A single list with its elements
+    pub async fn list(&self, id: &str) -> Result<NetworkList> {
+        let response = self
+            .client
+            .get(&list_path(id))
+            .query("includeElements", "true")
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Create a list, normalizing its elements
+    pub async fn create(&self, list: &NewNetworkList) -> Result<NetworkList> {
+        let list = NewNetworkList {
+            list: normalize_elements(list.list_type, &list.list)?,
+            ..list.clone()
+        };
+        let response = self.client.post(NETWORK_LISTS).json(&list).send().await?;
+        read_json(response).await
+    }
+
+    /// Replace a list's name, description and elements
+    ///
+    /// Elements the list does not already hold are validated and normalized;
+    /// existing ones are sent unchanged, even if they are not in the form
+    /// [`normalize_element`] produces. The update is rejected with a 409
+    /// [`EdgeGridError::Api`] if the list changed since `list.sync_point`;
+    /// see [`is_conflict`].
+    pub async fn update(&self, list: &NetworkList) -> Result<NetworkList> {
+        let normalized = normalize_elements(list.list_type, &list.list)
+            .is_ok_and(|elements| elements == list.list);
+        let existing = if normalized {
+            Vec::new()
+        } else {
+            self.list(&list.unique_id).await?.list
+        };
+        self.save(list, &existing).await
+    }
+
+    /// Save `list`, normalizing the elements that are not in `existing`
+    async fn save(&self, list: &NetworkList, existing: &[String]) -> Result<NetworkList> {
+        let existing: HashSet<&str> = existing.iter().map(String::as_str).collect();
+        let mut seen = HashSet::new();
+        let mut elements = Vec::with_capacity(list.list.len());
+        for element in &list.list {
+            let element = if existing.contains(element.as_str()) {
+                element.clone()
+            } else {
+                normalize_element(list.list_type, element)?
+            };
+            if seen.insert(element.clone()) {
+                elements.push(element);
+            }
+        }
+
+        let list = NetworkList {
+            list: elements,
+            ..list.clone()
+        };
+        let response = self
+            .client
+            .put(&list_path(&list.unique_id))
+            .json(&list)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Read a list, apply `change` and save it, starting over if someone
+    /// else changed the list in between
+    ///
+    /// Only elements added by `change` are validated and normalized. Gives up
+    /// after [`MAX_CONFLICT_RETRIES`] conflicts. `change` may be called once
+    /// per attempt.
+    pub async fn modify<F>(&self, id: &str, mut change: F) -> Result<NetworkList>
+    where
+        F: FnMut(&mut NetworkList),
+    {
+        let mut conflicts = 0;
+        loop {
+            let mut list = self.list(id).await?;
+            let existing = list.list.clone();
+            change(&mut list);
+            match self.save(&list, &existing).await {
+                Err(e) if is_conflict(&e) && conflicts < MAX_CONFLICT_RETRIES => {
+                    conflicts += 1;
+                    tracing::debug!(id, conflicts, "network list changed concurrently, retrying");
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Delete a list that is not active on either network
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        check_status(self.client.delete(&list_path(id)).send().await?).await?;
+        Ok(())
+    }
+
+    /// Add elements to a list, ignoring ones it already contains
+    ///
+    /// The list's type is read first to validate the elements.
+    pub async fn append<I, S>(&self, id: &str, elements: I) -> Result<NetworkList>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let elements: Vec<String> = elements
+            .into_iter()
+            .map(|element| element.as_ref().to_string())
+            .collect();
+        let list_type = self.list_type(id).await?;
+        let elements = normalize_elements(list_type, &elements)?;
+
+        let response = self
+            .client
+            .post(&format!("{}/append", list_path(id)))
+            .json(&ElementsBody { list: &elements })
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Remove elements from a list, ignoring ones it does not contain
+    ///
+    /// Elements are compared after normalization, so removing
+    /// `192.0.2.1/32` removes `192.0.2.1`.
+    pub async fn remove<I, S>(&self, id: &str, elements: I) -> Result<NetworkList>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let elements: Vec<String> = elements
+            .into_iter()
+            .map(|element| element.as_ref().to_string())
+            .collect();
+        let list_type = self.list_type(id).await?;
+        let removed: HashSet<String> = normalize_elements(list_type, &elements)?
+            .into_iter()
+            .collect();
+
+        self.modify(id, |list| {
+            list.list.retain(|element| {
+                normalize_element(list.list_type, element)
+                    .map(|element| !removed.contains(&element))
+                    .unwrap_or(true)
+            })
+        })
+        .await
+    }
+
+    /// Start activating the current version of a list
+    pub async fn activate(
+        &self,
+        id: &str,
+        network: Network,
+        request: &ActivationRequest,
+    ) -> Result<ListActivation> {
+        let response = self
+            .client
+            .post(&format!("{}/activate", environment_path(id, network)))
+            .json(request)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Activation status of a list on a network
+    pub async fn activation_status(&self, id: &str, network: Network) -> Result<ListActivation> {
+        let response = self
+            .client
+            .get(&format!("{}/status", environment_path(id, network)))
+            .send()
+            .await?;
+        read_json(response).await
+    }
+
+    /// Poll an activation until it is active or has failed
+    pub fn activation_operation(&self, activation_id: i64) -> Operation<ListActivation> {
+        self.client
+            .operation(&format!("/network-list/v2/activations/{}", activation_id))
+    }
+
+    async fn list_type(&self, id: &str) -> Result<ListType> {
+        let response = self
+            .client
+            .get(&list_path(id))
+            .query("includeElements", "false")
+            .send()
+            .await?;
+        let list: NetworkList = read_json(response).await?;
+        Ok(list.list_type)
+    }
+}
+
+fn list_path(id: &str) -> String {
+    format!("{}/{}", NETWORK_LISTS, id)
+}
+
+fn environment_path(id: &str, network: Network) -> String {
+    format!(
+        "{}/environments/{}",
+        list_path(id),
+        network.as_str().to_uppercase()
+    )
+}
+
+/// Whether `error` is a 409 returned because a list changed since it was read
+pub fn is_conflict(error: &EdgeGridError) -> bool {
+    error.problem().is_some_and(|problem| problem.status == 409)
+}
+
+/// Validate and normalize a list element
+///
+/// IP elements are an IPv4 or IPv6 address or CIDR block. Blocks are reduced
+/// to their network address (`192.0.2.7/24` becomes `192.0.2.0/24`) and
+/// single-host blocks to the bare address. GEO elements are a two-letter
+/// country code, optionally followed by `:` and a subdivision code, and are
+/// upper-cased.
+pub fn normalize_element(list_type: ListType, element: &str) -> Result<String> {
+    let element = element.trim();
+    let normalized = match list_type {
+        ListType::Ip => normalize_ip(element),
+        ListType::Geo => normalize_geo(element),
+    };
+    normalized.ok_or_else(|| {
+        EdgeGridError::Config(format!("invalid {} list element '{}'", list_type, element))
+    })
+}
+
+/// Normalize every element, dropping duplicates while keeping the first
+/// occurrence's position
+pub fn normalize_elements(list_type: ListType, elements: &[String]) -> Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(elements.len());
+    for element in elements {
+        let element = normalize_element(list_type, element)?;
+        if seen.insert(element.clone()) {
+            normalized.push(element);
+        }
+    }
+    Ok(normalized)
+}
+
+fn normalize_ip(element: &str) -> Option<String> {
+    let (address, prefix) = match element.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (element, None),
+    };
+    let address: IpAddr = address.parse().ok()?;
+    let max = match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    let prefix = match prefix {
+        Some(prefix) if prefix.bytes().all(|b| b.is_ascii_digit()) => prefix.parse().ok()?,
+        Some(_) => return None,
+        None => max,
+    };
+    if prefix > max {
+        return None;
+    }
+    if prefix == max {
+        return Some(address.to_string());
+    }
+
+    let network = match address {
+        IpAddr::V4(address) => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(address) & mask))
+        }
+        IpAddr::V6(address) => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(address) & mask))
+        }
+    };
+    Some(format!("{}/{}", network, prefix))
+}
+
+fn normalize_geo(element: &str) -> Option<String> {
+    let (country, subdivision) = match element.split_once(':') {
+        Some((country, subdivision)) => (country, Some(subdivision)),
+        None => (element, None),
+    };
+    if country.len() != 2 || !country.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    match subdivision {
+        None => Some(country.to_ascii_uppercase()),
+        Some(subdivision)
+            if (1..=3).contains(&subdivision.len())
+                && subdivision.bytes().all(|b| b.is_ascii_alphanumeric()) =>
+        {
+            Some(format!("{}:{}", country, subdivision).to_ascii_uppercase())
+        }
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(element: &str) -> Option<String> {
+        normalize_element(ListType::Ip, element).ok()
+    }
+
+    #[test]
+    fn test_normalize_ip_elements() {
+        assert_eq!(ip(" 192.0.2.7 ").as_deref(), Some("192.0.2.7"));
+        assert_eq!(ip("192.0.2.7/24").as_deref(), Some("192.0.2.0/24"));
+        assert_eq!(ip("192.0.2.7/32").as_deref(), Some("192.0.2.7"));
+        assert_eq!(ip("10.1.2.3/0").as_deref(), Some("0.0.0.0/0"));
+        assert_eq!(ip("2001:DB8:0:0:1::5/64").as_deref(), Some("2001:db8::/64"));
+        assert_eq!(ip("2001:db8::1/128").as_deref(), Some("2001:db8::1"));
+
+        for invalid in [
+            "192.0.2.256",
+            "192.0.2.1/33",
+            "192.0.2.1/",
+            "192.0.2.1/+8",
+            "2001:db8::/129",
+            "example.com",
+            "",
+        ] {
+            assert_eq!(ip(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_normalize_geo_elements() {
+        let geo = |element| normalize_element(ListType::Geo, element).ok();
+        assert_eq!(geo("se").as_deref(), Some("SE"));
+        assert_eq!(geo("us:ca").as_deref(), Some("US:CA"));
+        assert_eq!(geo("USA"), None);
+        assert_eq!(geo("US:"), None);
+        assert_eq!(geo("1A"), None);
+    }
+
+    #[test]
+    fn test_normalize_elements_drops_duplicates() {
+        let elements = [
+            "192.0.2.9/24",
+            "198.51.100.1",
+            "192.0.2.0/24",
+            "198.51.100.1/32",
+        ]
+        .map(String::from);
+        assert_eq!(
+            normalize_elements(ListType::Ip, &elements).unwrap(),
+            vec!["192.0.2.0/24", "198.51.100.1"]
+        );
+
+        let err = normalize_elements(ListType::Ip, &["SE".to_string()]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: invalid IP list element 'SE'"
+        );
+    }
+
+    #[test]
+    fn test_activation_state() {
+        let activation: ListActivation = serde_json::from_str(
+            r#"{"activationId": 7, "activationStatus": "PENDING_ACTIVATION", "syncPoint": 4, "fast": true}"#,
+        )
+        .unwrap();
+        assert_eq!(activation.state(), OperationState::Pending);
+        assert_eq!(activation.extra["fast"], true);
+    }
+}
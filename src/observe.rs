@@ -0,0 +1,223 @@
+//! Request observers for metrics and monitoring
+//!
+//! A [`RequestObserver`] registered with
+//! [`EdgeGridClient::with_observer`](crate::EdgeGridClient::with_observer) is
+//! called before each request is sent and after it completes, with the
+//! normalized endpoint, total latency and outcome. With the `metrics` feature,
+//! [`MetricsObserver`] exports these as counters and histograms through the
+//! [`metrics`](https://docs.rs/metrics) facade.
+
+use crate::error::EdgeGridError;
+use crate::telemetry::path_template;
+use reqwest::{Method, Request, StatusCode};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The request being observed
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// HTTP method
+    pub method: Method,
+    /// Path with identifiers replaced by `{id}`, suitable as a metric label
+    pub path: String,
+    /// Full request URL
+    pub url: url::Url,
+}
+
+impl RequestInfo {
+    pub(crate) fn new(request: &Request) -> Self {
+        Self {
+            method: request.method().clone(),
+            path: path_template(request.url().path()),
+            url: request.url().clone(),
+        }
+    }
+}
+
+/// How an observed request ended
+#[derive(Debug, Clone)]
+pub struct RequestOutcome {
+    /// Response status, if a response was received
+    pub status: Option<StatusCode>,
+    /// Coarse outcome label: `2xx`..`5xx`, `timeout`, `cancelled` or `error`
+    pub status_class: &'static str,
+    /// Error message if the request failed or the response could not be read
+    pub error: Option<String>,
+    /// Time from sending the request until the response was fully handled
+    pub latency: Duration,
+}
+
+impl RequestOutcome {
+    pub(crate) fn new(
+        status: Option<StatusCode>,
+        error: Option<&EdgeGridError>,
+        latency: Duration,
+    ) -> Self {
+        let status_class = match (status, error) {
+            (Some(status), _) => match status.as_u16() {
+                100..=199 => "1xx",
+                200..=299 => "2xx",
+                300..=399 => "3xx",
+                400..=499 => "4xx",
+                _ => "5xx",
+            },
+            (None, Some(EdgeGridError::Cancelled)) => "cancelled",
+            (None, Some(e)) if e.is_timeout() => "timeout",
+            (None, _) => "error",
+        };
+
+        Self {
+            status,
+            status_class,
+            error: error.map(|e| e.to_string()),
+            latency,
+        }
+    }
+
+    /// Whether the request completed with a successful status
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.status.is_some_and(|s| s.is_success())
+    }
+
+    /// Whether the request failed, the response could not be read or it has
+    /// a 4xx or 5xx status
+    pub fn is_error(&self) -> bool {
+        self.error.is_some() || !matches!(self.status_class, "1xx" | "2xx" | "3xx")
+    }
+}
+
+/// Hooks invoked around every request made by an [`EdgeGridClient`](crate::EdgeGridClient)
+///
+/// Both methods have empty default implementations. They are called on the
+/// request path, so they should return quickly.
+pub trait RequestObserver: Send + Sync {
+    /// Called once the request has been built, before it is signed and sent
+    fn on_request(&self, _request: &RequestInfo) {}
+
+    /// Called when the request has completed, failed, timed out or been cancelled
+    fn on_response(&self, _request: &RequestInfo, _outcome: &RequestOutcome) {}
+}
+
+/// The observers registered on a client
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Arc<dyn RequestObserver>>);
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Arc<dyn RequestObserver>) {
+        self.0.push(observer);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn on_request(&self, request: &RequestInfo) {
+        for observer in &self.0 {
+            observer.on_request(request);
+        }
+    }
+
+    pub(crate) fn on_response(&self, request: &RequestInfo, outcome: &RequestOutcome) {
+        for observer in &self.0 {
+            observer.on_response(request, outcome);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
+
+/// Exports request counts, error counts and latency histograms via the `metrics` crate
+///
+/// Metrics are labeled with `method`, the normalized `path` and `status`
+/// (the outcome's status class):
+///
+/// - `akamai_edgegrid_requests_total` (counter)
+/// - `akamai_edgegrid_errors_total` (counter; outcomes where
+///   [`RequestOutcome::is_error`] holds)
+/// - `akamai_edgegrid_request_duration_seconds` (histogram)
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Default)]
+pub struct MetricsObserver {
+    prefix: Option<String>,
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsObserver {
+    /// Create an observer using the default `akamai_edgegrid` metric names
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `prefix` instead of `akamai_edgegrid` for metric names
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: Some(prefix.into()),
+        }
+    }
+
+    fn name(&self, metric: &str) -> String {
+        format!(
+            "{}_{}",
+            self.prefix.as_deref().unwrap_or("akamai_edgegrid"),
+            metric
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl RequestObserver for MetricsObserver {
+    fn on_response(&self, request: &RequestInfo, outcome: &RequestOutcome) {
+        let labels = [
+            ("method", request.method.to_string()),
+            ("path", request.path.clone()),
+            ("status", outcome.status_class.to_string()),
+        ];
+
+        metrics::counter!(self.name("requests_total"), &labels).increment(1);
+        metrics::histogram!(self.name("request_duration_seconds"), &labels)
+            .record(outcome.latency.as_secs_f64());
+
+        if outcome.is_error() {
+            metrics::counter!(self.name("errors_total"), &labels).increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_status_class() {
+        let latency = Duration::from_millis(5);
+
+        let ok = RequestOutcome::new(Some(StatusCode::OK), None, latency);
+        assert_eq!(ok.status_class, "2xx");
+        assert!(ok.is_success());
+        assert!(!ok.is_error());
+
+        let unreadable = EdgeGridError::Config("truncated body".to_string());
+        let failed_read = RequestOutcome::new(Some(StatusCode::OK), Some(&unreadable), latency);
+        assert_eq!(failed_read.status_class, "2xx");
+        assert!(!failed_read.is_success());
+        assert!(failed_read.is_error());
+
+        let moved = RequestOutcome::new(Some(StatusCode::FOUND), None, latency);
+        assert!(!moved.is_error());
+        let missing = RequestOutcome::new(Some(StatusCode::NOT_FOUND), None, latency);
+        assert!(missing.is_error());
+
+        let timeout = EdgeGridError::Timeout(latency);
+        let timed_out = RequestOutcome::new(None, Some(&timeout), latency);
+        assert_eq!(timed_out.status_class, "timeout");
+        assert!(!timed_out.is_success());
+
+        let cancelled = RequestOutcome::new(None, Some(&EdgeGridError::Cancelled), latency);
+        assert_eq!(cancelled.status_class, "cancelled");
+    }
+}
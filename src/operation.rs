@@ -0,0 +1,346 @@
+//! Polling long-running operations
+//!
+//! Activations and similar changes are accepted immediately and finish
+//! minutes later; the API returns a link to poll for their status.
+//! [`Operation`] follows that link until the status is final, waiting longer
+//! between polls each time, honouring `Retry-After` and giving up after a
+//! timeout.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::operation::{OperationState, OperationStatus};
+//! use akamai_edgegrid::EdgeGridClient;
+//! use serde::Deserialize;
+//! use std::time::Duration;
+//!
+//! #[derive(Deserialize)]
+//! struct Status {
+//!     status: String,
+//! }
+//!
+//! impl OperationStatus for Status {
+//!     fn from_body(body: &[u8]) -> akamai_edgegrid::Result<Self> {
+//!         Ok(serde_json::from_slice(body)?)
+//!     }
+//!
+//!     fn state(&self) -> OperationState {
+//!         match self.status.as_str() {
+//!             "ACTIVE" => OperationState::Succeeded,
+//!             "FAILED" => OperationState::Failed(self.status.clone()),
+//!             _ => OperationState::Pending,
+//!         }
+//!     }
+//! }
+//!
+//! # async fn example(client: EdgeGridClient, link: &str) -> akamai_edgegrid::Result<()> {
+//! let status: Status = client
+//!     .operation(link)
+//!     .timeout(Duration::from_secs(1800))
+//!     .on_progress(|progress| println!("poll {}: {:?}", progress.attempt, progress.state))
+//!     .wait()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::check_status;
+use crate::client::EdgeGridClient;
+use crate::error::{EdgeGridError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Default time to wait for an operation to finish
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3600);
+
+const DEFAULT_INITIAL_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where an operation stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationState {
+    /// Still running; poll again
+    Pending,
+    /// Finished successfully
+    Succeeded,
+    /// Finished unsuccessfully, with a description of why
+    Failed(String),
+}
+
+/// A status document returned by an operation's status link
+pub trait OperationStatus: Sized {
+    /// Parse a successful status response body
+    fn from_body(body: &[u8]) -> Result<Self>;
+
+    /// Where the operation stands according to this status
+    fn state(&self) -> OperationState;
+}
+
+/// Reported after each status poll
+#[derive(Debug)]
+pub struct Progress<'a, T> {
+    /// Number of the poll, starting at 1
+    pub attempt: u32,
+    /// Time since waiting started
+    pub elapsed: Duration,
+    /// The status returned by this poll
+    pub status: &'a T,
+    /// Where the operation stands
+    pub state: &'a OperationState,
+    /// Delay before the next poll, or `None` if the operation is finished
+    pub next_poll: Option<Duration>,
+}
+
+type ProgressCallback<T> = Box<dyn FnMut(Progress<'_, T>) + Send>;
+
+/// A long-running operation polled through its status link
+pub struct Operation<T> {
+    client: EdgeGridClient,
+    link: String,
+    headers: Vec<(String, String)>,
+    query_params: Vec<(String, String)>,
+    timeout: Duration,
+    initial_interval: Duration,
+    max_interval: Duration,
+    cancel: Option<CancellationToken>,
+    on_progress: Option<ProgressCallback<T>>,
+    status: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for Operation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Operation")
+            .field("link", &self.link)
+            .field("timeout", &self.timeout)
+            .field("initial_interval", &self.initial_interval)
+            .field("max_interval", &self.max_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Outcome of a single poll
+enum Poll<T> {
+    Status(T, Option<Duration>),
+    /// Rate limited or temporarily unavailable; try again later
+    Throttled(Option<Duration>),
+}
+
+impl<T: OperationStatus> Operation<T> {
+    /// Poll `link`, a path or URL returned by the API, through `client`
+    pub fn new(client: EdgeGridClient, link: impl Into<String>) -> Self {
+        Self {
+            client,
+            link: link.into(),
+            headers: Vec::new(),
+            query_params: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            initial_interval: DEFAULT_INITIAL_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            cancel: None,
+            on_progress: None,
+            status: PhantomData,
+        }
+    }
+
+    /// The status link being polled
+    pub fn link(&self) -> &str {
+        &self.link
+    }
+
+    /// Send a header with every status request
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send a query parameter with every status request
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Give up with [`EdgeGridError::Timeout`] after `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Wait `initial` before the first poll, doubling the wait after each
+    /// poll up to `max`
+    pub fn interval(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_interval = initial;
+        self.max_interval = max.max(initial);
+        self
+    }
+
+    /// Stop waiting with [`EdgeGridError::Cancelled`] once `token` is cancelled
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Call `callback` after every successful poll
+    pub fn on_progress(mut self, callback: impl FnMut(Progress<'_, T>) + Send + 'static) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Fetch the current status once
+    pub async fn status(&self) -> Result<T> {
+        match self.poll().await? {
+            Poll::Status(status, _) => Ok(status),
+            Poll::Throttled(_) => Err(EdgeGridError::Config(format!(
+                "status of {} is temporarily unavailable",
+                self.link
+            ))),
+        }
+    }
+
+    /// Poll until the operation succeeds, fails or times out
+    ///
+    /// Returns the final status on success and
+    /// [`EdgeGridError::OperationFailed`] on failure. Rate limiting, server
+    /// errors and connection failures are retried until the timeout; if the
+    /// last poll failed, its error is returned instead of the timeout.
+    pub async fn wait(mut self) -> Result<T> {
+        let started = Instant::now();
+        let mut interval = self.initial_interval;
+        let mut attempt = 0;
+
+        loop {
+            self.sleep(interval.min(self.remaining(started))).await?;
+            attempt += 1;
+
+            let (status, retry_after, error) = match self.poll().await {
+                Ok(Poll::Status(status, retry_after)) => (Some(status), retry_after, None),
+                Ok(Poll::Throttled(retry_after)) => (None, retry_after, None),
+                Err(e) if e.is_retryable() => (None, None, Some(e)),
+                Err(e) => return Err(e),
+            };
+
+            // A `Retry-After` of zero or in the past must not turn into a busy loop
+            interval = match retry_after {
+                Some(delay) => delay.max(self.initial_interval),
+                None => (interval * 2).min(self.max_interval),
+            };
+            let out_of_time = self.remaining(started).is_zero();
+
+            let Some(status) = status else {
+                if out_of_time {
+                    return Err(error.unwrap_or(EdgeGridError::Timeout(self.timeout)));
+                }
+                continue;
+            };
+
+            let state = status.state();
+            if let Some(callback) = self.on_progress.as_mut() {
+                let finished = state != OperationState::Pending || out_of_time;
+                callback(Progress {
+                    attempt,
+                    elapsed: started.elapsed(),
+                    status: &status,
+                    state: &state,
+                    next_poll: (!finished).then_some(interval),
+                });
+            }
+
+            match state {
+                OperationState::Succeeded => return Ok(status),
+                OperationState::Failed(reason) => {
+                    return Err(EdgeGridError::OperationFailed(format!(
+                        "{}: {}",
+                        self.link, reason
+                    )))
+                }
+                OperationState::Pending if out_of_time => {
+                    return Err(EdgeGridError::Timeout(self.timeout))
+                }
+                OperationState::Pending => {}
+            }
+        }
+    }
+
+    async fn poll(&self) -> Result<Poll<T>> {
+        let mut request = self.client.get(&self.link);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        for (name, value) in &self.query_params {
+            request = request.query(name, value);
+        }
+
+        let response = request.send().await?;
+        let retry_after = retry_after(response.headers());
+        if matches!(
+            response.status(),
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            return Ok(Poll::Throttled(retry_after));
+        }
+
+        let body = check_status(response).await?.bytes().await?;
+        Ok(Poll::Status(T::from_body(&body)?, retry_after))
+    }
+
+    fn remaining(&self, started: Instant) -> Duration {
+        self.timeout.saturating_sub(started.elapsed())
+    }
+
+    async fn sleep(&self, delay: Duration) -> Result<()> {
+        match &self.cancel {
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(EdgeGridError::Cancelled),
+                _ = tokio::time::sleep(delay) => Ok(()),
+            },
+            None => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `Retry-After` as either delay seconds or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_retry_after_seconds_and_date() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        let later = (Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&later).unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(80) && delay <= Duration::from_secs(90));
+    }
+}
@@ -0,0 +1,827 @@
+//! Property Manager (PAPI v1) API
+//!
+//! Typed access to contracts, groups, properties, versions, hostnames, rule
+//! trees and activations. Requests are sent with `PAPI-Use-Prefixes: true`, so
+//! identifiers keep their `ctr_`, `grp_`, `prp_` and `atv_` prefixes.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::papi::{Papi, PatchOperation};
+//! use akamai_edgegrid::EdgeGridClient;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let client = EdgeGridClient::from_edgerc("~/.edgerc", "papi")?;
+//! let papi = Papi::new(client).with_group("ctr_C-0N7RAC7", "grp_12345");
+//!
+//! let property = papi.property("prp_175780").await?;
+//! let latest = papi.version("prp_175780", property.latest_version).await?;
+//! let version = papi
+//!     .create_version_from_etag("prp_175780", latest.property_version, &latest.etag)
+//!     .await?;
+//!
+//! let tree = papi.rules("prp_175780", version).await?;
+//! let patch = [PatchOperation::replace(
+//!     "/rules/behaviors/0/options/hostname",
+//!     "origin.example.com",
+//! )];
+//! papi.patch_rules("prp_175780", version, &tree.etag, &patch).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::{read_json, Network};
+use crate::client::{EdgeGridClient, EdgeGridRequestBuilder};
+use crate::error::{EdgeGridError, Result};
+use crate::operation::{Operation, OperationState, OperationStatus};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, IF_MATCH};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+const USE_PREFIXES: &str = "PAPI-Use-Prefixes";
+const JSON_PATCH: &str = "application/json-patch+json";
+
+/// Property Manager API client
+#[derive(Debug, Clone)]
+pub struct Papi {
+    client: EdgeGridClient,
+    group: Option<(String, String)>,
+    rule_format: Option<String>,
+}
+
+/// A contract the credential can access
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contract {
+    /// Contract ID, e.g. `ctr_C-0N7RAC7`
+    pub contract_id: String,
+    /// Contract type, e.g. `DIRECT_CUSTOMER`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_type_name: Option<String>,
+}
+
+/// A group in the account hierarchy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    /// Group ID, e.g. `grp_12345`
+    pub group_id: String,
+    /// Group name
+    pub group_name: String,
+    /// Parent group, absent for the top-level group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_group_id: Option<String>,
+    /// Contracts the group belongs to
+    #[serde(default)]
+    pub contract_ids: Vec<String>,
+}
+
+/// A property and the versions active on each network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Property {
+    /// Property ID, e.g. `prp_175780`
+    pub property_id: String,
+    /// Property name
+    pub property_name: String,
+    /// Account ID
+    #[serde(default)]
+    pub account_id: Option<String>,
+    /// Contract ID, e.g. `ctr_C-0N7RAC7`
+    pub contract_id: String,
+    /// Group ID, e.g. `grp_12345`
+    pub group_id: String,
+    /// Most recent version
+    pub latest_version: u32,
+    /// Version active on staging
+    #[serde(default)]
+    pub staging_version: Option<u32>,
+    /// Version active on production
+    #[serde(default)]
+    pub production_version: Option<u32>,
+    /// Asset ID used by other APIs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_id: Option<String>,
+    /// Free-form note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// One version of a property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyVersion {
+    /// Version number
+    pub property_version: u32,
+    /// Changes whenever the version is edited; needed to create a version from it
+    pub etag: String,
+    /// `ACTIVE`, `INACTIVE`, `PENDING` or `DEACTIVATED` on staging
+    #[serde(default)]
+    pub staging_status: String,
+    /// `ACTIVE`, `INACTIVE`, `PENDING` or `DEACTIVATED` on production
+    #[serde(default)]
+    pub production_status: String,
+    /// User who last edited the version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by_user: Option<String>,
+    /// When the version was last edited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_date: Option<String>,
+    /// Product the version is built on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    /// Rule format, e.g. `v2023-01-05` or `latest`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_format: Option<String>,
+    /// Free-form note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A hostname served by a property version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hostname {
+    /// Hostname clients request
+    pub cname_from: String,
+    /// Always `EDGE_HOSTNAME`
+    #[serde(default = "edge_hostname")]
+    pub cname_type: String,
+    /// Edge hostname the hostname is CNAMEd to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cname_to: Option<String>,
+    /// Edge hostname ID, e.g. `ehn_895822`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_hostname_id: Option<String>,
+    /// `CPS_MANAGED` or `DEFAULT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_provisioning_type: Option<String>,
+}
+
+impl Hostname {
+    /// Map `cname_from` to the edge hostname `cname_to`
+    pub fn new(cname_from: impl Into<String>, cname_to: impl Into<String>) -> Self {
+        Self {
+            cname_from: cname_from.into(),
+            cname_type: edge_hostname(),
+            cname_to: Some(cname_to.into()),
+            edge_hostname_id: None,
+            cert_provisioning_type: None,
+        }
+    }
+}
+
+fn edge_hostname() -> String {
+    "EDGE_HOSTNAME".to_string()
+}
+
+/// A property version's rule tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTree {
+    /// Property ID, e.g. `prp_175780`
+    #[serde(default)]
+    pub property_id: String,
+    /// Version number
+    #[serde(default)]
+    pub property_version: u32,
+    /// Pass to [`Papi::update_rules`] and [`Papi::patch_rules`] to guard against concurrent edits
+    #[serde(default)]
+    pub etag: String,
+    /// Rule format, e.g. `v2023-01-05` or `latest`
+    #[serde(default)]
+    pub rule_format: String,
+    /// The top-level `default` rule
+    pub rules: Rule,
+    /// Validation errors reported for the tree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Value>,
+    /// Validation warnings reported for the tree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Value>,
+}
+
+/// A rule and its children
+///
+/// Members not modelled here, such as `variables` or `customOverride`, are
+/// kept in `extra` and written back unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    /// Rule name
+    pub name: String,
+    /// Conditions for the rule's behaviors to apply
+    #[serde(default)]
+    pub criteria: Vec<Criterion>,
+    /// Behaviors applied when the criteria match
+    #[serde(default)]
+    pub behaviors: Vec<Behavior>,
+    /// Nested rules
+    #[serde(default)]
+    pub children: Vec<Rule>,
+    /// `all` or `any`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub criteria_must_satisfy: Option<String>,
+    /// Free-form comments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>,
+    /// Rule options, e.g. `is_secure` on the default rule
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub options: Map<String, Value>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A behavior in a rule
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Behavior {
+    /// Behavior or criterion name, e.g. `origin`
+    pub name: String,
+    /// Behavior or criterion options
+    #[serde(default)]
+    pub options: Map<String, Value>,
+    /// Members not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A match criterion in a rule; criteria share the shape of behaviors
+pub type Criterion = Behavior;
+
+/// A JSON Patch (RFC 6902) operation on a rule tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add `value` at `path`
+    Add { path: String, value: Value },
+    /// Remove the value at `path`
+    Remove { path: String },
+    /// Replace the value at `path`
+    Replace { path: String, value: Value },
+    /// Move the value at `from` to `path`
+    Move { from: String, path: String },
+    /// Copy the value at `from` to `path`
+    Copy { from: String, path: String },
+    /// Fail unless the value at `path` equals `value`
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    /// Add `value` at `path`
+    pub fn add(path: impl Into<String>, value: impl Into<Value>) -> Self {
+        PatchOperation::Add {
+            path: path.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Remove the value at `path`
+    pub fn remove(path: impl Into<String>) -> Self {
+        PatchOperation::Remove { path: path.into() }
+    }
+
+    /// Replace the value at `path` with `value`
+    pub fn replace(path: impl Into<String>, value: impl Into<Value>) -> Self {
+        PatchOperation::Replace {
+            path: path.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Parameters for activating a property version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationRequest {
+    /// Version number
+    pub property_version: u32,
+    /// Network
+    pub network: Network,
+    /// Free-form note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Addresses notified of progress
+    pub notify_emails: Vec<String>,
+    /// Accept validation warnings instead of failing the activation
+    #[serde(default)]
+    pub acknowledge_all_warnings: bool,
+}
+
+impl ActivationRequest {
+    /// Activate `property_version` on `network`, notifying `notify_emails`
+    pub fn new<I, S>(property_version: u32, network: Network, notify_emails: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            property_version,
+            network,
+            note: None,
+            notify_emails: notify_emails.into_iter().map(Into::into).collect(),
+            acknowledge_all_warnings: false,
+        }
+    }
+
+    /// Attach a note to the activation
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Accept validation warnings
+    pub fn acknowledge_all_warnings(mut self) -> Self {
+        self.acknowledge_all_warnings = true;
+        self
+    }
+}
+
+/// An activation or deactivation of a property version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activation {
+    /// Activation ID, e.g. `atv_67037`
+    pub activation_id: String,
+    /// Version number
+    pub property_version: u32,
+    /// Network
+    pub network: Network,
+    /// `PENDING`, `ACTIVE`, `FAILED` and so on
+    pub status: String,
+    /// `ACTIVATE` or `DEACTIVATE`
+    #[serde(default)]
+    pub activation_type: String,
+    /// When the activation was submitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submit_date: Option<String>,
+    /// When the status last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_date: Option<String>,
+    /// Free-form note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Addresses notified of progress
+    #[serde(default)]
+    pub notify_emails: Vec<String>,
+}
+
+impl OperationStatus for Activation {
+    fn from_body(body: &[u8]) -> Result<Self> {
+        let response: ActivationsResponse = serde_json::from_slice(body)?;
+        response.activations.into_first("activation")
+    }
+
+    fn state(&self) -> OperationState {
+        let done = match self.activation_type.as_str() {
+            "DEACTIVATE" => "DEACTIVATED",
+            _ => "ACTIVE",
+        };
+        match self.status.as_str() {
+            status if status == done => OperationState::Succeeded,
+            "FAILED" | "ABORTED" => OperationState::Failed(format!(
+                "activation {} is {}",
+                self.activation_id, self.status
+            )),
+            _ => OperationState::Pending,
+        }
+    }
+}
+
+/// PAPI wraps lists as `{"<name>": {"items": [...]}}`
+#[derive(Deserialize)]
+struct Items<T> {
+    items: Vec<T>,
+}
+
+impl<T> Items<T> {
+    fn into_first(self, what: &str) -> Result<T> {
+        self.items
+            .into_iter()
+            .next()
+            .ok_or_else(|| EdgeGridError::Config(format!("PAPI returned no {}", what)))
+    }
+}
+
+#[derive(Deserialize)]
+struct ContractsResponse {
+    contracts: Items<Contract>,
+}
+
+#[derive(Deserialize)]
+struct GroupsResponse {
+    groups: Items<Group>,
+}
+
+#[derive(Deserialize)]
+struct PropertiesResponse {
+    properties: Items<Property>,
+}
+
+#[derive(Deserialize)]
+struct VersionsResponse {
+    versions: Items<PropertyVersion>,
+}
+
+#[derive(Deserialize)]
+struct HostnamesResponse {
+    hostnames: Items<Hostname>,
+}
+
+#[derive(Deserialize)]
+struct ActivationsResponse {
+    activations: Items<Activation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionLink {
+    version_link: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivationLink {
+    activation_link: String,
+}
+
+impl Papi {
+    /// Use `client` for Property Manager requests
+    pub fn new(client: EdgeGridClient) -> Self {
+        Self {
+            client,
+            group: None,
+            rule_format: None,
+        }
+    }
+
+    /// Send `contractId` and `groupId` with every property request
+    ///
+    /// Some accounts need these to look up properties.
+    pub fn with_group(
+        mut self,
+        contract_id: impl Into<String>,
+        group_id: impl Into<String>,
+    ) -> Self {
+        self.group = Some((contract_id.into(), group_id.into()));
+        self
+    }
+
+    /// Read and write rule trees in a frozen rule format, e.g. `v2023-01-05`
+    ///
+    /// Without one, rule trees use the format the version was saved with.
+    pub fn with_rule_format(mut self, rule_format: impl Into<String>) -> Self {
+        self.rule_format = Some(rule_format.into());
+        self
+    }
+
+    /// Contracts the credential can access
+    pub async fn contracts(&self) -> Result<Vec<Contract>> {
+        let response: ContractsResponse = self.get("/papi/v1/contracts").await?;
+        Ok(response.contracts.items)
+    }
+
+    /// Groups the credential can access
+    pub async fn groups(&self) -> Result<Vec<Group>> {
+        let response: GroupsResponse = self.get("/papi/v1/groups").await?;
+        Ok(response.groups.items)
+    }
+
+    /// Properties in a contract and group
+    pub async fn properties(&self, contract_id: &str, group_id: &str) -> Result<Vec<Property>> {
+        let response = self
+            .client
+            .get("/papi/v1/properties")
+            .header(USE_PREFIXES, "true")
+            .query("contractId", contract_id)
+            .query("groupId", group_id)
+            .send()
+            .await?;
+        let response: PropertiesResponse = read_json(response).await?;
+        Ok(response.properties.items)
+    }
+
+    /// A single property
+    pub async fn property(&self, property_id: &str) -> Result<Property> {
+        let response: PropertiesResponse = self
+            .get(&format!("/papi/v1/properties/{}", property_id))
+            .await?;
+        response.properties.into_first("property")
+    }
+
+    /// All versions of a property, newest first
+    pub async fn versions(&self, property_id: &str) -> Result<Vec<PropertyVersion>> {
+        let response: VersionsResponse = self
+            .get(&format!("/papi/v1/properties/{}/versions", property_id))
+            .await?;
+        Ok(response.versions.items)
+    }
+
+    /// A single property version
+    pub async fn version(&self, property_id: &str, version: u32) -> Result<PropertyVersion> {
+        let response: VersionsResponse = self
+            .get(&format!(
+                "/papi/v1/properties/{}/versions/{}",
+                property_id, version
+            ))
+            .await?;
+        response.versions.into_first("version")
+    }
+
+    /// Create a new version from `from_version`, returning its number
+    ///
+    /// `etag` is the source version's [`PropertyVersion::etag`]; the request
+    /// fails if the source version was edited after it was read.
+    pub async fn create_version_from_etag(
+        &self,
+        property_id: &str,
+        from_version: u32,
+        etag: &str,
+    ) -> Result<u32> {
+        let body = serde_json::json!({
+            "createFromVersion": from_version,
+            "createFromVersionEtag": etag,
+        });
+        let response = self
+            .request(
+                Method::POST,
+                &format!("/papi/v1/properties/{}/versions", property_id),
+            )
+            .json(&body)
+            .send()
+            .await?;
+        let link: VersionLink = read_json(response).await?;
+
+        link_id(&link.version_link)
+            .and_then(|version| version.parse().ok())
+            .ok_or_else(|| {
+                EdgeGridError::Config(format!("unexpected versionLink '{}'", link.version_link))
+            })
+    }
+
+    /// Hostnames of a property version
+    pub async fn hostnames(&self, property_id: &str, version: u32) -> Result<Vec<Hostname>> {
+        let response: HostnamesResponse = self
+            .get(&format!(
+                "/papi/v1/properties/{}/versions/{}/hostnames",
+                property_id, version
+            ))
+            .await?;
+        Ok(response.hostnames.items)
+    }
+
+    /// Replace the hostnames of a property version
+    pub async fn update_hostnames(
+        &self,
+        property_id: &str,
+        version: u32,
+        hostnames: &[Hostname],
+    ) -> Result<Vec<Hostname>> {
+        let response = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "/papi/v1/properties/{}/versions/{}/hostnames",
+                    property_id, version
+                ),
+            )
+            .json(hostnames)
+            .send()
+            .await?;
+        let response: HostnamesResponse = read_json(response).await?;
+        Ok(response.hostnames.items)
+    }
+
+    /// The rule tree of a property version
+    pub async fn rules(&self, property_id: &str, version: u32) -> Result<RuleTree> {
+        let mut request = self.request(Method::GET, &rules_path(property_id, version));
+        if let Some(media_type) = self.rules_media_type() {
+            request = request.header(ACCEPT.as_str(), media_type);
+        }
+        read_json(request.send().await?).await
+    }
+
+    /// Replace the rule tree of a property version
+    ///
+    /// `tree.etag` is sent as `If-Match`, so the update fails if the rules
+    /// changed since `tree` was read. An empty etag sends no `If-Match`.
+    pub async fn update_rules(
+        &self,
+        property_id: &str,
+        version: u32,
+        tree: &RuleTree,
+    ) -> Result<RuleTree> {
+        let media_type = self
+            .rules_media_type()
+            .unwrap_or_else(|| "application/json".to_string());
+        let body = serde_json::to_vec(&serde_json::json!({ "rules": tree.rules }))?;
+
+        let mut request = self
+            .request(Method::PUT, &rules_path(property_id, version))
+            .header(CONTENT_TYPE.as_str(), media_type)
+            .body(body);
+        if let Some(etag) = quote_etag(&tree.etag) {
+            request = request.header(IF_MATCH.as_str(), etag);
+        }
+        read_json(request.send().await?).await
+    }
+
+    /// Apply JSON Patch operations to the rule tree of a property version
+    ///
+    /// Paths are relative to the rule tree document, e.g.
+    /// `/rules/children/0/behaviors/1`.
+    pub async fn patch_rules(
+        &self,
+        property_id: &str,
+        version: u32,
+        etag: &str,
+        operations: &[PatchOperation],
+    ) -> Result<RuleTree> {
+        let mut request = self
+            .request(Method::PATCH, &rules_path(property_id, version))
+            .header(CONTENT_TYPE.as_str(), JSON_PATCH)
+            .body(serde_json::to_vec(operations)?);
+        if let Some(etag) = quote_etag(etag) {
+            request = request.header(IF_MATCH.as_str(), etag);
+        }
+        if let Some(media_type) = self.rules_media_type() {
+            request = request.header(ACCEPT.as_str(), media_type);
+        }
+        read_json(request.send().await?).await
+    }
+
+    /// Activate a property version, returning the activation ID
+    pub async fn activate(
+        &self,
+        property_id: &str,
+        activation: &ActivationRequest,
+    ) -> Result<String> {
+        let response = self
+            .request(
+                Method::POST,
+                &format!("/papi/v1/properties/{}/activations", property_id),
+            )
+            .json(activation)
+            .send()
+            .await?;
+        let link: ActivationLink = read_json(response).await?;
+
+        link_id(&link.activation_link).ok_or_else(|| {
+            EdgeGridError::Config(format!(
+                "unexpected activationLink '{}'",
+                link.activation_link
+            ))
+        })
+    }
+
+    /// Activations of a property, newest first
+    pub async fn activations(&self, property_id: &str) -> Result<Vec<Activation>> {
+        let response: ActivationsResponse = self
+            .get(&format!("/papi/v1/properties/{}/activations", property_id))
+            .await?;
+        Ok(response.activations.items)
+    }
+
+    /// A single activation
+    pub async fn activation(&self, property_id: &str, activation_id: &str) -> Result<Activation> {
+        let response: ActivationsResponse = self
+            .get(&format!(
+                "/papi/v1/properties/{}/activations/{}",
+                property_id, activation_id
+            ))
+            .await?;
+        response.activations.into_first("activation")
+    }
+
+    /// Poll an activation until it is `ACTIVE`, or `DEACTIVATED` for a deactivation
+    pub fn activation_operation(
+        &self,
+        property_id: &str,
+        activation_id: &str,
+    ) -> Operation<Activation> {
+        let mut operation = self
+            .client
+            .operation(&format!(
+                "/papi/v1/properties/{}/activations/{}",
+                property_id, activation_id
+            ))
+            .header(USE_PREFIXES, "true");
+        if let Some((contract_id, group_id)) = &self.group {
+            operation = operation
+                .query("contractId", contract_id)
+                .query("groupId", group_id);
+        }
+        operation
+    }
+
+    fn request(&self, method: Method, path: &str) -> EdgeGridRequestBuilder {
+        let mut request = self
+            .client
+            .request(method, path)
+            .header(USE_PREFIXES, "true");
+        if let Some((contract_id, group_id)) = &self.group {
+            request = request
+                .query("contractId", contract_id)
+                .query("groupId", group_id);
+        }
+        request
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        read_json(self.request(Method::GET, path).send().await?).await
+    }
+
+    fn rules_media_type(&self) -> Option<String> {
+        self.rule_format
+            .as_ref()
+            .map(|format| format!("application/vnd.akamai.papirules.{}+json", format))
+    }
+}
+
+fn rules_path(property_id: &str, version: u32) -> String {
+    format!(
+        "/papi/v1/properties/{}/versions/{}/rules",
+        property_id, version
+    )
+}
+
+/// Quoted `If-Match` value for a bare PAPI etag, or `None` if there is none
+fn quote_etag(etag: &str) -> Option<String> {
+    let etag = etag.trim();
+    if etag.is_empty() || etag == "\"\"" {
+        None
+    } else if etag.starts_with('"') {
+        Some(etag.to_string())
+    } else {
+        Some(format!("\"{}\"", etag))
+    }
+}
+
+/// Last path segment of a link such as `/papi/v1/properties/prp_1/versions/2?contractId=ctr_1`
+fn link_id(link: &str) -> Option<String> {
+    let path = link.split('?').next()?;
+    path.rsplit('/')
+        .next()
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_id() {
+        assert_eq!(
+            link_id("/papi/v1/properties/prp_1/versions/2?contractId=ctr_1&groupId=grp_1"),
+            Some("2".to_string())
+        );
+        assert_eq!(
+            link_id("/papi/v1/properties/prp_1/activations/atv_67037"),
+            Some("atv_67037".to_string())
+        );
+        assert_eq!(link_id("/papi/v1/properties/"), None);
+    }
+
+    #[test]
+    fn test_quote_etag() {
+        assert_eq!(quote_etag("abc").as_deref(), Some("\"abc\""));
+        assert_eq!(quote_etag("\"abc\"").as_deref(), Some("\"abc\""));
+        assert_eq!(quote_etag(""), None);
+        assert_eq!(quote_etag("\"\""), None);
+    }
+
+    #[test]
+    fn test_rule_tree_keeps_unknown_members() {
+        let json = serde_json::json!({
+            "name": "default",
+            "variables": [{"name": "PMUSER_X", "value": "", "hidden": false, "sensitive": false}],
+            "behaviors": [{"name": "origin", "options": {"hostname": "origin.example.com"}, "uuid": "abc"}],
+            "children": [{"name": "Static", "criteria": [{"name": "fileExtension", "options": {"values": ["css"]}}]}],
+            "options": {"is_secure": true}
+        });
+
+        let rule: Rule = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(rule.behaviors[0].options["hostname"], "origin.example.com");
+        assert_eq!(rule.children[0].criteria[0].name, "fileExtension");
+        assert!(rule.extra.contains_key("variables"));
+
+        let round_trip = serde_json::to_value(&rule).unwrap();
+        assert_eq!(round_trip["variables"], json["variables"]);
+        assert_eq!(round_trip["behaviors"][0]["uuid"], "abc");
+    }
+
+    #[test]
+    fn test_patch_operations_serialize_as_json_patch() {
+        let ops = vec![
+            PatchOperation::replace("/rules/behaviors/0/options/hostname", "new.example.com"),
+            PatchOperation::remove("/rules/children/1"),
+        ];
+        assert_eq!(
+            serde_json::to_value(&ops).unwrap(),
+            serde_json::json!([
+                {"op": "replace", "path": "/rules/behaviors/0/options/hostname", "value": "new.example.com"},
+                {"op": "remove", "path": "/rules/children/1"}
+            ])
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Credential secrets and providers for resolving `client_secret`
+//!
+//! Credentials are held in [`Secret`], which zeroizes its contents on drop and
+//! redacts them in `Debug` and `Display` output.
+//!
+//! Instead of embedding `client_secret` in plaintext, an `.edgerc` section can
+//! reference where the secret lives:
+//!
+//! ```ini
+//! [default]
+//! host = akab-xxxxxxxxxxxxxxxx-xxxxxxxxxxxxxxxx.luna.akamaiapis.net
+//! access_token = akab-xxxxxxxxxxxxxxxx-xxxxxxxxxxxxxxxx
+//! client_token = akab-xxxxxxxxxxxxxxxx-xxxxxxxxxxxxxxxx
+//! ; one of:
+//! client_secret_file = ~/.akamai/default.secret
+//! client_secret_command = pass show akamai/default
+//! client_secret_keyring = akamai-edgegrid:default
+//! ```
+//!
+//! The same sources can be used from environment variables
+//! (`AKAMAI_CLIENT_SECRET_FILE`, `AKAMAI_CLIENT_SECRET_COMMAND`) or
+//! programmatically through [`EdgeGridConfig::with_secret_provider`].
+//!
+//! [`EdgeGridConfig::with_secret_provider`]: crate::EdgeGridConfig::with_secret_provider
+
+use crate::config::resolve_home_path;
+use crate::edgerc::write_private;
+use crate::error::{EdgeGridError, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Placeholder printed in place of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// `.edgerc` key referencing a file that contains the client secret
+pub const CLIENT_SECRET_FILE_KEY: &str = "client_secret_file";
+/// `.edgerc` key holding a command whose output is the client secret
+pub const CLIENT_SECRET_COMMAND_KEY: &str = "client_secret_command";
+/// `.edgerc` key referencing an OS keyring entry as `service:account`
+pub const CLIENT_SECRET_KEYRING_KEY: &str = "client_secret_keyring";
+
+/// A credential value that is zeroized on drop and never printed
+///
+/// `Secret` deliberately does not implement `Serialize`; use
+/// [`serialize_exposed`] to opt a field in to writing the real value.
+///
+/// Deserializing the [`REDACTED`] placeholder is an error, so that a
+/// redacted config cannot be loaded back and used to sign requests.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap a secret value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Access the underlying value
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the secret is empty or only whitespace
+    pub fn is_blank(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Secret {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = Secret(String::deserialize(deserializer)?);
+        if value.0 == REDACTED {
+            return Err(serde::de::Error::custom(
+                "credential is redacted; serialize with EdgeGridConfig::exposed to keep it",
+            ));
+        }
+        Ok(value)
+    }
+}
+
+impl PartialEq<str> for Secret {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Secret {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Serialize a [`Secret`] as a redacted placeholder
+pub fn serialize_redacted<S: serde::Serializer>(
+    _secret: &Secret,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+/// Serialize the real value of a [`Secret`]
+///
+/// Use with `#[serde(serialize_with = "akamai_edgegrid::secret::serialize_exposed")]`.
+pub fn serialize_exposed<S: serde::Serializer>(
+    secret: &Secret,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(secret.expose())
+}
+
+/// A source the client secret can be resolved from
+pub trait SecretProvider: fmt::Debug + Send + Sync {
+    /// Resolve the secret
+    fn secret(&self) -> Result<Secret>;
+
+    /// Whether [`store`](SecretProvider::store) can replace the secret
+    fn can_store(&self) -> bool {
+        false
+    }
+
+    /// Replace the secret, e.g. after rotating a credential
+    ///
+    /// Read-only providers such as [`CommandSecretProvider`] return an error.
+    fn store(&self, _secret: &Secret) -> Result<()> {
+        Err(EdgeGridError::Config(
+            "This secret provider cannot store a client secret".to_string(),
+        ))
+    }
+}
+
+/// Reads the secret from a file, ignoring surrounding whitespace
+#[derive(Debug, Clone)]
+pub struct FileSecretProvider {
+    path: PathBuf,
+}
+
+impl FileSecretProvider {
+    /// Create a provider reading from `path` (`~` is expanded)
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    fn secret(&self) -> Result<Secret> {
+        let path = resolve_home_path(&self.path)?;
+        let content = Zeroizing::new(fs::read_to_string(&path).map_err(|e| {
+            EdgeGridError::Config(format!(
+                "Cannot read client secret file {}: {}",
+                path.display(),
+                e
+            ))
+        })?);
+        Ok(trimmed_secret(content))
+    }
+
+    fn can_store(&self) -> bool {
+        true
+    }
+
+    /// Write the secret to the file, readable and writable only by its owner on Unix
+    fn store(&self, secret: &Secret) -> Result<()> {
+        let path = resolve_home_path(&self.path)?;
+        let content = Zeroizing::new(format!("{}\n", secret.expose()));
+        write_private(&path, content.as_bytes()).map_err(|e| {
+            EdgeGridError::Config(format!(
+                "Cannot write client secret file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Runs a shell command and uses its standard output as the secret
+#[derive(Debug, Clone)]
+pub struct CommandSecretProvider {
+    command: String,
+}
+
+impl CommandSecretProvider {
+    /// Create a provider running `command` through the platform shell
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+}
+
+impl SecretProvider for CommandSecretProvider {
+    fn secret(&self) -> Result<Secret> {
+        let output = if cfg!(windows) {
+            Command::new("cmd").args(["/C", &self.command]).output()
+        } else {
+            Command::new("sh").args(["-c", &self.command]).output()
+        }
+        .map_err(|e| {
+            EdgeGridError::Config(format!(
+                "Cannot run client secret command '{}': {}",
+                self.command, e
+            ))
+        })?;
+
+        if !output.status.success() {
+            return Err(EdgeGridError::Config(format!(
+                "Client secret command '{}' failed ({}): {}",
+                self.command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let content = String::from_utf8(output.stdout).map_err(|e| {
+            e.into_bytes().zeroize();
+            EdgeGridError::Config(format!(
+                "Client secret command '{}' did not print valid UTF-8",
+                self.command
+            ))
+        })?;
+        Ok(trimmed_secret(Zeroizing::new(content)))
+    }
+}
+
+/// Trim surrounding whitespace from a buffer holding a secret, in place
+///
+/// The buffer itself becomes the returned [`Secret`], so no untrimmed copy
+/// is left behind; bytes shifted out by the trim stay in its spare capacity,
+/// which is zeroized along with the rest on drop.
+fn trimmed_secret(mut buffer: Zeroizing<String>) -> Secret {
+    let end = buffer.trim_end().len();
+    buffer.truncate(end);
+    let start = buffer.len() - buffer.trim_start().len();
+    buffer.drain(..start);
+    Secret::new(std::mem::take(&mut *buffer))
+}
+
+/// Reads the secret from the OS keyring (macOS Keychain, Windows Credential
+/// Manager or the Linux kernel keyring)
+#[cfg(feature = "keyring")]
+#[derive(Debug, Clone)]
+pub struct KeyringSecretProvider {
+    service: String,
+    account: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringSecretProvider {
+    /// Create a provider for the keyring entry identified by `service` and `account`
+    pub fn new(service: impl Into<String>, account: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            account: account.into(),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretProvider for KeyringSecretProvider {
+    fn secret(&self) -> Result<Secret> {
+        keyring::Entry::new(&self.service, &self.account)
+            .and_then(|entry| entry.get_password())
+            .map(Secret::from)
+            .map_err(|e| {
+                EdgeGridError::Config(format!(
+                    "Cannot read client secret from keyring {}:{}: {}",
+                    self.service, self.account, e
+                ))
+            })
+    }
+
+    fn can_store(&self) -> bool {
+        true
+    }
+
+    fn store(&self, secret: &Secret) -> Result<()> {
+        keyring::Entry::new(&self.service, &self.account)
+            .and_then(|entry| entry.set_password(secret.expose()))
+            .map_err(|e| {
+                EdgeGridError::Config(format!(
+                    "Cannot store client secret in keyring {}:{}: {}",
+                    self.service, self.account, e
+                ))
+            })
+    }
+}
+
+/// Pick the provider referenced by a set of `.edgerc` style key-value pairs
+///
+/// Keyring references are written as `service:account`; when the account is
+/// omitted the client token is used.
+pub(crate) fn provider_from_values(
+    values: &HashMap<String, String>,
+) -> Result<Option<Box<dyn SecretProvider>>> {
+    if let Some(path) = values.get(CLIENT_SECRET_FILE_KEY) {
+        return Ok(Some(Box::new(FileSecretProvider::new(path))));
+    }
+
+    if let Some(command) = values.get(CLIENT_SECRET_COMMAND_KEY) {
+        return Ok(Some(Box::new(CommandSecretProvider::new(command.as_str()))));
+    }
+
+    if let Some(reference) = values.get(CLIENT_SECRET_KEYRING_KEY) {
+        #[cfg(feature = "keyring")]
+        {
+            let (service, account) = match reference.split_once(':') {
+                Some((service, account)) => (service.to_string(), account.to_string()),
+                None => (
+                    reference.clone(),
+                    values.get("client_token").cloned().unwrap_or_default(),
+                ),
+            };
+            return Ok(Some(Box::new(KeyringSecretProvider::new(service, account))));
+        }
+
+        #[cfg(not(feature = "keyring"))]
+        return Err(EdgeGridError::Config(format!(
+            "{} = {} requires the `keyring` feature",
+            CLIENT_SECRET_KEYRING_KEY, reference
+        )));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::new("hunter2");
+        assert_eq!(secret.expose(), "hunter2");
+        assert!(!format!("{:?}", secret).contains("hunter2"));
+        assert_eq!(secret.to_string(), REDACTED);
+    }
+
+    #[test]
+    fn test_file_secret_provider() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "  file-secret  ").unwrap();
+
+        let provider = FileSecretProvider::new(file.path());
+        assert_eq!(provider.secret().unwrap().expose(), "file-secret");
+
+        assert!(provider.can_store());
+        provider.store(&Secret::new("rotated-secret")).unwrap();
+        assert_eq!(provider.secret().unwrap().expose(), "rotated-secret");
+    }
+
+    #[test]
+    fn test_trimmed_secret_reuses_buffer() {
+        let buffer = Zeroizing::new(" \tsecret value\n".to_string());
+        let ptr = buffer.as_ptr();
+
+        let secret = trimmed_secret(buffer);
+        assert_eq!(secret.expose(), "secret value");
+        assert_eq!(secret.expose().as_ptr(), ptr);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_secret_provider() {
+        let provider = CommandSecretProvider::new("echo command-secret");
+        assert_eq!(provider.secret().unwrap().expose(), "command-secret");
+
+        let failing = CommandSecretProvider::new("echo oops >&2; exit 3");
+        let err = failing.secret().unwrap_err().to_string();
+        assert!(err.contains("oops"), "{}", err);
+
+        assert!(!provider.can_store());
+        assert!(provider.store(&Secret::new("rotated-secret")).is_err());
+    }
+
+    #[test]
+    fn test_provider_from_values() {
+        let mut values = HashMap::new();
+        assert!(provider_from_values(&values).unwrap().is_none());
+
+        values.insert(
+            CLIENT_SECRET_FILE_KEY.to_string(),
+            "/does/not/exist".to_string(),
+        );
+        let provider = provider_from_values(&values).unwrap().unwrap();
+        assert!(provider.secret().is_err());
+    }
+}
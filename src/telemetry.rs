@@ -0,0 +1,234 @@
+//! Helpers shared by request tracing and metrics
+//!
+//! Paths are reduced to templates so that spans and metrics can be grouped
+//! per endpoint, and anything written to logs has its credentials redacted.
+
+use crate::config::EdgeGridConfig;
+use crate::secret::REDACTED;
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+
+/// Response headers Akamai uses to identify a request, in order of preference
+pub const REQUEST_ID_HEADERS: &[&str] = &["x-akamai-request-id", "akamai-request-id", "x-trace-id"];
+
+/// Collections whose members are addressed by name as well as by ID, such as
+/// DNS zones or EdgeKV items; the segment after one of them is a parameter
+const COLLECTIONS: &[&str] = &[
+    "activations",
+    "api-clients",
+    "changelists",
+    "changes",
+    "configs",
+    "create-requests",
+    "credentials",
+    "custom-rules",
+    "enrollments",
+    "groups",
+    "ids",
+    "items",
+    "match-targets",
+    "names",
+    "namespaces",
+    "network-lists",
+    "properties",
+    "rate-policies",
+    "roles",
+    "security-policies",
+    "tokens",
+    "ui-identities",
+    "users",
+    "versions",
+    "zones",
+];
+
+/// Reduce a request path to a template by replacing identifiers with `{id}`
+///
+/// `/papi/v1/properties/prp_123/versions/4` becomes
+/// `/papi/v1/properties/{id}/versions/{id}`, and names such as the zone in
+/// `/config-dns/v2/zones/example.com` are replaced as well, so that the number
+/// of distinct templates stays bounded. Query strings are dropped.
+pub fn path_template(path: &str) -> String {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let mut previous = "";
+    path.split('/')
+        .map(|segment| {
+            let member = COLLECTIONS.contains(&previous)
+                && !segment.is_empty()
+                && !COLLECTIONS.contains(&segment);
+            previous = segment;
+            if member || is_identifier(segment) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn is_identifier(segment: &str) -> bool {
+    if segment.is_empty() {
+        return false;
+    }
+
+    // Plain numbers: versions, CP codes, enrollment IDs
+    if segment.chars().all(|c| c.is_ascii_digit()) {
+        return true;
+    }
+
+    // Prefixed IDs such as prp_123, ctr_C-1ABCDE, grp_42, atv_1234
+    if let Some((prefix, rest)) = segment.split_once('_') {
+        if (2..=4).contains(&prefix.len())
+            && prefix.chars().all(|c| c.is_ascii_lowercase())
+            && rest.chars().any(|c| c.is_ascii_digit())
+        {
+            return true;
+        }
+    }
+
+    // UUIDs and long hex strings
+    let hex = segment.chars().filter(|c| *c != '-').count();
+    hex >= 16 && segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// First Akamai request identifier found in the response headers
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Replace the tokens in a string-to-sign or `Authorization` value
+pub(crate) fn redact_credentials(text: &str, config: &EdgeGridConfig) -> String {
+    let mut redacted = text.to_string();
+    for secret in [
+        &config.client_token,
+        &config.access_token,
+        &config.client_secret,
+    ] {
+        if !secret.is_blank() {
+            redacted = redacted.replace(secret.expose(), REDACTED);
+        }
+    }
+
+    match redacted.find("signature=") {
+        Some(pos) => format!("{}signature={}", &redacted[..pos], REDACTED),
+        None => redacted,
+    }
+}
+
+/// Render headers for debug logging with the `Authorization` value redacted
+pub(crate) fn redact_headers(headers: &HeaderMap, config: &EdgeGridConfig) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or("<binary>");
+            if name == AUTHORIZATION {
+                format!("{}: {}", name, redact_credentials(value, config))
+            } else {
+                format!("{}: {}", name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replaces credentials, the API host and user-supplied values in recorded text
+pub(crate) struct Scrubber {
+    replacements: Vec<(String, String)>,
+}
+
+impl Scrubber {
+    pub(crate) fn new(config: &EdgeGridConfig, extra: &[(String, String)]) -> Self {
+        let host = config
+            .host
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/');
+
+        let mut replacements: Vec<(String, String)> = [
+            config.client_token.expose(),
+            config.client_secret.expose(),
+            config.access_token.expose(),
+            host,
+        ]
+        .into_iter()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| (value.to_string(), REDACTED.to_string()))
+        .collect();
+        replacements.extend(extra.iter().cloned());
+
+        Self { replacements }
+    }
+
+    pub(crate) fn apply(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (value, replacement)| {
+                text.replace(value.as_str(), replacement)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_template() {
+        assert_eq!(
+            path_template("/papi/v1/properties/prp_123/versions/4/rules?contractId=ctr_1"),
+            "/papi/v1/properties/{id}/versions/{id}/rules"
+        );
+        assert_eq!(
+            path_template("/edgekv/v1/networks/staging/namespaces/default"),
+            "/edgekv/v1/networks/staging/namespaces/{id}"
+        );
+        assert_eq!(
+            path_template("/identity-management/v3/users/550e8400-e29b-41d4-a716-446655440000"),
+            "/identity-management/v3/users/{id}"
+        );
+    }
+
+    #[test]
+    fn test_path_template_replaces_names() {
+        assert_eq!(
+            path_template("/config-dns/v2/zones/example.com/names/www.example.com/types/A"),
+            "/config-dns/v2/zones/{id}/names/{id}/types/A"
+        );
+        assert_eq!(
+            path_template("/config-dns/v2/zones/create-requests/abc/result"),
+            "/config-dns/v2/zones/create-requests/{id}/result"
+        );
+        assert_eq!(
+            path_template("/edgekv/v1/networks/production/namespaces/ns/groups/g/items/my%20key"),
+            "/edgekv/v1/networks/production/namespaces/{id}/groups/{id}/items/{id}"
+        );
+        assert_eq!(
+            path_template("/network-list/v2/network-lists/1234_BLOCKLIST/append"),
+            "/network-list/v2/network-lists/{id}/append"
+        );
+        assert_eq!(
+            path_template("/appsec/v1/configs/1/versions/2/security-policies/abc1_123456"),
+            "/appsec/v1/configs/{id}/versions/{id}/security-policies/{id}"
+        );
+    }
+
+    #[test]
+    fn test_redact_credentials() {
+        let config = EdgeGridConfig::new(
+            "akab-client",
+            "secret",
+            "akab-access",
+            "host.luna.akamaiapis.net".to_string(),
+        );
+        let header = "EG1-HMAC-SHA256 client_token=akab-client;access_token=akab-access;timestamp=20240101T00:00:00+0000;nonce=n;signature=abc=";
+
+        assert_eq!(
+            redact_credentials(header, &config),
+            "EG1-HMAC-SHA256 client_token=[REDACTED];access_token=[REDACTED];timestamp=20240101T00:00:00+0000;nonce=n;signature=[REDACTED]"
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! In-process fake Akamai API server for end-to-end tests
+//!
+//! [`FakeAkamai`] listens on a local port, checks the EdgeGrid signature of
+//! every request with the same algorithm [`EdgeGridAuth`] signs with, and
+//! answers from route handlers registered by the test. Requests with a
+//! missing or wrong signature are rejected with `401` the way the real API
+//! rejects them, unmatched routes get a `404`, and every request is recorded
+//! for later assertions.
+//!
+//! ```rust,no_run
+//! use akamai_edgegrid::testing::{FakeAkamai, FakeResponse};
+//! use reqwest::Method;
+//!
+//! # async fn example() -> akamai_edgegrid::Result<()> {
+//! let server = FakeAkamai::start().await?;
+//! server.route(Method::GET, "/papi/v1/groups/{groupId}", |_| {
+//!     FakeResponse::json(200, serde_json::json!({"groups": {"items": []}}))
+//! });
+//! server.problem(Method::DELETE, "/papi/v1/properties/{propertyId}", 403, "Forbidden", "Not allowed");
+//!
+//! let client = server.client()?;
+//! client.get("/papi/v1/groups/grp_1").send().await?;
+//!
+//! let received = server.requests();
+//! assert!(received[0].authenticated);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::auth::EdgeGridAuth;
+use crate::client::EdgeGridClient;
+use crate::config::EdgeGridConfig;
+use crate::error::{EdgeGridError, Result};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use url::Url;
+
+/// Credentials accepted by [`FakeAkamai::start`]
+pub const TEST_CLIENT_TOKEN: &str = "akab-test00000000000-client0000000000";
+/// Secret accepted by [`FakeAkamai::start`]
+pub const TEST_CLIENT_SECRET: &str = "dGVzdC1zZWNyZXQtZm9yLWZha2UtYWthbWFpLXNydg==";
+/// Access token accepted by [`FakeAkamai::start`]
+pub const TEST_ACCESS_TOKEN: &str = "akab-test00000000000-access0000000000";
+
+type Handler = Arc<dyn Fn(&ReceivedRequest) -> FakeResponse + Send + Sync>;
+
+/// A request received by the fake server
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// HTTP method
+    pub method: Method,
+    /// URL path
+    pub path: String,
+    /// Raw query string
+    pub query: Option<String>,
+    /// Request headers
+    pub headers: HeaderMap,
+    /// Request body
+    pub body: Vec<u8>,
+    /// Whether the EdgeGrid signature was valid
+    pub authenticated: bool,
+}
+
+impl ReceivedRequest {
+    /// Value of a query parameter
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        let query = self.query.as_deref()?;
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    /// Deserialize the body as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// A response returned by a route handler
+#[derive(Debug, Clone)]
+pub struct FakeResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    pub headers: HeaderMap,
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl FakeResponse {
+    /// A response with no body
+    pub fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// A `text/plain` response
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self::empty(status)
+            .header(CONTENT_TYPE.as_str(), "text/plain")
+            .body(body.into().into_bytes())
+    }
+
+    /// An `application/json` response
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self::empty(status)
+            .header(CONTENT_TYPE.as_str(), "application/json")
+            .body(body.to_string().into_bytes())
+    }
+
+    /// An `application/problem+json` error in the format Akamai APIs use
+    pub fn problem(status: u16, title: &str, detail: &str) -> Self {
+        let problem_type = format!(
+            "https://problems.luna.akamaiapis.net/-/fake/{}",
+            title.to_ascii_lowercase().replace(' ', "-")
+        );
+        let body = serde_json::json!({
+            "type": problem_type,
+            "title": title,
+            "status": status,
+            "detail": detail,
+        });
+
+        Self::empty(status)
+            .header(CONTENT_TYPE.as_str(), "application/problem+json")
+            .body(body.to_string().into_bytes())
+    }
+
+    /// Add a header
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            self.headers.insert(name, value);
+        }
+        self
+    }
+
+    /// Replace the body
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+struct Route {
+    method: Method,
+    segments: Vec<String>,
+    handler: Handler,
+}
+
+impl Route {
+    /// Match the path segment by segment; `{name}` segments match anything
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let segments: Vec<&str> = path.split('/').collect();
+        self.method == method
+            && self.segments.len() == segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(segments)
+                .all(|(pattern, segment)| {
+                    (pattern.starts_with('{') && pattern.ends_with('}')) || pattern == segment
+                })
+    }
+}
+
+struct State {
+    config: EdgeGridConfig,
+    routes: Mutex<Vec<Route>>,
+    requests: Mutex<Vec<ReceivedRequest>>,
+}
+
+/// A local HTTP server that behaves like an EdgeGrid API host
+///
+/// The server shuts down when dropped.
+pub struct FakeAkamai {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeAkamai {
+    /// Start a server accepting the `TEST_*` credentials
+    pub async fn start() -> Result<Self> {
+        Self::start_with(EdgeGridConfig::new(
+            TEST_CLIENT_TOKEN,
+            TEST_CLIENT_SECRET,
+            TEST_ACCESS_TOKEN,
+            "http://127.0.0.1".to_string(),
+        ))
+        .await
+    }
+
+    /// Start a server accepting the tokens and secret of `config`
+    ///
+    /// The host of `config` is ignored; use [`config`](Self::config) for a
+    /// configuration pointing at the server.
+    pub async fn start_with(config: EdgeGridConfig) -> Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(State {
+            config,
+            routes: Mutex::new(Vec::new()),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(handle(&state, request).await) }
+                }))
+            }
+        });
+
+        let (shutdown, stop) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)
+            .map_err(|e| EdgeGridError::Config(format!("fake server: {}", e)))?
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                stop.await.ok();
+            });
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                tracing::warn!("Fake Akamai server failed: {}", e);
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Base URL of the server
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Credentials accepted by the server, with the host set to its URL
+    pub fn config(&self) -> EdgeGridConfig {
+        let mut config = self.state.config.clone();
+        config.host = self.url();
+        config
+    }
+
+    /// A client signing with the accepted credentials
+    pub fn client(&self) -> Result<EdgeGridClient> {
+        EdgeGridClient::new(self.config())
+    }
+
+    /// Answer `method` requests for `path` with `handler`
+    ///
+    /// Path segments written as `{name}` match any value. Routes registered
+    /// later take precedence over earlier ones.
+    pub fn route<F>(&self, method: Method, path: &str, handler: F)
+    where
+        F: Fn(&ReceivedRequest) -> FakeResponse + Send + Sync + 'static,
+    {
+        let route = Route {
+            method,
+            segments: path.split('/').map(str::to_string).collect(),
+            handler: Arc::new(handler),
+        };
+        lock(&self.state.routes).push(route);
+    }
+
+    /// Answer `method` requests for `path` with a fixed JSON body
+    pub fn json(&self, method: Method, path: &str, status: u16, body: serde_json::Value) {
+        self.route(method, path, move |_| {
+            FakeResponse::json(status, body.clone())
+        });
+    }
+
+    /// Answer `method` requests for `path` with a `problem+json` error
+    pub fn problem(&self, method: Method, path: &str, status: u16, title: &str, detail: &str) {
+        let response = FakeResponse::problem(status, title, detail);
+        self.route(method, path, move |_| response.clone());
+    }
+
+    /// Every request received so far, in order
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        lock(&self.state.requests).clone()
+    }
+
+    /// Forget the recorded requests
+    pub fn clear_requests(&self) {
+        lock(&self.state.requests).clear();
+    }
+}
+
+impl Drop for FakeAkamai {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl std::fmt::Debug for FakeAkamai {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FakeAkamai")
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+async fn handle(state: &State, request: hyper::Request<Body>) -> hyper::Response<Body> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map(|bytes| bytes.to_vec())
+        .unwrap_or_default();
+
+    let host = parts
+        .headers
+        .get(hyper::header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("127.0.0.1");
+    let verified = verify_signature(&state.config, &parts, host, &body);
+
+    let received = ReceivedRequest {
+        method: parts.method.clone(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().map(str::to_string),
+        headers: parts.headers.clone(),
+        body,
+        authenticated: verified.is_ok(),
+    };
+    lock(&state.requests).push(received.clone());
+
+    let response = match verified {
+        Err(detail) => FakeResponse::problem(401, "Not authorized", &detail),
+        Ok(()) => {
+            let handler = lock(&state.routes)
+                .iter()
+                .rev()
+                .find(|route| route.matches(&received.method, &received.path))
+                .map(|route| route.handler.clone());
+            match handler {
+                Some(handler) => handler(&received),
+                None => FakeResponse::problem(
+                    404,
+                    "Not Found",
+                    &format!("No route for {} {}", received.method, received.path),
+                ),
+            }
+        }
+    };
+
+    let mut builder = hyper::Response::builder()
+        .status(StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+    if let Some(headers) = builder.headers_mut() {
+        headers.extend(response.headers);
+    }
+    builder
+        .body(Body::from(response.body))
+        .unwrap_or_else(|_| hyper::Response::new(Body::empty()))
+}
+
+/// Check the `Authorization` header, describing the first problem found
+fn verify_signature(
+    config: &EdgeGridConfig,
+    parts: &hyper::http::request::Parts,
+    host: &str,
+    body: &[u8],
+) -> std::result::Result<(), String> {
+    let header = parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Missing Authorization header")?;
+    let fields = header
+        .strip_prefix("EG1-HMAC-SHA256 ")
+        .ok_or("Authorization header is not EG1-HMAC-SHA256")?;
+
+    let field = |name: &str| {
+        fields
+            .split(';')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+            .ok_or(format!("Authorization header is missing {}", name))
+    };
+
+    if field("client_token")? != config.client_token.expose() {
+        return Err("Invalid client_token".to_string());
+    }
+    if field("access_token")? != config.access_token.expose() {
+        return Err("Invalid access_token".to_string());
+    }
+
+    let url = Url::parse(&format!("http://{}{}", host, parts.uri))
+        .map_err(|e| format!("Invalid request URL: {}", e))?;
+    let auth = EdgeGridAuth::new(config.clone());
+    let expected = |body: Option<&[u8]>| {
+        auth.expected_signature(
+            config,
+            &parts.method,
+            &url,
+            body,
+            field("timestamp")?,
+            field("nonce")?,
+        )
+        .map_err(|e| e.to_string())
+    };
+
+    // An empty body on the wire may have been signed either as an explicitly
+    // empty body or as no body at all
+    let signature = field("signature")?;
+    if signature == expected(Some(body))? || (body.is_empty() && signature == expected(None)?) {
+        Ok(())
+    } else {
+        Err("The signature does not match".to_string())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_signed_requests_are_routed_and_recorded() {
+        let server = FakeAkamai::start().await.unwrap();
+        server.route(Method::POST, "/ccu/v3/delete/url/{network}", |request| {
+            let body: serde_json::Value = request.json().unwrap();
+            FakeResponse::json(201, serde_json::json!({ "objects": body["objects"] }))
+        });
+
+        let client = server.client().unwrap();
+        let response: serde_json::Value = client
+            .post("/ccu/v3/delete/url/staging")
+            .query("force", "true")
+            .json(&serde_json::json!({ "objects": ["https://www.example.com/"] }))
+            .send_json()
+            .await
+            .unwrap();
+        assert_eq!(response["objects"][0], "https://www.example.com/");
+
+        let missing = client.get("/ccu/v3/unknown").send().await.unwrap();
+        assert_eq!(missing.status(), 404);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.authenticated));
+        assert_eq!(requests[0].query_param("force").as_deref(), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_credentials_are_rejected() {
+        let server = FakeAkamai::start().await.unwrap();
+        server.json(Method::GET, "/papi/v1/groups", 200, serde_json::json!({}));
+
+        let mut config = server.config();
+        config.client_secret = "d3Jvbmctc2VjcmV0".into();
+        let client = EdgeGridClient::new(config).unwrap();
+
+        let response = client.get("/papi/v1/groups").send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/problem+json");
+        assert!(!server.requests()[0].authenticated);
+    }
+}
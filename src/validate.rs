@@ -0,0 +1,352 @@
+//! Credential format validation
+//!
+//! [`EdgeGridConfig::lint`] inspects a configuration for the mistakes that
+//! otherwise only surface as an opaque `401` from the API: tokens pasted into
+//! the wrong field, a secret that is not base64, or a host with a scheme or
+//! path. Every problem is reported, each with a [`Severity`].
+
+use crate::config::{is_loopback_http, EdgeGridConfig, MAX_BODY};
+use crate::error::{EdgeGridError, Result};
+use crate::secret::Secret;
+use base64::alphabet::STANDARD;
+use base64::engine::{GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use std::fmt;
+
+/// Domain every EdgeGrid API host is expected to live under
+pub const API_HOST_SUFFIX: &str = ".luna.akamaiapis.net";
+
+/// Secrets are only checked for their base64 shape, so tolerate non-zero trailing bits
+const BASE64_SHAPE: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_allow_trailing_bits(true),
+);
+
+/// How serious a validation finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Unusual, but may be intentional
+    Warning,
+    /// Requests signed with this configuration will not be accepted
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A single problem found in a configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Configuration field the problem relates to
+    pub field: &'static str,
+    /// Human readable description; never contains secret values
+    pub message: String,
+}
+
+impl Finding {
+    fn error(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            field,
+            message: message.into(),
+        }
+    }
+
+    fn warning(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {}", self.severity, self.field, self.message)
+    }
+}
+
+impl EdgeGridConfig {
+    /// Check the format of every credential field and return all findings
+    pub fn lint(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        check_token(&mut findings, "client_token", &self.client_token);
+        check_token(&mut findings, "access_token", &self.access_token);
+        if !self.client_token.is_blank() && self.client_token == self.access_token {
+            findings.push(Finding::error(
+                "access_token",
+                "access_token is identical to client_token",
+            ));
+        }
+
+        check_secret(&mut findings, &self.client_secret);
+        check_host(&mut findings, &self.host);
+        check_max_body(&mut findings, self.max_body);
+
+        if let Some(key) = &self.account_switch_key {
+            if key.trim().is_empty() || key.chars().any(char::is_whitespace) {
+                findings.push(Finding::error(
+                    "account_switch_key",
+                    "account_switch_key must not be empty or contain whitespace",
+                ));
+            }
+        }
+
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+        findings
+    }
+
+    /// Fail with [`EdgeGridError::Validation`] if [`lint`](Self::lint) reports any errors
+    ///
+    /// Warnings are returned so they can still be shown to the user.
+    pub fn ensure_valid(&self) -> Result<Vec<Finding>> {
+        let findings = self.lint();
+        if findings.iter().any(|f| f.severity == Severity::Error) {
+            Err(EdgeGridError::Validation(findings))
+        } else {
+            Ok(findings)
+        }
+    }
+}
+
+fn check_token(findings: &mut Vec<Finding>, field: &'static str, token: &Secret) {
+    let token = token.expose();
+
+    if token.trim().is_empty() {
+        findings.push(Finding::error(field, format!("{} is empty", field)));
+        return;
+    }
+    if token.trim() != token || token.chars().any(char::is_whitespace) {
+        findings.push(Finding::error(
+            field,
+            format!("{} contains whitespace", field),
+        ));
+    }
+    if !token.starts_with("akab-") {
+        findings.push(Finding::error(
+            field,
+            format!("{} should start with 'akab-'", field),
+        ));
+        return;
+    }
+
+    let parts: Vec<&str> = token["akab-".len()..].split('-').collect();
+    let well_formed = parts.len() == 2
+        && parts
+            .iter()
+            .all(|part| part.len() == 16 && part.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !well_formed {
+        findings.push(Finding::warning(
+            field,
+            format!("{} does not look like akab-<16 chars>-<16 chars>", field),
+        ));
+    }
+}
+
+fn check_secret(findings: &mut Vec<Finding>, secret: &Secret) {
+    let secret = secret.expose();
+
+    if secret.trim().is_empty() {
+        findings.push(Finding::error("client_secret", "client_secret is empty"));
+        return;
+    }
+    if secret.starts_with("akab-") {
+        findings.push(Finding::error(
+            "client_secret",
+            "client_secret looks like a token; check that the fields are not swapped",
+        ));
+        return;
+    }
+    if secret.chars().any(char::is_whitespace) {
+        findings.push(Finding::error(
+            "client_secret",
+            "client_secret contains whitespace",
+        ));
+    }
+
+    match BASE64_SHAPE.decode(secret.trim()) {
+        Ok(decoded) if decoded.len() != 32 => findings.push(Finding::warning(
+            "client_secret",
+            format!(
+                "client_secret decodes to {} bytes, expected 32",
+                decoded.len()
+            ),
+        )),
+        Ok(_) => {}
+        Err(_) => findings.push(Finding::error(
+            "client_secret",
+            "client_secret is not valid base64",
+        )),
+    }
+}
+
+fn check_host(findings: &mut Vec<Finding>, host: &str) {
+    let bare = if let Some(rest) = host.strip_prefix("https://") {
+        rest
+    } else if let Some(rest) = host.strip_prefix("http://") {
+        // Same policy as the config loader: plain http only reaches this machine
+        if is_loopback_http(host) {
+            findings.push(Finding::warning(
+                "host",
+                "host uses plain http; only use this for local test servers",
+            ));
+        } else {
+            findings.push(Finding::error(
+                "host",
+                "host must use https; remove the 'http://' scheme",
+            ));
+        }
+        rest
+    } else {
+        host
+    };
+
+    if bare.trim().is_empty() {
+        findings.push(Finding::error("host", "host is empty"));
+        return;
+    }
+    if bare.chars().any(char::is_whitespace) {
+        findings.push(Finding::error("host", "host contains whitespace"));
+    }
+
+    let (name, path) = match bare.find(['/', '?', '#']) {
+        Some(pos) => (&bare[..pos], &bare[pos..]),
+        None => (bare, ""),
+    };
+    if !path.is_empty() && path != "/" {
+        findings.push(Finding::error(
+            "host",
+            format!("host must not include a path or query ('{}')", path),
+        ));
+    }
+    if name.contains(':') {
+        findings.push(Finding::warning("host", "host includes a port"));
+    }
+
+    let name = name.to_ascii_lowercase();
+    if !name.ends_with(API_HOST_SUFFIX) {
+        findings.push(Finding::warning(
+            "host",
+            format!("host does not end with '{}'", API_HOST_SUFFIX),
+        ));
+    } else if !name.starts_with("akab-") {
+        findings.push(Finding::warning("host", "host should start with 'akab-'"));
+    }
+}
+
+fn check_max_body(findings: &mut Vec<Finding>, max_body: usize) {
+    if max_body == 0 {
+        findings.push(Finding::error(
+            "max_body",
+            "max_body must be greater than zero",
+        ));
+    } else if max_body > MAX_BODY {
+        findings.push(Finding::warning(
+            "max_body",
+            format!(
+                "max_body {} exceeds the {} bytes Akamai APIs hash",
+                max_body, MAX_BODY
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_A: &str = "akab-abcdefghijklmnop-qrstuvwxyz012345";
+    const TOKEN_B: &str = "akab-0123456789abcdef-ghijklmnopqrstuv";
+    const SECRET: &str = "abcdefghijklmnopqrstuvwxyz0123456789ABCDEFG=";
+
+    fn fields(findings: &[Finding], severity: Severity) -> Vec<&'static str> {
+        findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .map(|f| f.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config_has_no_findings() {
+        let config = EdgeGridConfig::new(
+            TOKEN_A,
+            SECRET,
+            TOKEN_B,
+            "akab-xxxxxxxxxxxxxxxx-xxxxxxxxxxxxxxxx.luna.akamaiapis.net".to_string(),
+        );
+        assert_eq!(config.lint(), vec![]);
+        assert!(config.ensure_valid().is_ok());
+    }
+
+    #[test]
+    fn test_common_mistakes_are_reported() {
+        let mut config = EdgeGridConfig::new(
+            "client-token",
+            TOKEN_A,
+            TOKEN_B,
+            "akab-xxxx.luna.akamaiapis.net/papi/v1".to_string(),
+        );
+        config.max_body = 0;
+
+        let findings = config.lint();
+        assert_eq!(
+            fields(&findings, Severity::Error),
+            vec!["client_token", "client_secret", "host", "max_body"]
+        );
+        assert!(findings.iter().all(|f| !f.message.contains(TOKEN_A)));
+
+        match config.ensure_valid() {
+            Err(EdgeGridError::Validation(reported)) => assert_eq!(reported, findings),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_secret_shape_and_host_warnings() {
+        let config = EdgeGridConfig::new(
+            TOKEN_A,
+            "not base64!",
+            TOKEN_A,
+            "http://example.com:8080".to_string(),
+        );
+
+        let findings = config.lint();
+        assert_eq!(
+            fields(&findings, Severity::Error),
+            vec!["access_token", "client_secret", "client_secret", "host"]
+        );
+        assert_eq!(fields(&findings, Severity::Warning), vec!["host", "host"]);
+    }
+
+    #[test]
+    fn test_loopback_http_host_is_a_warning() {
+        let config = EdgeGridConfig::new(
+            TOKEN_A,
+            SECRET,
+            TOKEN_B,
+            "http://127.0.0.1:8080".to_string(),
+        );
+
+        let findings = config.lint();
+        assert!(
+            fields(&findings, Severity::Error).is_empty(),
+            "{:?}",
+            findings
+        );
+        assert!(findings
+            .iter()
+            .any(|f| f.field == "host" && f.message.contains("plain http")));
+        assert!(config.ensure_valid().is_ok());
+    }
+}
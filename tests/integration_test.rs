@@ -6,9 +6,13 @@ use akamai_edgegrid::{EdgeGridClient, EdgeGridConfig};
 async fn test_get_request() {
     let mut server = mockito::Server::new_async().await;
     let url = server.url();
-    
-    let _m = server.mock("GET", "/test")
-        .match_header("authorization", mockito::Matcher::Regex(r"^EG1-HMAC-SHA256.*".to_string()))
+
+    let _m = server
+        .mock("GET", "/test")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex(r"^EG1-HMAC-SHA256.*".to_string()),
+        )
         .with_status(200)
         .with_body(r#"{"status": "ok"}"#)
         .create_async()
@@ -31,9 +35,13 @@ async fn test_get_request() {
 async fn test_post_request_with_json() {
     let mut server = mockito::Server::new_async().await;
     let url = server.url();
-    
-    let _m = server.mock("POST", "/test")
-        .match_header("authorization", mockito::Matcher::Regex(r"^EG1-HMAC-SHA256.*".to_string()))
+
+    let _m = server
+        .mock("POST", "/test")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex(r"^EG1-HMAC-SHA256.*".to_string()),
+        )
         .match_header("content-type", "application/json")
         .match_body(mockito::Matcher::Json(serde_json::json!({
             "key": "value"
@@ -54,13 +62,8 @@ async fn test_post_request_with_json() {
     let body = serde_json::json!({
         "key": "value"
     });
-    
-    let response = client
-        .post("/test")
-        .json(&body)
-        .send()
-        .await
-        .unwrap();
+
+    let response = client.post("/test").json(&body).send().await.unwrap();
 
     assert_eq!(response.status(), 201);
 }
@@ -69,10 +72,17 @@ async fn test_post_request_with_json() {
 async fn test_query_parameters() {
     let mut server = mockito::Server::new_async().await;
     let url = server.url();
-    
-    let _m = server.mock("GET", "/test")
-        .match_query(mockito::Matcher::UrlEncoded("limit".to_string(), "10".to_string()))
-        .match_query(mockito::Matcher::UrlEncoded("offset".to_string(), "20".to_string()))
+
+    let _m = server
+        .mock("GET", "/test")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "limit".to_string(),
+            "10".to_string(),
+        ))
+        .match_query(mockito::Matcher::UrlEncoded(
+            "offset".to_string(),
+            "20".to_string(),
+        ))
         .with_status(200)
         .create_async()
         .await;
@@ -105,7 +115,7 @@ fn test_config_validation() {
         "token".to_string(),
         "host".to_string(),
     );
-    
+
     let result = EdgeGridClient::new(config);
     assert!(result.is_err());
 }
@@ -123,8 +133,1829 @@ fn test_edgerc_parsing() {
     writeln!(file, "client_token = test-client").unwrap();
 
     let config = EdgeGridConfig::from_edgerc(file.path(), "default").unwrap();
-    assert_eq!(config.client_token, "test-client");
-    assert_eq!(config.client_secret, "test-secret");
-    assert_eq!(config.access_token, "test-access");
+    assert_eq!(config.client_token.expose(), "test-client");
+    assert_eq!(config.client_secret.expose(), "test-secret");
+    assert_eq!(config.access_token.expose(), "test-access");
     assert_eq!(config.host, "https://test.luna.akamaiapis.net");
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_rotation_retries_401_with_previous_credentials() {
+    let mut server = mockito::Server::new_async().await;
+    let url = server.url();
+
+    let rejected = server
+        .mock("GET", "/test")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("client_token=new-client-token;".to_string()),
+        )
+        .with_status(401)
+        .create_async()
+        .await;
+    let accepted = server
+        .mock("GET", "/test")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("client_token=old-client-token;".to_string()),
+        )
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let config = |token: &str| {
+        EdgeGridConfig::new(
+            token.to_string(),
+            "test-client-secret".to_string(),
+            "test-access-token".to_string(),
+            url.clone(),
+        )
+    };
+
+    let client = EdgeGridClient::new(config("old-client-token")).unwrap();
+    let clone = client.clone();
+    client
+        .credentials()
+        .rotate(config("new-client-token"))
+        .unwrap();
+
+    let response = clone.get("/test").send().await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    rejected.assert_async().await;
+    accepted.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_request_timeout_and_cancellation() {
+    use akamai_edgegrid::{CancellationToken, EdgeGridError};
+    use std::time::Duration;
+
+    // Accept connections but never answer, so only the deadline can end the request
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        url,
+    );
+    let client = EdgeGridClient::new(config)
+        .unwrap()
+        .with_timeout(Duration::from_secs(30));
+
+    let err = client
+        .get("/slow")
+        .timeout(Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EdgeGridError::Timeout(_)));
+    assert!(err.is_timeout() && err.is_retryable());
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let err = client
+        .get("/slow")
+        .cancel_on(token)
+        .send_text()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EdgeGridError::Cancelled));
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_tracing_span_and_redacted_debug_logging() {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut server = mockito::Server::new_async().await;
+    let _m = server
+        .mock("GET", "/papi/v1/properties/prp_123")
+        .with_status(200)
+        .with_header("x-akamai-request-id", "req-42")
+        .create_async()
+        .await;
+
+    let mut config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    config.debug = true;
+
+    let client = EdgeGridClient::new(config).unwrap();
+    client
+        .get("/papi/v1/properties/prp_123")
+        .send()
+        .await
+        .unwrap();
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(
+        output.contains("edgegrid.request{method=GET path=/papi/v1/properties/{id} attempt=1"),
+        "{}",
+        output
+    );
+    assert!(output.contains("status=200"), "{}", output);
+    assert!(output.contains("request_id=\"req-42\""), "{}", output);
+    assert!(output.contains("string_to_sign="), "{}", output);
+    assert!(output.contains("signature=[REDACTED]"), "{}", output);
+    for secret in [
+        "test-client-token",
+        "test-client-secret",
+        "test-access-token",
+    ] {
+        assert!(!output.contains(secret), "{}", output);
+    }
+}
+
+#[tokio::test]
+async fn test_observer_sees_request_and_outcome() {
+    use akamai_edgegrid::{RequestInfo, RequestObserver, RequestOutcome};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl RequestObserver for Recorder {
+        fn on_request(&self, request: &RequestInfo) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("start {} {}", request.method, request.path));
+        }
+
+        fn on_response(&self, request: &RequestInfo, outcome: &RequestOutcome) {
+            self.0.lock().unwrap().push(format!(
+                "end {} {} {}",
+                request.method, request.path, outcome.status_class
+            ));
+        }
+    }
+
+    let mut server = mockito::Server::new_async().await;
+    let _ok = server
+        .mock("GET", "/ccu/v3/purges/123")
+        .with_status(200)
+        .with_body("{}")
+        .create_async()
+        .await;
+    let _missing = server
+        .mock("DELETE", "/ccu/v3/purges/456")
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let recorder = Recorder::default();
+    let client = EdgeGridClient::new(config)
+        .unwrap()
+        .with_observer(recorder.clone());
+
+    client
+        .get("/ccu/v3/purges/123")
+        .send_json::<serde_json::Value>()
+        .await
+        .unwrap();
+    let err = client
+        .delete("/ccu/v3/purges/456")
+        .send_json::<serde_json::Value>()
+        .await
+        .unwrap_err();
+    assert_eq!(err.problem().unwrap().status, 404);
+    assert!(!err.is_retryable());
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![
+            "start GET /ccu/v3/purges/{id}",
+            "end GET /ccu/v3/purges/{id} 2xx",
+            "start DELETE /ccu/v3/purges/{id}",
+            "end DELETE /ccu/v3/purges/{id} 4xx",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_interceptors_mutate_short_circuit_and_abort() {
+    use akamai_edgegrid::{EdgeGridError, InterceptAction, Interceptor, RequestInfo};
+    use reqwest::{Request, Response};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Audit(Arc<Mutex<Vec<String>>>);
+
+    impl Interceptor for Audit {
+        fn before_sign(&self, request: &mut Request) -> akamai_edgegrid::Result<InterceptAction> {
+            match request.url().path() {
+                "/blocked" => Err(EdgeGridError::Config("blocked by policy".to_string())),
+                "/cached" => Ok(InterceptAction::respond(
+                    http::Response::builder()
+                        .status(203)
+                        .body("cached")
+                        .unwrap(),
+                )),
+                _ => {
+                    request
+                        .headers_mut()
+                        .insert("x-correlation-id", "abc-123".parse().unwrap());
+                    Ok(InterceptAction::Continue)
+                }
+            }
+        }
+
+        fn after_sign(&self, request: &mut Request) -> akamai_edgegrid::Result<InterceptAction> {
+            assert!(request.headers().contains_key("authorization"));
+            Ok(InterceptAction::Continue)
+        }
+
+        fn on_response(
+            &self,
+            request: &RequestInfo,
+            response: &mut Response,
+        ) -> akamai_edgegrid::Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", request.path, response.status().as_u16()));
+            Ok(())
+        }
+    }
+
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/live")
+        .match_header("x-correlation-id", "abc-123")
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let audit = Audit::default();
+    let client = EdgeGridClient::new(config)
+        .unwrap()
+        .with_interceptor(audit.clone());
+
+    assert_eq!(client.get("/live").send().await.unwrap().status(), 200);
+    assert_eq!(client.get("/cached").send_text().await.unwrap(), "cached");
+    let err = client.get("/blocked").send().await.unwrap_err();
+    assert!(err.to_string().contains("blocked by policy"));
+
+    mock.assert_async().await;
+    assert_eq!(*audit.0.lock().unwrap(), vec!["/live 200", "/cached 203"]);
+}
+
+#[tokio::test]
+async fn test_cassette_records_then_replays_offline() {
+    use akamai_edgegrid::{Cassette, EdgeGridError};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassettes/groups.json");
+
+    let mut server = mockito::Server::new_async().await;
+    let _m = server
+        .mock("POST", "/papi/v1/search")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"versions":{"items":[]}}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+
+    let recorder = EdgeGridClient::new(config.clone())
+        .unwrap()
+        .with_cassette(Cassette::record(&path));
+    let recorded = recorder
+        .post("/papi/v1/search")
+        .query("contractId", "ctr_1")
+        .json(&serde_json::json!({"propertyName": "www.example.com"}))
+        .send_text()
+        .await
+        .unwrap();
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("test-client-token"));
+    assert!(!saved.contains("test-access-token"));
+    assert!(!saved.contains("EG1-HMAC-SHA256"));
+
+    // Replay without a server
+    drop(server);
+    let player = EdgeGridClient::new(config)
+        .unwrap()
+        .with_cassette(Cassette::replay(&path).unwrap());
+    let replayed = player
+        .post("/papi/v1/search")
+        .query("contractId", "ctr_1")
+        .json(&serde_json::json!({"propertyName": "www.example.com"}))
+        .send_text()
+        .await
+        .unwrap();
+    assert_eq!(replayed, recorded);
+
+    let err = player
+        .post("/papi/v1/search")
+        .query("contractId", "ctr_1")
+        .json(&serde_json::json!({"propertyName": "other.example.com"}))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EdgeGridError::Cassette(_)));
+}
+
+#[tokio::test]
+async fn test_har_recorder_captures_exchanges() {
+    use akamai_edgegrid::HarRecorder;
+
+    let mut server = mockito::Server::new_async().await;
+    let _m = server
+        .mock("GET", "/papi/v1/groups")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("set-cookie", "session=abc")
+        .with_body(r#"{"groups":{"items":[]}}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let har = HarRecorder::new();
+    let client = EdgeGridClient::new(config).unwrap().with_har(har.clone());
+
+    let body = client.get("/papi/v1/groups").send_text().await.unwrap();
+    assert_eq!(body, r#"{"groups":{"items":[]}}"#);
+    assert_eq!(har.len(), 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("traffic.har");
+    har.save(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("test-client-token"));
+    assert!(!saved.contains("session=abc"));
+
+    let json: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    let entry = &json["log"]["entries"][0];
+    assert_eq!(json["log"]["version"], "1.2");
+    assert_eq!(entry["request"]["method"], "GET");
+    assert_eq!(entry["response"]["status"], 200);
+    assert_eq!(entry["response"]["content"]["text"], body);
+    assert!(entry["timings"]["wait"].as_f64().unwrap() >= 0.0);
+}
+
+#[tokio::test]
+async fn test_sign_only_and_to_curl() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/papi/v1/groups")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "contractId".into(),
+            "ctr_1".into(),
+        ))
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex(
+                r"^EG1-HMAC-SHA256 client_token=test-client-token;".to_string(),
+            ),
+        )
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let client = EdgeGridClient::new(config).unwrap();
+
+    let curl = client
+        .get("/papi/v1/groups")
+        .query("contractId", "ctr_1")
+        .to_curl()
+        .unwrap();
+    assert!(curl.starts_with(&format!(
+        "curl -X GET '{}/papi/v1/groups?contractId=ctr_1'",
+        server.url()
+    )));
+    assert!(!curl.contains("test-client-token"));
+
+    // Nothing was sent by to_curl; the signed request can be sent elsewhere
+    let signed = client
+        .get("/papi/v1/groups")
+        .query("contractId", "ctr_1")
+        .sign_only()
+        .unwrap();
+    let response = reqwest::Client::new()
+        .execute(signed.into_request())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_fast_purge_batches_and_reports_errors() {
+    use akamai_edgegrid::ccu::{FastPurge, PurgeObjects};
+    use akamai_edgegrid::Network;
+
+    let mut server = mockito::Server::new_async().await;
+    let accepted = server.mock("POST", "/ccu/v3/invalidate/tag/staging")
+        .match_header("content-type", "application/json")
+        .with_status(201)
+        .with_header("x-ratelimit-limit", "10000")
+        .with_header("x-ratelimit-remaining", "9990")
+        .with_body(r#"{"httpStatus": 201, "detail": "Request accepted", "supportId": "s-1", "purgeId": "p-1", "estimatedSeconds": 5}"#)
+        .expect(2)
+        .create_async()
+        .await;
+    let limited = server.mock("POST", "/ccu/v3/delete/cpcode/production")
+        .match_body(mockito::Matcher::Json(serde_json::json!({"objects": [12345]})))
+        .with_status(429)
+        .with_header("content-type", "application/problem+json")
+        .with_body(r#"{"type": "https://problems.luna.akamaiapis.net/ccu/v3/rate-limit", "title": "Too Many Requests", "detail": "Rate limit exceeded"}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let ccu = FastPurge::new(EdgeGridClient::new(config).unwrap()).with_max_body(40);
+
+    let tags = PurgeObjects::tags(["product-1", "product-2", "product-3"]);
+    let responses = ccu.invalidate(Network::Staging, tags).await.unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses.iter().map(|r| r.objects).sum::<usize>(), 3);
+    assert_eq!(responses[0].purge_id, "p-1");
+    assert_eq!(responses[0].estimated_seconds, 5);
+    assert_eq!(responses[0].rate_limit.remaining, Some(9990));
+    accepted.assert_async().await;
+
+    let err = ccu
+        .delete(Network::Production, PurgeObjects::cp_codes([12345]))
+        .await
+        .unwrap_err();
+    let problem = err.problem().unwrap();
+    assert_eq!(problem.status, 429);
+    assert_eq!(problem.detail.as_deref(), Some("Rate limit exceeded"));
+    assert!(err.is_retryable());
+    limited.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_papi_versions_and_rule_tree_patch() {
+    use akamai_edgegrid::papi::{Papi, PatchOperation};
+
+    let mut server = mockito::Server::new_async().await;
+    let group = mockito::Matcher::AllOf(vec![
+        mockito::Matcher::UrlEncoded("contractId".into(), "ctr_1".into()),
+        mockito::Matcher::UrlEncoded("groupId".into(), "grp_1".into()),
+    ]);
+    let create = server.mock("POST", "/papi/v1/properties/prp_1/versions")
+        .match_query(group.clone())
+        .match_header("papi-use-prefixes", "true")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "createFromVersion": 3,
+            "createFromVersionEtag": "a1b2"
+        })))
+        .with_status(201)
+        .with_body(r#"{"versionLink": "/papi/v1/properties/prp_1/versions/4?contractId=ctr_1&groupId=grp_1"}"#)
+        .create_async()
+        .await;
+    let rules = server.mock("GET", "/papi/v1/properties/prp_1/versions/4/rules")
+        .match_query(group.clone())
+        .match_header("accept", "application/vnd.akamai.papirules.v2023-01-05+json")
+        .with_status(200)
+        .with_body(r#"{"propertyId": "prp_1", "propertyVersion": 4, "etag": "e4", "ruleFormat": "v2023-01-05",
+            "rules": {"name": "default", "behaviors": [{"name": "origin", "options": {"hostname": "old.example.com"}}]}}"#)
+        .create_async()
+        .await;
+    let patch = server.mock("PATCH", "/papi/v1/properties/prp_1/versions/4/rules")
+        .match_query(group)
+        .match_header("content-type", "application/json-patch+json")
+        .match_header("if-match", "\"e4\"")
+        .match_body(mockito::Matcher::Json(serde_json::json!([
+            {"op": "replace", "path": "/rules/behaviors/0/options/hostname", "value": "new.example.com"}
+        ])))
+        .with_status(200)
+        .with_body(r#"{"propertyId": "prp_1", "propertyVersion": 4, "etag": "e5", "ruleFormat": "v2023-01-05",
+            "rules": {"name": "default", "behaviors": [{"name": "origin", "options": {"hostname": "new.example.com"}}]}}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let papi = Papi::new(EdgeGridClient::new(config).unwrap())
+        .with_group("ctr_1", "grp_1")
+        .with_rule_format("v2023-01-05");
+
+    let version = papi
+        .create_version_from_etag("prp_1", 3, "a1b2")
+        .await
+        .unwrap();
+    assert_eq!(version, 4);
+
+    let tree = papi.rules("prp_1", version).await.unwrap();
+    assert_eq!(
+        tree.rules.behaviors[0].options["hostname"],
+        "old.example.com"
+    );
+
+    let operations = [PatchOperation::replace(
+        "/rules/behaviors/0/options/hostname",
+        "new.example.com",
+    )];
+    let tree = papi
+        .patch_rules("prp_1", version, &tree.etag, &operations)
+        .await
+        .unwrap();
+    assert_eq!(tree.etag, "e5");
+    assert_eq!(
+        tree.rules.behaviors[0].options["hostname"],
+        "new.example.com"
+    );
+
+    create.assert_async().await;
+    rules.assert_async().await;
+    patch.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_operation_polls_activation_until_active() {
+    use akamai_edgegrid::papi::{Activation, Papi};
+    use akamai_edgegrid::{EdgeGridError, Network, OperationState};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let path = "/papi/v1/properties/prp_1/activations/atv_1";
+    let activation = |status: &str| {
+        serde_json::json!({"activations": {"items": [{
+            "activationId": "atv_1", "propertyVersion": 4, "network": "STAGING",
+            "status": status, "activationType": "ACTIVATE"
+        }]}})
+        .to_string()
+    };
+
+    let mut server = mockito::Server::new_async().await;
+    let throttled = server
+        .mock("GET", path)
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .expect(1)
+        .create_async()
+        .await;
+    let pending = server
+        .mock("GET", path)
+        .match_header("papi-use-prefixes", "true")
+        .with_status(200)
+        .with_body(activation("PENDING"))
+        .expect(1)
+        .create_async()
+        .await;
+    let active = server
+        .mock("GET", path)
+        .with_status(200)
+        .with_body(activation("ACTIVE"))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let papi = Papi::new(EdgeGridClient::new(config).unwrap());
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let events = seen.clone();
+    let done: Activation = papi
+        .activation_operation("prp_1", "atv_1")
+        .interval(Duration::from_millis(1), Duration::from_millis(5))
+        .timeout(Duration::from_secs(10))
+        .on_progress(move |progress| {
+            events.lock().unwrap().push((
+                progress.attempt,
+                progress.state.clone(),
+                progress.next_poll.is_some(),
+            ));
+        })
+        .wait()
+        .await
+        .unwrap();
+
+    assert_eq!(done.status, "ACTIVE");
+    assert_eq!(done.network, Network::Staging);
+    // The throttled first poll is retried without a progress event
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            (2, OperationState::Pending, true),
+            (3, OperationState::Succeeded, false)
+        ]
+    );
+    throttled.assert_async().await;
+    pending.assert_async().await;
+    active.assert_async().await;
+
+    // An operation that stays pending times out
+    let _stuck = server
+        .mock("GET", "/papi/v1/properties/prp_1/activations/atv_2")
+        .with_status(200)
+        .with_body(activation("PENDING"))
+        .create_async()
+        .await;
+    let err = papi
+        .activation_operation("prp_1", "atv_2")
+        .interval(Duration::from_millis(1), Duration::from_millis(1))
+        .timeout(Duration::from_millis(20))
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, EdgeGridError::Timeout(timeout) if timeout == Duration::from_millis(20)),
+        "{:?}",
+        err
+    );
+
+    let _failed = server
+        .mock("GET", "/papi/v1/properties/prp_1/activations/atv_3")
+        .with_status(200)
+        .with_body(activation("FAILED").replace("atv_1", "atv_3"))
+        .create_async()
+        .await;
+    let err = papi
+        .activation_operation("prp_1", "atv_3")
+        .interval(Duration::from_millis(1), Duration::from_millis(1))
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, EdgeGridError::OperationFailed(ref reason) if reason.contains("atv_3 is FAILED"))
+    );
+}
+
+#[tokio::test]
+async fn test_operation_does_not_busy_loop_on_zero_retry_after() {
+    use akamai_edgegrid::papi::Papi;
+    use akamai_edgegrid::EdgeGridError;
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let throttled = server
+        .mock("GET", "/papi/v1/properties/prp_1/activations/atv_1")
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .expect_at_least(2)
+        .expect_at_most(8)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let papi = Papi::new(EdgeGridClient::new(config).unwrap());
+
+    let err = papi
+        .activation_operation("prp_1", "atv_1")
+        .interval(Duration::from_millis(50), Duration::from_secs(1))
+        .timeout(Duration::from_millis(300))
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EdgeGridError::Timeout(_)), "{:?}", err);
+    throttled.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_dns_changelist_and_bulk_zone_create() {
+    use akamai_edgegrid::dns::{Dns, RecordData, RecordSet, RecordSetChange, RecordType, Zone};
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let create = server
+        .mock("POST", "/config-dns/v2/changelists")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "zone".into(),
+            "example.com".into(),
+        ))
+        .with_status(201)
+        .with_body(
+            r#"{"zone": "example.com", "changeTag": "t1", "zoneVersionId": "v1", "stale": false}"#,
+        )
+        .create_async()
+        .await;
+    let change = server.mock("POST", "/config-dns/v2/changelists/example.com/recordsets/add-change")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "name": "www.example.com", "type": "MX", "op": "ADD", "ttl": 300, "rdata": ["10 mail.example.com."]
+        })))
+        .with_status(204)
+        .create_async()
+        .await;
+    let submit = server
+        .mock("POST", "/config-dns/v2/changelists/example.com/submit")
+        .with_status(204)
+        .create_async()
+        .await;
+    let record_sets = server.mock("GET", "/config-dns/v2/zones/example.com/recordsets")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("showAll".into(), "true".into()),
+            mockito::Matcher::UrlEncoded("types".into(), "MX,TXT".into()),
+        ]))
+        .with_status(200)
+        .with_body(r#"{"metadata": {"totalElements": 1}, "recordsets": [{"name": "www.example.com", "type": "MX", "ttl": 300, "rdata": ["10 mail.example.com."]}]}"#)
+        .create_async()
+        .await;
+    let bulk = server.mock("POST", "/config-dns/v2/zones/create-requests")
+        .match_query(mockito::Matcher::UrlEncoded("contractId".into(), "ctr_1".into()))
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "zones": [{"zone": "a.example", "type": "PRIMARY"}, {"zone": "b.example", "type": "SECONDARY", "masters": ["192.0.2.53"]}]
+        })))
+        .with_status(202)
+        .with_body(r#"{"requestId": "req-1", "expirationDate": "2030-01-01T00:00:00Z"}"#)
+        .create_async()
+        .await;
+    let _status = server.mock("GET", "/config-dns/v2/zones/create-requests/req-1")
+        .with_status(200)
+        .with_body(r#"{"requestId": "req-1", "zonesSubmitted": 2, "successCount": 1, "failureCount": 1, "isComplete": true}"#)
+        .create_async()
+        .await;
+    let _result = server.mock("GET", "/config-dns/v2/zones/create-requests/req-1/result")
+        .with_status(200)
+        .with_body(r#"{"requestId": "req-1", "successfullyCreatedZones": ["a.example"], "failedZones": [{"zone": "b.example", "failureReason": "ZONE_ALREADY_EXISTS"}]}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let dns = Dns::new(EdgeGridClient::new(config).unwrap());
+
+    let mx = RecordSet::from_data(
+        "www.example.com",
+        300,
+        vec![RecordData::Mx {
+            preference: 10,
+            exchange: "mail.example.com.".to_string(),
+        }],
+    )
+    .unwrap();
+    let changelist = dns.create_changelist("example.com").await.unwrap();
+    assert_eq!(changelist.change_tag, "t1");
+    dns.add_change("example.com", &RecordSetChange::add(mx.clone()))
+        .await
+        .unwrap();
+    dns.submit_changelist("example.com").await.unwrap();
+
+    let sets = dns
+        .record_sets("example.com", &[RecordType::Mx, RecordType::Txt])
+        .await
+        .unwrap();
+    assert_eq!(sets, vec![mx]);
+    assert_eq!(
+        sets[0].data().unwrap()[0],
+        RecordData::Mx {
+            preference: 10,
+            exchange: "mail.example.com.".to_string()
+        }
+    );
+
+    let zones = [
+        Zone::primary("a.example").comment("bulk"),
+        Zone::secondary("b.example", ["192.0.2.53"]).comment("bulk"),
+    ];
+    let request = dns.create_zones("ctr_1", None, &zones).await.unwrap();
+    let status = dns
+        .bulk_create_operation(&request.request_id)
+        .interval(Duration::from_millis(1), Duration::from_millis(1))
+        .wait()
+        .await
+        .unwrap();
+    assert_eq!((status.success_count, status.failure_count), (1, 1));
+    let result = dns.bulk_create_result(&request.request_id).await.unwrap();
+    assert_eq!(result.successfully_created_zones, vec!["a.example"]);
+    assert_eq!(result.failed_zones[0].failure_reason, "ZONE_ALREADY_EXISTS");
+
+    create.assert_async().await;
+    change.assert_async().await;
+    submit.assert_async().await;
+    record_sets.assert_async().await;
+    bulk.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_iam_rotates_edgerc_credential() {
+    use akamai_edgegrid::iam::{Iam, RotateOptions};
+    use akamai_edgegrid::Edgerc;
+
+    let mut server = mockito::Server::new_async().await;
+    let list = server.mock("GET", "/identity-management/v3/api-clients/self/credentials")
+        .with_status(200)
+        .with_body(r#"[
+            {"credentialId": 1, "clientToken": "old-client-token", "status": "ACTIVE", "createdOn": "2025-01-01T00:00:00.000Z", "expiresOn": "2027-01-01T00:00:00.000Z"},
+            {"credentialId": 2, "clientToken": "other-client-token", "status": "INACTIVE", "createdOn": "2024-01-01T00:00:00.000Z", "expiresOn": "2026-01-01T00:00:00.000Z"}
+        ]"#)
+        .create_async()
+        .await;
+    let create = server.mock("POST", "/identity-management/v3/api-clients/self/credentials")
+        .with_status(201)
+        .with_body(r#"{"credentialId": 3, "clientToken": "new-client-token", "clientSecret": "new-client-secret", "status": "ACTIVE", "createdOn": "2026-10-18T00:00:00.000Z", "expiresOn": "2028-10-18T00:00:00.000Z"}"#)
+        .create_async()
+        .await;
+    let probe = server
+        .mock("GET", "/identity-management/v3/api-clients/self")
+        .match_query(mockito::Matcher::Any)
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("client_token=new-client-token".to_string()),
+        )
+        .with_status(200)
+        .with_body(r#"{"clientId": "abc123", "clientName": "cron"}"#)
+        .create_async()
+        .await;
+    let deactivate = server
+        .mock(
+            "POST",
+            "/identity-management/v3/api-clients/self/credentials/1/deactivate",
+        )
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("client_token=new-client-token".to_string()),
+        )
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".edgerc");
+    let secret = dir.path().join("secret");
+    std::fs::write(&secret, "old-client-secret").unwrap();
+    std::fs::write(
+        &path,
+        format!(
+            "; rotated by cron\n[default]\nhost = {}\nclient_token = old-client-token\nclient_secret_file = {}\naccess_token = test-access-token\nmax_body = 131072\n",
+            server.url(),
+            secret.display()
+        ),
+    )
+    .unwrap();
+
+    let config = EdgeGridConfig::new(
+        "old-client-token".to_string(),
+        "old-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let client = EdgeGridClient::new(config).unwrap();
+    let iam = Iam::new(client.clone());
+
+    let options = RotateOptions {
+        deactivate_previous: true,
+        ..RotateOptions::default()
+    };
+    let rotation = iam
+        .rotate_edgerc_credential(&path, "default", options)
+        .await
+        .unwrap();
+    list.assert_async().await;
+    create.assert_async().await;
+    probe.assert_async().await;
+    deactivate.assert_async().await;
+
+    assert_eq!(rotation.credential.credential_id, 3);
+    assert_eq!(rotation.previous.credential_id, 1);
+    assert!(rotation.deactivated_previous);
+    assert_eq!(rotation.config.client_secret.expose(), "new-client-secret");
+    assert_eq!(
+        client.credentials().current().client_token.expose(),
+        "new-client-token"
+    );
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("; rotated by cron\n"));
+    assert!(saved.contains("client_secret_file"));
+    assert!(!saved.contains("new-client-secret"));
+    assert_eq!(
+        std::fs::read_to_string(&secret).unwrap().trim(),
+        "new-client-secret"
+    );
+    let config = Edgerc::load(&path).unwrap().config("default").unwrap();
+    assert_eq!(config.client_token.expose(), "new-client-token");
+    assert_eq!(config.client_secret.expose(), "new-client-secret");
+    assert_eq!(config.max_body, 131072);
+}
+
+#[tokio::test]
+async fn test_iam_rotation_keeps_previous_credential_until_accepted() {
+    use akamai_edgegrid::iam::{Iam, RotateOptions};
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let _list = server
+        .mock(
+            "GET",
+            "/identity-management/v3/api-clients/self/credentials",
+        )
+        .with_status(200)
+        .with_body(
+            r#"[{"credentialId": 1, "clientToken": "old-client-token", "status": "ACTIVE"}]"#,
+        )
+        .create_async()
+        .await;
+    let _create = server.mock("POST", "/identity-management/v3/api-clients/self/credentials")
+        .with_status(201)
+        .with_body(r#"{"credentialId": 3, "clientToken": "new-client-token", "clientSecret": "new-client-secret"}"#)
+        .create_async()
+        .await;
+    let _probe = server
+        .mock("GET", "/identity-management/v3/api-clients/self")
+        .match_query(mockito::Matcher::Any)
+        .with_status(401)
+        .create_async()
+        .await;
+    let deactivate = server
+        .mock(
+            "POST",
+            "/identity-management/v3/api-clients/self/credentials/1/deactivate",
+        )
+        .expect(0)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".edgerc");
+    std::fs::write(
+        &path,
+        format!(
+            "[default]\nhost = {}\nclient_token = old-client-token\nclient_secret = old-client-secret\naccess_token = test-access-token\n",
+            server.url()
+        ),
+    )
+    .unwrap();
+
+    let client = EdgeGridClient::from_edgerc(&path, "default").unwrap();
+    let options = RotateOptions {
+        deactivate_previous: true,
+        propagation_timeout: Duration::ZERO,
+        ..RotateOptions::default()
+    };
+    let rotation = Iam::new(client)
+        .rotate_edgerc_credential(&path, "default", options)
+        .await
+        .unwrap();
+
+    deactivate.assert_async().await;
+    assert!(!rotation.deactivated_previous);
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("client_secret = new-client-secret"));
+}
+
+#[tokio::test]
+async fn test_iam_rotation_removes_credential_it_cannot_save() {
+    use akamai_edgegrid::iam::{Iam, RotateOptions};
+
+    let mut server = mockito::Server::new_async().await;
+    let _list = server
+        .mock(
+            "GET",
+            "/identity-management/v3/api-clients/self/credentials",
+        )
+        .with_status(200)
+        .with_body(
+            r#"[{"credentialId": 1, "clientToken": "old-client-token", "status": "ACTIVE"}]"#,
+        )
+        .create_async()
+        .await;
+    let create = server.mock("POST", "/identity-management/v3/api-clients/self/credentials")
+        .with_status(201)
+        .with_body(r#"{"credentialId": 3, "clientToken": "new-client-token", "clientSecret": "new-client-secret"}"#)
+        .create_async()
+        .await;
+    let deactivate = server
+        .mock(
+            "POST",
+            "/identity-management/v3/api-clients/self/credentials/3/deactivate",
+        )
+        .with_status(204)
+        .create_async()
+        .await;
+    let delete = server
+        .mock(
+            "DELETE",
+            "/identity-management/v3/api-clients/self/credentials/3",
+        )
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".edgerc");
+    let original = format!(
+        "[default]\nhost = {}\nclient_token = old-client-token\nclient_secret = old-client-secret\naccess_token = test-access-token\n",
+        server.url()
+    );
+    std::fs::write(&path, &original).unwrap();
+    // A directory where the temporary file goes makes the save fail, even as root
+    std::fs::create_dir(dir.path().join("..edgerc.tmp")).unwrap();
+
+    let client = EdgeGridClient::from_edgerc(&path, "default").unwrap();
+    let result = Iam::new(client.clone())
+        .rotate_edgerc_credential(&path, "default", RotateOptions::default())
+        .await;
+
+    assert!(result.is_err());
+    create.assert_async().await;
+    deactivate.assert_async().await;
+    delete.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+    assert_eq!(
+        client.credentials().current().client_token.expose(),
+        "old-client-token"
+    );
+}
+
+#[tokio::test]
+async fn test_iam_rotation_checks_section_before_creating_credential() {
+    use akamai_edgegrid::iam::{Iam, RotateOptions};
+
+    let mut server = mockito::Server::new_async().await;
+    let _list = server
+        .mock(
+            "GET",
+            "/identity-management/v3/api-clients/self/credentials",
+        )
+        .with_status(200)
+        .with_body(
+            r#"[{"credentialId": 1, "clientToken": "old-client-token", "status": "ACTIVE"}]"#,
+        )
+        .create_async()
+        .await;
+    let create = server
+        .mock(
+            "POST",
+            "/identity-management/v3/api-clients/self/credentials",
+        )
+        .expect(0)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".edgerc");
+    let original = format!(
+        "[command]\nhost = {url}\nclient_token = old-client-token\nclient_secret_command = echo old-client-secret\naccess_token = test-access-token\n\n\
+         [other]\nhost = {url}\nclient_token = other-client-token\nclient_secret = other-client-secret\naccess_token = test-access-token\n",
+        url = server.url()
+    );
+    std::fs::write(&path, &original).unwrap();
+
+    let client = EdgeGridClient::from_edgerc(&path, "other").unwrap();
+    let iam = Iam::new(client);
+
+    let err = iam
+        .rotate_edgerc_credential(&path, "command", RotateOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("replace_secret_provider"),
+        "{}",
+        err
+    );
+
+    let err = iam
+        .rotate_edgerc_credential(&path, "other", RotateOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("does not hold a credential"),
+        "{}",
+        err
+    );
+
+    create.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+}
+
+#[tokio::test]
+async fn test_network_list_remove_retries_after_conflict() {
+    use akamai_edgegrid::network_lists::{ActivationRequest, NetworkLists};
+    use akamai_edgegrid::Network;
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let path = "/network-list/v2/network-lists/123_BLOCK";
+    let _summary = server
+        .mock("GET", path)
+        .match_query(mockito::Matcher::UrlEncoded(
+            "includeElements".into(),
+            "false".into(),
+        ))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 4}"#)
+        .create_async()
+        .await;
+    let stale = server.mock("GET", path)
+        .match_query(mockito::Matcher::UrlEncoded("includeElements".into(), "true".into()))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 4, "list": ["192.0.2.1", "198.51.100.0/24"]}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let fresh = server.mock("GET", path)
+        .match_query(mockito::Matcher::UrlEncoded("includeElements".into(), "true".into()))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 5, "list": ["192.0.2.1", "198.51.100.0/24", "203.0.113.9"]}"#)
+        .create_async()
+        .await;
+    let conflict = server
+        .mock("PUT", path)
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"syncPoint": 4}),
+        ))
+        .with_status(409)
+        .with_header("content-type", "application/problem+json")
+        .with_body(r#"{"title": "Conflict", "detail": "Sync point mismatch"}"#)
+        .create_async()
+        .await;
+    let update = server.mock("PUT", path)
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "syncPoint": 5, "list": ["198.51.100.0/24", "203.0.113.9"]
+        })))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 6, "list": ["198.51.100.0/24", "203.0.113.9"]}"#)
+        .create_async()
+        .await;
+    let activate = server
+        .mock(
+            "POST",
+            "/network-list/v2/network-lists/123_BLOCK/environments/STAGING/activate",
+        )
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "comments": "remove 192.0.2.1", "notificationRecipients": []
+        })))
+        .with_status(200)
+        .with_body(
+            r#"{"activationId": 77, "activationStatus": "PENDING_ACTIVATION", "syncPoint": 6}"#,
+        )
+        .create_async()
+        .await;
+    let _pending = server
+        .mock("GET", "/network-list/v2/activations/77")
+        .with_status(200)
+        .with_body(
+            r#"{"activationId": 77, "activationStatus": "PENDING_ACTIVATION", "syncPoint": 6}"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let _active = server
+        .mock("GET", "/network-list/v2/activations/77")
+        .with_status(200)
+        .with_body(r#"{"activationId": 77, "activationStatus": "ACTIVE", "syncPoint": 6}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let lists = NetworkLists::new(EdgeGridClient::new(config).unwrap());
+
+    let list = lists.remove("123_BLOCK", ["192.0.2.1/32"]).await.unwrap();
+    assert_eq!(list.sync_point, 6);
+    stale.assert_async().await;
+    fresh.assert_async().await;
+    conflict.assert_async().await;
+    update.assert_async().await;
+
+    let err = lists
+        .remove("123_BLOCK", ["not-an-address"])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid IP list element"));
+
+    let activation = lists
+        .activate(
+            "123_BLOCK",
+            Network::Staging,
+            &ActivationRequest::new("remove 192.0.2.1"),
+        )
+        .await
+        .unwrap();
+    activate.assert_async().await;
+    let activation = lists
+        .activation_operation(activation.activation_id)
+        .interval(Duration::from_millis(1), Duration::from_millis(5))
+        .wait()
+        .await
+        .unwrap();
+    assert_eq!(activation.activation_status, "ACTIVE");
+}
+
+#[tokio::test]
+async fn test_network_list_keeps_existing_elements_verbatim() {
+    use akamai_edgegrid::network_lists::NetworkLists;
+
+    let mut server = mockito::Server::new_async().await;
+    let path = "/network-list/v2/network-lists/123_BLOCK";
+    let _summary = server
+        .mock("GET", path)
+        .match_query(mockito::Matcher::UrlEncoded(
+            "includeElements".into(),
+            "false".into(),
+        ))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 4}"#)
+        .create_async()
+        .await;
+    let _read = server.mock("GET", path)
+        .match_query(mockito::Matcher::UrlEncoded("includeElements".into(), "true".into()))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 4, "list": ["192.0.2.1/32", "2001:DB8::1", "203.0.113.9"]}"#)
+        .create_async()
+        .await;
+    let removed = server
+        .mock("PUT", path)
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "syncPoint": 4, "list": ["192.0.2.1/32", "2001:DB8::1"]
+        })))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 5}"#)
+        .create_async()
+        .await;
+    let updated = server.mock("PUT", path)
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "syncPoint": 4, "list": ["192.0.2.1/32", "2001:DB8::1", "203.0.113.9", "198.51.100.0/24"]
+        })))
+        .with_status(200)
+        .with_body(r#"{"uniqueId": "123_BLOCK", "name": "Block", "type": "IP", "syncPoint": 5}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let lists = NetworkLists::new(EdgeGridClient::new(config).unwrap());
+
+    lists.remove("123_BLOCK", ["203.0.113.9"]).await.unwrap();
+    removed.assert_async().await;
+
+    let mut list = lists.list("123_BLOCK").await.unwrap();
+    list.list.push("198.51.100.7/24".to_string());
+    lists.update(&list).await.unwrap();
+    updated.assert_async().await;
+
+    list.list.push("not-an-address".to_string());
+    let err = lists.update(&list).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid IP list element 'not-an-address'"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_edgekv_import_dir_retries_until_consistent() {
+    use akamai_edgegrid::edgekv::EdgeKv;
+    use akamai_edgegrid::Network;
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let items = "/edgekv/v1/networks/staging/namespaces/config/groups/pages/items";
+    let not_yet = server
+        .mock("PUT", format!("{}/home.json", items).as_str())
+        .with_status(404)
+        .with_header("content-type", "application/problem+json")
+        .with_body(r#"{"title": "Not Found", "detail": "The requested namespace does not exist"}"#)
+        .expect(2)
+        .create_async()
+        .await;
+    let home = server
+        .mock("PUT", format!("{}/home.json", items).as_str())
+        .match_header("content-type", "application/json")
+        .match_body(mockito::Matcher::Json(serde_json::json!({"title": "Home"})))
+        .with_status(200)
+        .create_async()
+        .await;
+    let banner = server
+        .mock("PUT", format!("{}/banner.txt", items).as_str())
+        .match_header("content-type", "text/plain")
+        .match_body("Welcome!\n")
+        .with_status(200)
+        .create_async()
+        .await;
+    let rejected = server
+        .mock("PUT", format!("{}/huge.txt", items).as_str())
+        .with_status(400)
+        .with_body(r#"{"title": "Bad Request", "detail": "Value too large"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _get = server
+        .mock("GET", format!("{}/home.json", items).as_str())
+        .with_status(200)
+        .with_body(r#"{"title": "Home"}"#)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("home.json"), r#"{"title": "Home"}"#).unwrap();
+    std::fs::write(dir.path().join("banner.txt"), "Welcome!\n").unwrap();
+    std::fs::write(dir.path().join("huge.txt"), "x").unwrap();
+    std::fs::write(dir.path().join(".DS_Store"), "").unwrap();
+    std::fs::create_dir(dir.path().join("drafts")).unwrap();
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let edgekv = EdgeKv::new(EdgeGridClient::new(config).unwrap());
+
+    let report = edgekv
+        .importer(Network::Staging, "config", "pages")
+        .concurrency(2)
+        .retries(3, Duration::from_millis(1))
+        .import_dir(dir.path())
+        .await
+        .unwrap();
+    assert_eq!(report.imported, vec!["banner.txt", "home.json"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "huge.txt");
+    assert_eq!(report.failed[0].1.problem().unwrap().status, 400);
+    not_yet.assert_async().await;
+    home.assert_async().await;
+    banner.assert_async().await;
+    rejected.assert_async().await;
+
+    #[derive(serde::Deserialize)]
+    struct Page {
+        title: String,
+    }
+    let page: Page = edgekv
+        .item(Network::Staging, "config", "pages", "home.json")
+        .await
+        .unwrap();
+    assert_eq!(page.title, "Home");
+}
+
+#[tokio::test]
+async fn test_edgekv_import_dir_encodes_keys() {
+    use akamai_edgegrid::edgekv::EdgeKv;
+    use akamai_edgegrid::Network;
+
+    let mut server = mockito::Server::new_async().await;
+    let item = server.mock("PUT", "/edgekv/v1/networks/staging/namespaces/config/groups/pages/items/about%20us%3Fv%3D2%23top.txt")
+        .match_body("About\n")
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("about us?v=2#top.txt"), "About\n").unwrap();
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let edgekv = EdgeKv::new(EdgeGridClient::new(config).unwrap());
+
+    let report = edgekv
+        .importer(Network::Staging, "config", "pages")
+        .import_dir(dir.path())
+        .await
+        .unwrap();
+    assert_eq!(report.imported, vec!["about us?v=2#top.txt"]);
+    assert!(report.failed.is_empty());
+    item.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_edgeworkers_upload_and_rollback() {
+    use akamai_edgegrid::edgeworkers::{Bundle, EdgeWorkers};
+    use akamai_edgegrid::Network;
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("bundle.json"),
+        r#"{"edgeworker-version": "2.0.0"}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("main.js"),
+        "export function onClientRequest(request) {}",
+    )
+    .unwrap();
+    let bundle = Bundle::from_dir(dir.path()).unwrap();
+
+    let mut server = mockito::Server::new_async().await;
+    let upload = server.mock("POST", "/edgeworkers/v1/ids/42/versions")
+        .match_header("content-type", "application/gzip")
+        .match_header("authorization", mockito::Matcher::Regex(r"^EG1-HMAC-SHA256 ".to_string()))
+        .match_body(bundle.archive().to_vec())
+        .with_status(201)
+        .with_body(r#"{"edgeWorkerId": 42, "version": "2.0.0", "checksum": "abc", "sequenceNumber": 3, "createdTime": "2026-10-18T10:00:00Z"}"#)
+        .create_async()
+        .await;
+    let _activations = server.mock("GET", "/edgeworkers/v1/ids/42/activations")
+        .with_status(200)
+        .with_body(r#"{"activations": [
+            {"activationId": 10, "edgeWorkerId": 42, "version": "1.0.0", "network": "STAGING", "status": "COMPLETE", "createdTime": "2026-10-01T00:00:00Z"},
+            {"activationId": 12, "edgeWorkerId": 42, "version": "2.0.0", "network": "STAGING", "status": "COMPLETE", "createdTime": "2026-10-18T10:05:00Z"},
+            {"activationId": 11, "edgeWorkerId": 42, "version": "1.1.0", "network": "PRODUCTION", "status": "COMPLETE", "createdTime": "2026-10-10T00:00:00Z"},
+            {"activationId": 13, "edgeWorkerId": 42, "version": "2.0.0", "network": "STAGING", "status": "ABORTED", "createdTime": "2026-10-18T11:00:00Z"}
+        ]}"#)
+        .create_async()
+        .await;
+    let rollback = server.mock("POST", "/edgeworkers/v1/ids/42/activations")
+        .match_body(mockito::Matcher::Json(serde_json::json!({"network": "STAGING", "version": "1.0.0"})))
+        .with_status(201)
+        .with_body(r#"{"activationId": 14, "edgeWorkerId": 42, "version": "1.0.0", "network": "STAGING", "status": "PRESUBMIT"}"#)
+        .create_async()
+        .await;
+    let _complete = server.mock("GET", "/edgeworkers/v1/ids/42/activations/14")
+        .with_status(200)
+        .with_body(r#"{"activationId": 14, "edgeWorkerId": 42, "version": "1.0.0", "network": "STAGING", "status": "COMPLETE"}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let edgeworkers = EdgeWorkers::new(EdgeGridClient::new(config).unwrap());
+
+    let version = edgeworkers.upload(42, &bundle).await.unwrap();
+    assert_eq!(version.version, "2.0.0");
+    upload.assert_async().await;
+
+    let activation = edgeworkers.rollback(42, Network::Staging).await.unwrap();
+    rollback.assert_async().await;
+    let activation = edgeworkers
+        .activation_operation(42, activation.activation_id)
+        .interval(Duration::from_millis(1), Duration::from_millis(5))
+        .wait()
+        .await
+        .unwrap();
+    assert_eq!(activation.version, "1.0.0");
+
+    let err = edgeworkers
+        .rollback(42, Network::Production)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no earlier version"));
+}
+
+#[tokio::test]
+async fn test_appsec_export_diff_and_match_targets() {
+    use akamai_edgegrid::appsec::{AppSec, ChangeKind};
+
+    let mut server = mockito::Server::new_async().await;
+    let _v7 = server.mock("GET", "/appsec/v1/export/configs/12345/versions/7")
+        .with_status(200)
+        .with_body(r#"{"configId": 12345, "version": 7, "createDate": "2026-10-01T00:00:00Z",
+            "securityPolicies": [{"id": "web1_111", "name": "Web"}, {"id": "api1_222", "name": "API"}],
+            "ratePolicies": [{"id": 9, "name": "Login", "averageThreshold": 10}]}"#)
+        .create_async()
+        .await;
+    let _v8 = server.mock("GET", "/appsec/v1/export/configs/12345/versions/8")
+        .with_status(200)
+        .with_body(r#"{"ratePolicies": [{"averageThreshold": 5, "name": "Login", "id": 9}],
+            "securityPolicies": [{"name": "API", "id": "api1_222"}, {"name": "Web", "id": "web1_111"}],
+            "version": 8, "configId": 12345, "createDate": "2026-10-18T00:00:00Z"}"#)
+        .create_async()
+        .await;
+    let _targets = server.mock("GET", "/appsec/v1/configs/12345/versions/8/match-targets")
+        .with_status(200)
+        .with_body(r#"{"matchTargets": {
+            "websiteTargets": [{"targetId": 2, "type": "website", "sequence": 2, "securityPolicy": {"policyId": "web1_111"}, "hostnames": ["www.example.com"]}],
+            "apiTargets": [{"targetId": 1, "type": "api", "sequence": 1, "securityPolicy": {"policyId": "api1_222"}, "apis": [{"id": 77}]}]
+        }}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let appsec = AppSec::new(EdgeGridClient::new(config).unwrap());
+
+    let old = appsec.export(12345, 7).await.unwrap();
+    let new = appsec.export(12345, 8).await.unwrap();
+    assert!(!old.to_json().contains("createDate"));
+
+    let changes = old.diff(&new);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].path, "/ratePolicies[id=9]/averageThreshold");
+    assert_eq!(
+        changes[0].kind,
+        ChangeKind::Changed {
+            old: serde_json::json!(10),
+            new: serde_json::json!(5)
+        }
+    );
+    assert_eq!(changes[1].to_string(), "~ /version: 7 -> 8");
+
+    let targets = appsec.match_targets(12345, 8).await.unwrap();
+    let order: Vec<_> = targets
+        .iter()
+        .map(|target| target.target_type.as_str())
+        .collect();
+    assert_eq!(order, ["api", "website"]);
+    assert_eq!(targets[0].extra["apis"][0]["id"], 77);
+}
+
+#[tokio::test]
+async fn test_cps_change_inputs_use_versioned_media_types() {
+    use akamai_edgegrid::cps::{CertificateUpload, Cps, InputType};
+    use std::time::Duration;
+
+    let mut server = mockito::Server::new_async().await;
+    let change = "/cps/v2/enrollments/10000/changes/10001";
+    let _running = server.mock("GET", change)
+        .match_header("accept", "application/vnd.akamai.cps.change.v2+json")
+        .with_status(200)
+        .with_body(r#"{"statusInfo": {"status": "coordinate-domain-validation", "state": "running", "description": "Validating"}, "allowedInput": []}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let _awaiting = server.mock("GET", change)
+        .match_header("accept", "application/vnd.akamai.cps.change.v2+json")
+        .with_status(200)
+        .with_body(r#"{"statusInfo": {"status": "wait-upload-third-party", "state": "awaiting-input", "description": "Waiting for the certificate"},
+            "allowedInput": [{"type": "third-party-csr", "requiredToProceed": true,
+                "info": "/cps/v2/enrollments/10000/changes/10001/input/info/third-party-csr",
+                "update": "/cps/v2/enrollments/10000/changes/10001/input/update/third-party-cert-and-trust-chain"}]}"#)
+        .create_async()
+        .await;
+    let csrs = server.mock("GET", format!("{}/input/info/third-party-csr", change).as_str())
+        .match_header("accept", "application/vnd.akamai.cps.csr.v2+json")
+        .with_status(200)
+        .with_body(r#"{"csrs": [{"csr": "-----BEGIN CERTIFICATE REQUEST-----\nMIIB\n-----END CERTIFICATE REQUEST-----", "keyAlgorithm": "RSA"}]}"#)
+        .create_async()
+        .await;
+    let upload = server.mock("POST", format!("{}/input/update/third-party-cert-and-trust-chain", change).as_str())
+        .match_header("content-type", "application/vnd.akamai.cps.certificate-and-trust-chain.v2+json")
+        .match_header("accept", "application/vnd.akamai.cps.change-id.v1+json")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "certificatesAndTrustChains": [{"certificate": "CERT", "trustChain": "CHAIN", "keyAlgorithm": "RSA"}]
+        })))
+        .with_status(200)
+        .with_body(r#"{"change": "/cps/v2/enrollments/10000/changes/10001"}"#)
+        .create_async()
+        .await;
+    let acknowledge = server
+        .mock(
+            "POST",
+            format!("{}/input/update/post-verification-warnings-ack", change).as_str(),
+        )
+        .match_header(
+            "content-type",
+            "application/vnd.akamai.cps.acknowledgement.v1+json",
+        )
+        .match_body(mockito::Matcher::Json(
+            serde_json::json!({"acknowledgement": "acknowledge"}),
+        ))
+        .with_status(200)
+        .with_body(r#"{"change": "/cps/v2/enrollments/10000/changes/10001"}"#)
+        .create_async()
+        .await;
+
+    let config = EdgeGridConfig::new(
+        "test-client-token".to_string(),
+        "test-client-secret".to_string(),
+        "test-access-token".to_string(),
+        server.url(),
+    );
+    let cps = Cps::new(EdgeGridClient::new(config).unwrap());
+
+    let status = cps
+        .change_operation(10000, 10001)
+        .interval(Duration::from_millis(1), Duration::from_millis(5))
+        .wait()
+        .await
+        .unwrap();
+    assert!(status.requires_input(InputType::ThirdPartyCsr));
+
+    let csr = cps.third_party_csrs(10000, 10001).await.unwrap();
+    assert_eq!(csr[0].key_algorithm, "RSA");
+    csrs.assert_async().await;
+
+    cps.upload_certificate(
+        10000,
+        10001,
+        &[CertificateUpload {
+            certificate: "CERT".into(),
+            trust_chain: Some("CHAIN".into()),
+            key_algorithm: "RSA".into(),
+        }],
+    )
+    .await
+    .unwrap();
+    upload.assert_async().await;
+
+    cps.acknowledge(10000, 10001, InputType::PostVerificationWarnings)
+        .await
+        .unwrap();
+    acknowledge.assert_async().await;
+    assert!(cps
+        .acknowledge(10000, 10001, InputType::ThirdPartyCsr)
+        .await
+        .is_err());
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_fake_akamai_accepts_requests_signed_by_the_client() {
+    use akamai_edgegrid::ccu::{FastPurge, PurgeObjects};
+    use akamai_edgegrid::testing::{FakeAkamai, FakeResponse};
+    use akamai_edgegrid::Network;
+    use reqwest::Method;
+
+    let server = FakeAkamai::start().await.unwrap();
+    server.route(
+        Method::POST,
+        "/ccu/v3/invalidate/url/{network}",
+        |request| {
+            let body: serde_json::Value = request.json().unwrap();
+            FakeResponse::json(
+                201,
+                serde_json::json!({
+                    "httpStatus": 201,
+                    "purgeId": "p-1",
+                    "estimatedSeconds": 5,
+                    "detail": format!("{} objects", body["objects"].as_array().unwrap().len()),
+                }),
+            )
+        },
+    );
+    server.json(Method::POST, "/test/empty", 200, serde_json::json!({}));
+    server.problem(
+        Method::DELETE,
+        "/test/forbidden",
+        403,
+        "Forbidden",
+        "Not allowed",
+    );
+
+    let client = server.client().unwrap();
+    let purges = FastPurge::new(client.clone())
+        .invalidate(
+            Network::Staging,
+            PurgeObjects::urls(["https://www.example.com/"]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(purges[0].purge_id, "p-1");
+
+    // Explicitly empty and absent POST bodies are both signed correctly
+    let empty = client
+        .post("/test/empty")
+        .body(Vec::new())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(empty.status(), 200);
+    let absent = client.post("/test/empty").send().await.unwrap();
+    assert_eq!(absent.status(), 200);
+
+    let forbidden = client.delete("/test/forbidden").send().await.unwrap();
+    assert_eq!(forbidden.status(), 403);
+    assert_eq!(
+        forbidden.headers()["content-type"],
+        "application/problem+json"
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests.iter().all(|r| r.authenticated), "{:?}", requests);
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_cli_prints_only_the_body_with_environment_credentials() {
+    let mut server = mockito::Server::new_async().await;
+    let groups = server
+        .mock("GET", "/papi/v1/groups")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("client_token=env-client-token".to_string()),
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"groups": []}"#)
+        .create_async()
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".edgerc");
+    std::fs::write(
+        &path,
+        "[clienv]\nhost = akab-unused.luna.akamaiapis.net\nclient_token = file-client-token\nclient_secret = file-client-secret\naccess_token = file-access-token\n",
+    )
+    .unwrap();
+
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_akamai-edgegrid"))
+        .arg("--edgerc")
+        .arg(&path)
+        .args(["--section", "clienv", "--raw", "/papi/v1/groups"])
+        .env("AKAMAI_CLIENV_HOST", server.url())
+        .env("AKAMAI_CLIENV_CLIENT_TOKEN", "env-client-token")
+        .env("AKAMAI_CLIENV_CLIENT_SECRET", "env-client-secret")
+        .env("AKAMAI_CLIENV_ACCESS_TOKEN", "env-access-token")
+        .output()
+        .await
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"groups\": []}\n"
+    );
+    groups.assert_async().await;
+}
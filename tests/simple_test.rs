@@ -11,7 +11,7 @@ fn test_config_validation() {
         "token".to_string(),
         "host".to_string(),
     );
-    
+
     let result = EdgeGridClient::new(config);
     assert!(result.is_err());
 }
@@ -24,7 +24,7 @@ fn test_config_validation_with_empty_secret() {
         "token".to_string(),
         "host".to_string(),
     );
-    
+
     let result = EdgeGridClient::new(config);
     assert!(result.is_err());
 }
@@ -37,7 +37,7 @@ fn test_valid_config() {
         "test-access-token".to_string(),
         "https://test.luna.akamaiapis.net".to_string(),
     );
-    
+
     let result = EdgeGridClient::new(config);
     assert!(result.is_ok());
 }
@@ -55,9 +55,9 @@ fn test_edgerc_parsing() {
     writeln!(file, "client_token = test-client").unwrap();
 
     let config = EdgeGridConfig::from_edgerc(file.path(), "default").unwrap();
-    assert_eq!(config.client_token, "test-client");
-    assert_eq!(config.client_secret, "test-secret");
-    assert_eq!(config.access_token, "test-access");
+    assert_eq!(config.client_token.expose(), "test-client");
+    assert_eq!(config.client_secret.expose(), "test-secret");
+    assert_eq!(config.access_token.expose(), "test-access");
     assert_eq!(config.host, "https://test.luna.akamaiapis.net");
 }
 
@@ -76,8 +76,8 @@ fn test_edgerc_parsing_with_comments() {
     writeln!(file, "; Another comment").unwrap();
 
     let config = EdgeGridConfig::from_edgerc(file.path(), "default").unwrap();
-    assert_eq!(config.client_token, "test-client");
-    assert_eq!(config.client_secret, "test-secret");
-    assert_eq!(config.access_token, "test-access");
+    assert_eq!(config.client_token.expose(), "test-client");
+    assert_eq!(config.client_secret.expose(), "test-secret");
+    assert_eq!(config.access_token.expose(), "test-access");
     assert_eq!(config.host, "https://test.luna.akamaiapis.net");
-}
\ No newline at end of file
+}